@@ -0,0 +1,239 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// why an archive couldn't be unpacked, with what's needed to look into it
+#[derive(Debug)]
+pub enum UnpackError {
+  UnsupportedArchive(String),
+  Extract {
+    archive: PathBuf,
+    status: ExitStatus,
+    stderr: String,
+  },
+  MissingExecutable {
+    archive: PathBuf,
+    path: PathBuf,
+  },
+}
+
+impl std::fmt::Display for UnpackError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      UnpackError::UnsupportedArchive(ext) => write!(f, "unsupported archive type: '{}'", ext),
+      UnpackError::Extract {
+        archive,
+        status,
+        stderr,
+      } => {
+        write!(f, "failed to extract {} ({})", archive.display(), status)?;
+        if !stderr.trim().is_empty() {
+          write!(f, ":\n{}", stderr.trim())?;
+        }
+        Ok(())
+      }
+      UnpackError::MissingExecutable { archive, path } => write!(
+        f,
+        "{} was extracted but {} is not in it",
+        archive.display(),
+        path.display()
+      ),
+    }
+  }
+}
+
+impl std::error::Error for UnpackError {}
+
+/// unpack the archive written into `dir`, the executable is expected at `exe` afterwards
+type Unpack = fn(archive: &Path, dir: &Path, exe: &Path) -> Result<()>;
+
+/// how the archives of a format are unpacked, by the suffix of the name they're unpacked from
+pub struct Handler {
+  pub suffix: &'static str,
+  unpack: Unpack,
+}
+
+impl Handler {
+  pub fn unpack(&self, archive: &Path, dir: &Path, exe: &Path) -> Result<()> {
+    (self.unpack)(archive, dir, exe)
+  }
+}
+
+///
+/// the formats dvm unpacks, the first suffix matching the archive name wins, so `.tar.gz` comes before `.gz`.
+/// a format a mirror repacks the releases in is added here, the install pipeline stays as it is.
+/// names without an extension are plain executables.
+const HANDLERS: &[Handler] = &[
+  Handler {
+    suffix: ".zip",
+    unpack: unzip,
+  },
+  Handler {
+    suffix: ".tar.gz",
+    unpack: untar,
+  },
+  Handler {
+    suffix: ".tgz",
+    unpack: untar,
+  },
+  Handler {
+    suffix: ".tar.zst",
+    unpack: untar_zstd,
+  },
+  // the releases before v0.36.0, their unix executables are gzipped alone
+  Handler {
+    suffix: ".gz",
+    unpack: gunzip,
+  },
+  Handler {
+    suffix: ".zst",
+    unpack: unzstd,
+  },
+  Handler {
+    suffix: ".exe",
+    unpack: raw,
+  },
+];
+
+const RAW: Handler = Handler {
+  suffix: "",
+  unpack: raw,
+};
+
+/// the handler of an archive name, an unsupported format is named by its extension
+pub fn handler(archive_file: &str) -> Result<&'static Handler, UnpackError> {
+  let Some(ext) = Path::new(archive_file).extension() else {
+    return Ok(&RAW);
+  };
+  HANDLERS
+    .iter()
+    .find(|it| archive_file.ends_with(it.suffix))
+    .ok_or_else(|| UnpackError::UnsupportedArchive(ext.to_string_lossy().into_owned()))
+}
+
+/// run an extracting command, its output is of interest only when it fails
+fn run(command: &mut Command, archive: &Path) -> Result<()> {
+  let output = command.stdout(Stdio::null()).output()?;
+  if !output.status.success() {
+    return Err(
+      UnpackError::Extract {
+        archive: archive.to_path_buf(),
+        status: output.status,
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+      }
+      .into(),
+    );
+  }
+  Ok(())
+}
+
+/// the name of the archive in `dir`, the commands run in it so they never see wildcards from the user's home path
+fn file_name(archive: &Path) -> &std::ffi::OsStr {
+  archive.file_name().unwrap_or(archive.as_os_str())
+}
+
+fn unzip(archive: &Path, dir: &Path, _exe: &Path) -> Result<()> {
+  if cfg!(windows) {
+    // paths are handed over through the environment, so no quoting is involved
+    return run(
+      Command::new("powershell.exe")
+        .arg("-NoLogo")
+        .arg("-NoProfile")
+        .arg("-NonInteractive")
+        .arg("-Command")
+        .arg(
+          "& {
+            trap { $host.ui.WriteErrorLine($_.Exception); exit 1 }
+            Add-Type -AssemblyName System.IO.Compression.FileSystem
+            [System.IO.Compression.ZipFile]::ExtractToDirectory(
+              $env:DVM_UNPACK_ARCHIVE,
+              $env:DVM_UNPACK_DESTINATION
+            );
+          }",
+        )
+        .env("DVM_UNPACK_ARCHIVE", archive)
+        .env("DVM_UNPACK_DESTINATION", dir),
+      archive,
+    );
+  }
+  run(
+    Command::new("unzip").current_dir(dir).arg("-o").arg(file_name(archive)),
+    archive,
+  )
+}
+
+/// `tar` ships with windows 10 as well
+fn untar(archive: &Path, dir: &Path, _exe: &Path) -> Result<()> {
+  run(
+    Command::new("tar").current_dir(dir).arg("-xzf").arg(file_name(archive)),
+    archive,
+  )
+}
+
+fn untar_zstd(archive: &Path, dir: &Path, _exe: &Path) -> Result<()> {
+  let tar = archive.with_extension("");
+  zstd::stream::copy_decode(fs::File::open(archive)?, fs::File::create(&tar)?)?;
+  let result = run(
+    Command::new("tar").current_dir(dir).arg("-xf").arg(file_name(&tar)),
+    archive,
+  );
+  fs::remove_file(tar).ok();
+  result
+}
+
+fn gunzip(archive: &Path, dir: &Path, _exe: &Path) -> Result<()> {
+  if cfg!(windows) {
+    return Err(UnpackError::UnsupportedArchive("gz".to_string()).into());
+  }
+  run(
+    Command::new("gzip").current_dir(dir).arg("-df").arg(file_name(archive)),
+    archive,
+  )
+}
+
+/// a single zstd compressed executable
+fn unzstd(archive: &Path, _dir: &Path, exe: &Path) -> Result<()> {
+  zstd::stream::copy_decode(fs::File::open(archive)?, fs::File::create(exe)?)?;
+  fs::remove_file(archive)?;
+  Ok(())
+}
+
+/// the executable itself, moved into place unless it's named like it already
+fn raw(archive: &Path, _dir: &Path, exe: &Path) -> Result<()> {
+  if archive != exe {
+    fs::rename(archive, exe)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_handler() {
+    let suffix = |name: &str| handler(name).map(|it| it.suffix).map_err(|it| it.to_string());
+    assert_eq!(suffix("deno.zip"), Ok(".zip"));
+    assert_eq!(suffix("deno.tar.gz"), Ok(".tar.gz"));
+    assert_eq!(suffix("deno.gz"), Ok(".gz"));
+    assert_eq!(suffix("deno.tar.zst"), Ok(".tar.zst"));
+    assert_eq!(suffix("deno"), Ok(""));
+    assert_eq!(suffix("deno.tar.xz"), Err("unsupported archive type: 'xz'".to_string()));
+  }
+
+  #[test]
+  fn test_unzstd() {
+    let dir = std::env::temp_dir().join(format!("dvm-unzstd-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let archive = dir.join("deno.zst");
+    fs::write(&archive, zstd::encode_all(&b"#!/bin/sh\n"[..], 3).unwrap()).unwrap();
+    handler("deno.zst")
+      .unwrap()
+      .unpack(&archive, &dir, &dir.join("deno"))
+      .unwrap();
+    assert_eq!(fs::read(dir.join("deno")).unwrap(), b"#!/bin/sh\n");
+    assert!(!archive.exists());
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
@@ -0,0 +1,115 @@
+use crate::consts::DVM_ARCHIVES_PATH_PREFIX;
+use crate::interrupt;
+use crate::output;
+use crate::source;
+use crate::utils::dvm_cache_root;
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the download cache
+pub fn archives_root() -> PathBuf {
+  dvm_cache_root().join(DVM_ARCHIVES_PATH_PREFIX)
+}
+
+///
+/// downloaded archives are stored once under their sha256 in `archives/sha256/<hash>`,
+/// and `archives/index/v<version>/<asset>` records which hash an asset has.
+fn blob_path(hash: &str) -> PathBuf {
+  archives_root().join("sha256").join(hash)
+}
+
+fn index_path(version: &Version, asset: &str) -> PathBuf {
+  archives_root().join("index").join(format!("v{}", version)).join(asset)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+  Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// the hash of a `sha256sum` line, `<hash>  <file>`
+pub fn parse_sha256(text: &str) -> Option<String> {
+  let hash = text.split_whitespace().next()?.to_lowercase();
+  (hash.len() == 64 && hash.chars().all(|it| it.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// write through a temporary file, so an interrupted write never looks cached
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+  fs::create_dir_all(path.parent().unwrap())?;
+  let mut partial = path.file_name().unwrap().to_os_string();
+  partial.push(".partial");
+  let partial = path.with_file_name(partial);
+  let _staged = interrupt::stage(&partial);
+  fs::write(&partial, data)?;
+  fs::rename(partial, path)?;
+  Ok(())
+}
+
+/// the cached archive of an asset, verified against its hash, a corrupted one is dropped
+pub fn lookup(version: &Version, asset: &str) -> Option<Vec<u8>> {
+  let hash = fs::read_to_string(index_path(version, asset)).ok()?;
+  let blob = blob_path(hash.trim());
+  let data = fs::read(&blob).ok()?;
+  if sha256_hex(&data) != hash.trim() {
+    output::warn(t!("Dropping the corrupted cache of {}", asset));
+    fs::remove_file(blob).ok();
+    return None;
+  }
+  Some(data)
+}
+
+/// the hash recorded for an asset, without reading the archive
+pub fn cached_hash(version: &Version, asset: &str) -> Option<String> {
+  let hash = fs::read_to_string(index_path(version, asset)).ok()?;
+  blob_path(hash.trim()).exists().then(|| hash.trim().to_string())
+}
+
+pub fn is_cached(version: &Version, asset: &str) -> bool {
+  fs::read_to_string(index_path(version, asset))
+    .map(|hash| blob_path(hash.trim()).exists())
+    .unwrap_or(false)
+}
+
+pub fn store(version: &Version, asset: &str, data: &[u8]) -> Result<()> {
+  let hash = sha256_hex(data);
+  let blob = blob_path(&hash);
+  if !blob.exists() {
+    write_atomic(&blob, data)?;
+  }
+  write_atomic(&index_path(version, asset), hash.as_bytes())
+}
+
+///
+/// a release asset from the cache, downloaded and cached if it's not there,
+/// `None` if the release doesn't have it.
+pub async fn fetch(registry: &str, version: &Version, target: &str, asset: &str) -> Result<Option<Vec<u8>>> {
+  if let Some(data) = lookup(version, asset) {
+    output::info(t!("Using the cached {}", asset.bright_black()));
+    return Ok(Some(data));
+  }
+
+  let Some(data) = source::fetch(&source::release_urls(registry, version, target, asset)).await? else {
+    return Ok(None);
+  };
+  // a failure to cache shouldn't fail the download
+  if let Err(err) = store(version, asset, &data) {
+    output::warn(t!("Failed to cache {}: {}", asset, err));
+  }
+  Ok(Some(data))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sha256_hex() {
+    assert_eq!(
+      sha256_hex(b"deno"),
+      "e872e7bd2ae6abcf13a4c834029a342c882c1162ebf77b6720968b2000312ffb"
+    );
+  }
+}
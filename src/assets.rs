@@ -0,0 +1,139 @@
+use semver::{Version, VersionReq};
+
+/// how a release asset is packed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+  Zip,
+  /// the executable alone, gzipped
+  Gzip,
+}
+
+impl Format {
+  /// the name the archive is unpacked from, its extension tells `unpack` how
+  pub fn archive_file(self) -> &'static str {
+    match self {
+      Format::Zip => "deno.zip",
+      Format::Gzip => "deno.gz",
+    }
+  }
+}
+
+/// the deno asset of a release for a target
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Asset {
+  pub name: String,
+  pub format: Format,
+  /// the target the asset is built for, the x86_64 one for apple silicon before v1.6.0
+  pub target: String,
+}
+
+/// a version range, a target or `*`, and the asset name with its format
+type Rule = (&'static str, &'static str, Option<(&'static str, Format)>);
+
+///
+/// how the deno asset of a target was named over the years, the first rule matching both
+/// the version and the target wins, `None` means that release has no build for the target:
+///   before v0.36.0 the assets were named after os and arch and the unix ones were gzipped,
+///   apple silicon builds exist since v1.6.0, older releases run the x86_64 build through rosetta,
+///   linux arm64 builds exist since v1.41.0.
+const RULES: &[Rule] = &[
+  (
+    "<0.36.0",
+    "x86_64-unknown-linux-gnu",
+    Some(("deno_linux_x64.gz", Format::Gzip)),
+  ),
+  (
+    "<0.36.0",
+    "x86_64-apple-darwin",
+    Some(("deno_osx_x64.gz", Format::Gzip)),
+  ),
+  (
+    "<0.36.0",
+    "aarch64-apple-darwin",
+    Some(("deno_osx_x64.gz", Format::Gzip)),
+  ),
+  (
+    "<0.36.0",
+    "x86_64-pc-windows-msvc",
+    Some(("deno_win_x64.zip", Format::Zip)),
+  ),
+  (
+    "<1.6.0",
+    "aarch64-apple-darwin",
+    Some(("deno-x86_64-apple-darwin.zip", Format::Zip)),
+  ),
+  ("<1.41.0", "aarch64-unknown-linux-gnu", None),
+  ("*", "*", Some(("deno-{target}.zip", Format::Zip))),
+];
+
+/// prereleases of a version follow the rules of the version
+fn matches(range: &str, version: &Version) -> bool {
+  let version = Version::new(version.major, version.minor, version.patch);
+  VersionReq::parse(range).map(|it| it.matches(&version)).unwrap_or(false)
+}
+
+/// the target of an asset named `deno-<target>.zip` or `denort-<target>.zip`, also of the files next to it
+pub fn target_of(asset: &str) -> Option<&str> {
+  let rest = asset.strip_prefix("denort-").or_else(|| asset.strip_prefix("deno-"))?;
+  rest.split_once(".zip").map(|(target, _)| target)
+}
+
+/// the deno asset of `version` for `target`, `None` if that release has no build for it
+pub fn deno(version: &Version, target: &str) -> Option<Asset> {
+  let (_, _, asset) = RULES
+    .iter()
+    .find(|(range, triple, _)| (*triple == "*" || *triple == target) && matches(range, version))?;
+  asset.map(|(name, format)| {
+    let name = name.replace("{target}", target);
+    Asset {
+      target: target_of(&name).unwrap_or(target).to_string(),
+      name,
+      format,
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_target_of() {
+    assert_eq!(target_of("deno-aarch64-apple-darwin.zip"), Some("aarch64-apple-darwin"));
+    assert_eq!(
+      target_of("denort-x86_64-pc-windows-msvc.zip.sha256sum"),
+      Some("x86_64-pc-windows-msvc")
+    );
+    assert_eq!(target_of("deno_linux_x64.gz"), None);
+  }
+
+  fn name(version: &str, target: &str) -> Option<String> {
+    deno(&Version::parse(version).unwrap(), target).map(|it| it.name)
+  }
+
+  #[test]
+  fn test_deno() {
+    assert_eq!(
+      name("1.28.0", "x86_64-unknown-linux-gnu").as_deref(),
+      Some("deno-x86_64-unknown-linux-gnu.zip")
+    );
+    let old = deno(&Version::parse("0.35.0").unwrap(), "x86_64-unknown-linux-gnu").unwrap();
+    assert_eq!((old.name.as_str(), old.format), ("deno_linux_x64.gz", Format::Gzip));
+    assert_eq!(
+      name("1.5.4", "aarch64-apple-darwin").as_deref(),
+      Some("deno-x86_64-apple-darwin.zip")
+    );
+    let rosetta = deno(&Version::parse("1.5.4").unwrap(), "aarch64-apple-darwin").unwrap();
+    assert_eq!(rosetta.target, "x86_64-apple-darwin");
+    assert_eq!(old.target, "x86_64-unknown-linux-gnu");
+    assert_eq!(
+      name("1.6.0", "aarch64-apple-darwin").as_deref(),
+      Some("deno-aarch64-apple-darwin.zip")
+    );
+    assert_eq!(name("1.40.0", "aarch64-unknown-linux-gnu"), None);
+    assert_eq!(
+      name("1.41.0-rc.1", "aarch64-unknown-linux-gnu").as_deref(),
+      Some("deno-aarch64-unknown-linux-gnu.zip")
+    );
+  }
+}
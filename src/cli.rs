@@ -1,15 +1,50 @@
 use std::env;
+use std::path::PathBuf;
 
 use clap::Parser;
 use clap_complete::Shell;
-use clap_derive::{Parser, Subcommand};
+use clap_derive::{Parser, Subcommand, ValueEnum};
 
 use crate::commands;
-use crate::consts::{AFTER_HELP, COMPLETIONS_HELP};
+use crate::consts::{AFTER_HELP, COMPLETIONS_HELP, CURRENT_HELP};
 use crate::meta::DvmMeta;
+use crate::output;
+
+/// split the global `--dvm-dir` option from the arguments,
+/// arguments after `exec` are given to deno and are left untouched.
+fn split_dvm_dir(args: Vec<String>) -> (Option<String>, Vec<String>) {
+  let mut dvm_dir = None;
+  let mut rest = Vec::with_capacity(args.len());
+  let mut iter = args.into_iter();
+  while let Some(arg) = iter.next() {
+    if rest.len() == 2 && rest[1] == "exec" {
+      rest.push(arg);
+      rest.extend(iter);
+      break;
+    }
+    if arg == "--dvm-dir" {
+      dvm_dir = iter.next();
+    } else if let Some(dir) = arg.strip_prefix("--dvm-dir=") {
+      dvm_dir = Some(dir.to_string());
+    } else {
+      rest.push(arg);
+    }
+  }
+  (dvm_dir, rest)
+}
+
+///
+/// `--dvm-dir` has to take effect before the metadata is loaded,
+/// so it's handled ahead of clap by exporting it as `DVM_DIR`,
+/// which is also inherited by the deno processes spawned by dvm.
+pub fn init_dvm_dir() {
+  if let (Some(dvm_dir), _) = split_dvm_dir(env::args().collect()) {
+    env::set_var("DVM_DIR", dvm_dir);
+  }
+}
 
 pub fn cli_parse(meta: &mut DvmMeta) -> Result<Cli, ()> {
-  let args: Vec<String> = env::args().collect();
+  let (_, args) = split_dvm_dir(env::args().collect());
   if args.len() > 1 && args[1] == "exec" {
     if args.len() > 2 {
       let version: Option<String>;
@@ -19,7 +54,7 @@ pub fn cli_parse(meta: &mut DvmMeta) -> Result<Cli, ()> {
           version = Some(args[3].clone());
           exec_args = args[4..].to_vec();
         } else {
-          eprintln!("A version should be followed after {}", args[2]);
+          eprintln!("{}", t!("A version should be followed after {}", args[2]));
           std::process::exit(1)
         }
       } else if args[2].starts_with("--version=") || args[2].starts_with("-V=") {
@@ -34,7 +69,21 @@ pub fn cli_parse(meta: &mut DvmMeta) -> Result<Cli, ()> {
         version = None;
         exec_args = args[2..].to_vec();
       }
-      commands::exec::exec(meta, version, exec_args).unwrap();
+      // `#!/usr/bin/env -S dvm exec --` runs the script with the version pinned next to it,
+      // `--` only means so when a script follows, `--shebang` always does.
+      let result = match exec_args.split_first() {
+        Some((flag, script_args))
+          if flag == "--shebang" || (flag == "--" && commands::exec::is_script(script_args)) =>
+        {
+          commands::exec::exec_script(meta, version, script_args.to_vec())
+        }
+        Some((flag, deno_args)) if flag == "--" => commands::exec::exec(meta, version, deno_args.to_vec()),
+        _ => commands::exec::exec(meta, version, exec_args),
+      };
+      if let Err(err) = result {
+        output::report(err);
+        std::process::exit(1);
+      }
     } else {
       // TODO(CGQAQ): print help
     }
@@ -49,10 +98,67 @@ pub fn cli_parse(meta: &mut DvmMeta) -> Result<Cli, ()> {
 #[clap(after_help = AFTER_HELP)]
 #[clap(propagate_version = true)]
 pub struct Cli {
+  #[clap(
+    long,
+    global = true,
+    value_name = "DIR",
+    help = "The dvm root directory, overrides the DVM_DIR environment variable"
+  )]
+  pub dvm_dir: Option<String>,
+
+  #[clap(
+    long,
+    global = true,
+    value_name = "RATE",
+    value_parser = crate::http::parse_rate,
+    help = "Limit the download rate, e.g. 500K or 2M"
+  )]
+  pub limit_rate: Option<u64>,
+
+  #[clap(long, global = true, value_enum, default_value = "auto", help = "When to use colors")]
+  pub color: ColorChoice,
+
+  #[clap(
+    long,
+    global = true,
+    value_enum,
+    value_name = "FORMAT",
+    default_value = "human",
+    help = "How to report progress, `json` prints newline-delimited events to stdout"
+  )]
+  pub progress: ProgressFormat,
+
+  #[clap(
+    long,
+    global = true,
+    help = "Log every http request with its status and timing to stderr, credentials are redacted"
+  )]
+  pub trace_http: bool,
+
+  #[clap(
+    long,
+    global = true,
+    help = "Print the time spent resolving, downloading, verifying, extracting and activating"
+  )]
+  pub timings: bool,
+
   #[clap(subcommand)]
   pub command: Commands,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ColorChoice {
+  Auto,
+  Always,
+  Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProgressFormat {
+  Human,
+  Json,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
   #[clap(about = "Generate shell completions")]
@@ -60,33 +166,177 @@ pub enum Commands {
   Completions {
     #[arg(value_enum)]
     shell: Shell,
+    #[clap(
+      long,
+      help = "Write the script to the completion directory of the shell instead of printing it"
+    )]
+    install: bool,
   },
 
   #[clap(about = "Show dvm info.")]
   Info,
 
+  #[clap(about = "Print a path dvm uses, the dvm root by default")]
+  Root {
+    #[arg(value_enum, help = "The path to print")]
+    path: Option<DvmPath>,
+    #[clap(long, help = "Print all the paths as json")]
+    json: bool,
+  },
+
+  #[clap(about = "Print the directory of downloaded archives")]
+  CacheDir,
+
+  #[clap(about = "Print the shell hook that switches deno per project")]
+  Hook {
+    #[arg(value_enum)]
+    shell: HookShell,
+    #[clap(long, help = "Print the code to eval instead of how to set it up")]
+    eval: bool,
+  },
+
+  #[clap(about = "Print the environment dvm needs, e.g. the bin directory in PATH")]
+  Env {
+    #[clap(
+      long,
+      value_enum,
+      help = "The syntax to print, detected from the environment if not present"
+    )]
+    shell: Option<EnvShell>,
+  },
+
+  #[clap(about = "Write a shell plugin with the environment, the cd hook and the completions")]
+  ShellPlugin {
+    #[arg(value_enum)]
+    shell: PluginShell,
+    #[clap(
+      long,
+      help = "The directory to write the plugin to, `./dvm.fish` for fish, `./dvm` for the others by default"
+    )]
+    dir: Option<PathBuf>,
+  },
+
+  #[clap(about = "Print the bin directory of the deno the current project asks for, used by the shell hook")]
+  ResolveForCwd,
+
+  #[clap(about = "Show the active version without network access")]
+  #[clap(after_help=CURRENT_HELP)]
+  Current {
+    #[clap(
+      long,
+      help = "Print the version and a channel glyph on one line, nothing if none is active"
+    )]
+    prompt: bool,
+  },
+
   #[clap(about = "Install deno executable to the given version.")]
   #[clap(visible_aliases=&["i", "add"])]
   Install {
     #[clap(long, help = "Only install to local, but not use")]
     no_use: bool,
+    #[clap(long, help = "Export the installed deno to GITHUB_PATH and GITHUB_ENV")]
+    github_output: bool,
+    #[clap(long, help = "Also download the denort runtime used by `deno compile`")]
+    denort: bool,
+    #[clap(long, help = "Fail unless the archives are verified with `signature-public-key`")]
+    require_signature: bool,
+    #[clap(
+      long,
+      help = "Build the version from its source tarball with cargo, for targets without binaries"
+    )]
+    build_from_source: bool,
+    #[clap(
+      long,
+      value_name = "REPO",
+      conflicts_with_all = &["build_from_source", "dry_run"],
+      help = "Install a fork from the releases of a GitHub repository, `owner/name`, or a repository url"
+    )]
+    repo: Option<String>,
+    #[clap(
+      long,
+      value_name = "ARCH",
+      conflicts_with_all = &["build_from_source", "repo", "dry_run"],
+      help = "Install the build of another architecture next to the native one, `x64` or `arm64`"
+    )]
+    arch: Option<String>,
+    #[clap(
+      long,
+      conflicts_with_all = &["build_from_source", "repo", "arch", "dry_run"],
+      help = "Download the archive and verify its published checksum without installing it"
+    )]
+    verify_only: bool,
+    #[clap(
+      long,
+      value_name = "TARGET",
+      requires = "verify_only",
+      help = "The target to verify the archive of, this platform by default"
+    )]
+    target: Option<String>,
+    #[clap(long, help = "Print what would be installed without downloading it")]
+    dry_run: bool,
+    #[clap(long, requires = "dry_run", help = "Print the dry run plan as JSON")]
+    json: bool,
     #[clap(help = "The version to install")]
     version: Option<String>,
   },
 
+  #[clap(about = "Show the path of an installed deno executable")]
+  Which {
+    #[clap(long, help = "Show the path of the denort runtime instead")]
+    denort: bool,
+    #[clap(help = "The version, alias or semver range to look up")]
+    version: String,
+  },
+
+  #[clap(about = "Explain what needs an installed version, before uninstalling it")]
+  Why {
+    #[clap(help = "The version, alias or semver range to look up")]
+    version: String,
+  },
+
+  #[clap(about = "Show when each version was made active and where")]
+  History {
+    #[clap(long, short = 'n', help = "Show only the last N activations")]
+    limit: Option<usize>,
+  },
+
+  #[clap(about = "Go back to the previously active version and the mappings before the last upgrade")]
+  Rollback,
+
   #[clap(about = "List all installed versions")]
   #[clap(visible_aliases=&["ls", "ll", "la"])]
   List,
 
   #[clap(about = "List all released versions")]
   #[clap(visible_aliases=&["lr", "ls-remote"])]
-  ListRemote,
+  ListRemote {
+    #[clap(long, help = "Only list the releases of the LTS lines")]
+    lts: bool,
+  },
+
+  #[clap(about = "Search the released versions by a part of the version or a semver range")]
+  Search {
+    #[clap(help = "A part of the version such as 1.39 or rc, or a semver range")]
+    pattern: String,
+  },
 
   #[clap(about = "Uninstall a given version")]
   #[clap(visible_aliases=&["un", "unlink", "rm", "remove"])]
   Uninstall {
-    #[clap(help = "The version to install")]
+    #[clap(help = "The version to uninstall, `system` for the deno not managed by dvm")]
     version: Option<String>,
+    #[clap(
+      long,
+      help = "Remove a version that's active, the default or pinned by a project, or the system deno dvm never installed"
+    )]
+    force: bool,
+
+    #[clap(
+      long,
+      value_name = "ARCH",
+      help = "Remove only the build of another architecture installed with `install --arch`"
+    )]
+    arch: Option<String>,
   },
 
   #[clap(about = "Use a given version or a semver range or a alias to the range.")]
@@ -100,24 +350,69 @@ pub enum Commands {
       help = "Writing the version to the .dvmrc file of the current directory if present"
     )]
     local: bool,
+
+    #[clap(
+      long,
+      help = "Pin the version in the .dvmrc file of the current directory and keep it from `dvm clean`"
+    )]
+    save: bool,
+
+    #[clap(
+      long,
+      value_name = "ARCH",
+      help = "Use the build of another architecture, `x64` or `arm64`"
+    )]
+    arch: Option<String>,
   },
 
+  #[clap(about = "Register the current directory against a version")]
+  Link {
+    #[clap(help = "The version, semver range or alias, read from .dvmrc if not present")]
+    version: Option<String>,
+  },
+
+  #[clap(about = "List the linked projects and their versions")]
+  Projects,
+
   #[clap(about = "Set or unset an alias")]
   Alias {
     #[clap(subcommand)]
     command: AliasCommands,
   },
 
+  #[clap(about = "Set or unset environment variables injected into given versions")]
+  VersionEnv {
+    #[clap(subcommand)]
+    command: VersionEnvCommands,
+  },
+
   #[clap(about = "Activate Dvm")]
   Activate,
   #[clap(about = "Deactivate Dvm")]
-  Deactivate,
+  Deactivate {
+    #[clap(long, help = "Also forget the global default version in ~/.dvmrc")]
+    global: bool,
+  },
 
   #[clap(about = "Fixing dvm specific environment variables and other issues")]
-  Doctor,
+  Doctor {
+    #[clap(
+      long,
+      help = "Put dvm's deno before a deno dvm doesn't manage in PATH, e.g. one installed by homebrew or scoop"
+    )]
+    force_external: bool,
+  },
 
   #[clap(about = "Upgrade aliases to the latest version")]
   Upgrade {
+    #[clap(long, help = "Print what would be upgraded without downloading anything")]
+    dry_run: bool,
+    #[clap(long, requires = "dry_run", help = "Print the dry run plan as JSON")]
+    json: bool,
+    #[clap(long, conflicts_with = "alias", help = "Upgrade to the newest LTS release")]
+    lts: bool,
+    #[clap(long, help = "Move aliases to older versions when that's what they resolve to")]
+    allow_downgrade: bool,
     #[clap(help = "The alias to upgrade, upgrade all aliases if not present")]
     alias: Option<String>,
   },
@@ -131,16 +426,170 @@ pub enum Commands {
     deno_version: Option<String>,
   },
 
+  #[clap(about = "Move ~/.dvm over to the XDG base directories")]
+  MigrateLayout,
+
   #[clap(about = "Clean dvm cache")]
   Clean,
 
+  #[clap(about = "Compress the versions that haven't been used for a while")]
+  Compact {
+    #[clap(long, help = "Compress versions not used in this many days, 30 by default")]
+    days: Option<u64>,
+  },
+
+  #[clap(about = "Rebuild the dvm bin directory and validate the metadata")]
+  Rehash,
+
+  #[clap(about = "Download the latest stable into the cache without installing it")]
+  #[clap(hide = true)]
+  PrefetchLatest,
+
+  #[clap(about = "Download the runtimes of other targets for `deno compile --target`")]
+  PrefetchTargets {
+    #[clap(help = "The deno version to compile with")]
+    version: String,
+    #[clap(
+      long,
+      value_delimiter = ',',
+      required = true,
+      help = "Targets such as linux-x64,windows-x64"
+    )]
+    targets: Vec<String>,
+  },
+
+  #[clap(about = "Helpers for running dvm in CI pipelines")]
+  Ci {
+    #[clap(subcommand)]
+    command: CiCommands,
+  },
+
+  #[clap(about = "Point the VS Code Deno extension at the deno used by this project")]
+  Vscode {
+    #[clap(help = "The version, semver range or alias to use, read from .dvmrc if not present")]
+    version: Option<String>,
+  },
+
+  #[clap(about = "Answer version lookups of editor plugins as JSON-RPC over stdio")]
+  LspHelper,
+
+  #[clap(about = "Point an editor at the deno used by this project")]
+  Editor {
+    #[arg(value_enum)]
+    editor: Editor,
+    #[clap(help = "The version, semver range or alias to use, read from .dvmrc if not present")]
+    version: Option<String>,
+  },
+
+  #[clap(about = "Print a Dockerfile that installs the pinned deno with its checksum verified")]
+  Dockerfile {
+    #[clap(help = "The version, semver range or alias to install, read from .dvmrc if not present")]
+    version: Option<String>,
+    #[clap(long, help = "The linux target of the image, linux-x64 by default")]
+    target: Option<String>,
+    #[clap(long, help = "The sha256 of the archive, for releases that don't publish one")]
+    sha256: Option<String>,
+    #[clap(long, help = "Print only the instructions that install deno")]
+    snippet: bool,
+  },
+
+  #[clap(about = "Write a devcontainer feature that installs the pinned deno")]
+  Devcontainer {
+    #[clap(help = "The version, semver range or alias to install, read from .dvmrc if not present")]
+    version: Option<String>,
+    #[clap(long, help = "The directory of devcontainer.json, .devcontainer by default")]
+    dir: Option<PathBuf>,
+  },
+
+  #[clap(about = "Show the installed versions, disk usage and active configuration")]
+  Stats {
+    #[clap(long, help = "Print as json")]
+    json: bool,
+  },
+
+  #[clap(about = "Write or verify the dvm.lock of a project")]
+  Lock {
+    #[clap(subcommand)]
+    command: LockCommands,
+  },
+
+  #[clap(about = "Get or set dvm config")]
+  Config {
+    #[clap(subcommand)]
+    command: ConfigCommands,
+  },
+
+  #[clap(about = "Show or turn off the opt-in usage telemetry")]
+  Telemetry {
+    #[clap(subcommand)]
+    command: TelemetryCommands,
+  },
+
+  #[clap(about = "Let mise install deno through dvm and share its versions")]
+  Mise {
+    #[clap(subcommand)]
+    command: MiseCommands,
+  },
+
+  #[clap(about = "Print a proto plugin downloading deno from the dvm registry")]
+  ProtoPlugin {
+    #[clap(long, help = "Write the plugin to this file instead of printing it")]
+    output: Option<PathBuf>,
+  },
+
+  #[clap(about = "Print a software bill of materials of the installed deno binaries")]
+  Sbom {
+    #[clap(help = "The version, alias or semver range to describe, all installed versions if not present")]
+    version: Option<String>,
+    #[clap(long, value_enum, default_value = "cyclonedx", help = "The format of the document")]
+    format: SbomFormat,
+  },
+
+  #[clap(about = "Serve the download cache as a registry for other machines on the network")]
+  Serve {
+    #[clap(
+      long,
+      help = "The address to listen on, 127.0.0.1 by default, `0.0.0.0` to serve other machines"
+    )]
+    host: Option<String>,
+    #[clap(long, help = "The port to listen on, 8473 by default")]
+    port: Option<u16>,
+  },
+
+  #[clap(about = "Update dvm itself to the newest build of its channel")]
+  SelfUpdate {
+    #[clap(long, help = "Follow `stable` releases or `nightly` builds from now on")]
+    channel: Option<String>,
+  },
+
+  #[clap(about = "Advance the version in .dvmrc to the newest build of its channel")]
+  UpdatePin,
+
+  #[clap(about = "Print dvm's environment and the last command's log for a bug report, secrets redacted")]
+  Report {
+    #[clap(long, help = "Print it as JSON")]
+    json: bool,
+  },
+
   #[clap(about = "Change registry that dvm fetch from")]
+  #[clap(args_conflicts_with_subcommands = true)]
   Registry {
+    #[clap(subcommand)]
+    command: Option<RegistryCommands>,
     #[clap(help = "The registry to be set, `official`, `cn`, or url you desired")]
     registry: Option<String>,
   },
 }
 
+#[derive(Subcommand)]
+pub enum RegistryCommands {
+  #[clap(about = "Measure the latency of every known registry")]
+  Test {
+    #[clap(long, help = "Set the fastest registry as the default")]
+    save: bool,
+  },
+}
+
 #[derive(Subcommand)]
 pub enum AliasCommands {
   #[clap(about = "Set an alias")]
@@ -160,3 +609,214 @@ pub enum AliasCommands {
   #[clap(about = "List all aliases")]
   List,
 }
+
+#[derive(Subcommand)]
+pub enum VersionEnvCommands {
+  #[clap(about = "Set an environment variable")]
+  Set {
+    #[clap(help = "The semver range of versions to apply to")]
+    required: String,
+    #[clap(help = "Variable name")]
+    name: String,
+    #[clap(help = "Variable value")]
+    value: String,
+  },
+
+  #[clap(about = "Unset an environment variable")]
+  Unset {
+    #[clap(help = "The semver range of versions the variable was set for")]
+    required: String,
+    #[clap(help = "Variable name")]
+    name: String,
+  },
+
+  #[clap(about = "List all environment variables")]
+  List,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+  #[clap(about = "Print the value of a config key")]
+  Get {
+    #[clap(help = "Config key to get")]
+    key: String,
+  },
+
+  #[clap(about = "Set a config key")]
+  Set {
+    #[clap(help = "Config key to set")]
+    key: String,
+    #[clap(help = "Config value")]
+    value: String,
+  },
+
+  #[clap(about = "Unset a config key")]
+  Unset {
+    #[clap(help = "Config key to unset")]
+    key: String,
+  },
+
+  #[clap(about = "List all config keys that are set")]
+  List,
+
+  #[clap(about = "Print the config as TOML to import on other machines, secrets are left out")]
+  Export {
+    #[clap(long, help = "Include the registry credentials")]
+    include_secrets: bool,
+  },
+
+  #[clap(about = "Import a config exported by `dvm config export` from a file or url")]
+  Import {
+    #[clap(help = "The file or https url to import")]
+    source: String,
+    #[clap(long, help = "Only show what would change")]
+    dry_run: bool,
+  },
+}
+
+#[derive(Subcommand)]
+pub enum LockCommands {
+  #[clap(about = "Lock the installed executable of a version in dvm.lock")]
+  Write {
+    #[clap(help = "The version, semver range or alias to lock, read from .dvmrc if not present")]
+    version: Option<String>,
+  },
+
+  #[clap(about = "Fail unless the locked version is installed unchanged and active")]
+  Verify {
+    #[clap(long, help = "The lock file to verify, dvm.lock of the current directory by default")]
+    file: Option<PathBuf>,
+  },
+}
+
+#[derive(Subcommand)]
+pub enum MiseCommands {
+  #[clap(about = "Write a mise plugin whose scripts run dvm")]
+  Plugin {
+    #[clap(long, help = "Where to write the plugin, mise-plugin of the dvm root by default")]
+    dir: Option<PathBuf>,
+  },
+
+  #[clap(about = "Print every release on one line, as the plugin lists them")]
+  ListVersions,
+
+  #[clap(about = "Install a version and link it into a mise install path")]
+  Install {
+    version: String,
+    #[clap(help = "The install path mise gives the plugin")]
+    path: PathBuf,
+  },
+
+  #[clap(about = "Print the directory of an installed version")]
+  Where { version: String },
+}
+
+#[derive(Subcommand)]
+pub enum TelemetryCommands {
+  #[clap(about = "Show whether telemetry is on and what it reports")]
+  Status,
+
+  #[clap(about = "Turn telemetry off")]
+  Off,
+}
+
+#[derive(Subcommand)]
+pub enum CiCommands {
+  #[clap(about = "Print the directories worth caching and a stable cache key")]
+  CacheInfo {
+    #[clap(help = "The version, semver range or alias to resolve, read from .dvmrc if not present")]
+    version: Option<String>,
+    #[clap(long, help = "Print as json")]
+    json: bool,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum HookShell {
+  Bash,
+  Zsh,
+  Fish,
+  Powershell,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum EnvShell {
+  Bash,
+  Zsh,
+  Fish,
+  Powershell,
+  Cmd,
+}
+
+impl From<HookShell> for EnvShell {
+  fn from(shell: HookShell) -> Self {
+    match shell {
+      HookShell::Bash => EnvShell::Bash,
+      HookShell::Zsh => EnvShell::Zsh,
+      HookShell::Fish => EnvShell::Fish,
+      HookShell::Powershell => EnvShell::Powershell,
+    }
+  }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PluginShell {
+  /// for fisher and oh-my-fish
+  Fish,
+  /// for oh-my-zsh
+  Zsh,
+  /// a module for `Import-Module`
+  Powershell,
+  /// an activation script and a `deno.cmd` wrapper
+  Cmd,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SbomFormat {
+  Cyclonedx,
+  Spdx,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DvmPath {
+  /// versions, the bin directory and the metadata
+  Root,
+  /// the installed versions
+  Versions,
+  /// the downloaded archives
+  Cache,
+  /// the config file
+  Config,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Editor {
+  Vscode,
+  Jetbrains,
+  Zed,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|it| it.to_string()).collect()
+  }
+
+  #[test]
+  fn test_split_dvm_dir() {
+    assert_eq!(
+      split_dvm_dir(args(&["dvm", "--dvm-dir", "/opt/dvm", "list"])),
+      (Some("/opt/dvm".to_string()), args(&["dvm", "list"]))
+    );
+    assert_eq!(
+      split_dvm_dir(args(&["dvm", "use", "--dvm-dir=/opt/dvm", "1.28.0"])),
+      (Some("/opt/dvm".to_string()), args(&["dvm", "use", "1.28.0"]))
+    );
+    assert_eq!(
+      split_dvm_dir(args(&["dvm", "exec", "run", "--dvm-dir", "a.ts"])),
+      (None, args(&["dvm", "exec", "run", "--dvm-dir", "a.ts"]))
+    );
+  }
+}
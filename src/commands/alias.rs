@@ -1,4 +1,5 @@
 use crate::cli::AliasCommands;
+use crate::meta::default_alias;
 use crate::version::{find_max_matching_version, local_versions, remote_versions, version_req_parse};
 use crate::{DvmMeta, DEFAULT_ALIAS};
 
@@ -49,7 +50,8 @@ pub fn exec(meta: &mut DvmMeta, command: AliasCommands) -> Result<()> {
         }
         None
       };
-      for (key, val) in DEFAULT_ALIAS.entries() {
+      for key in DEFAULT_ALIAS {
+        let val = &default_alias(key).unwrap();
         let upgrade_version = get_upgrade_version(val);
         if let Some(upgrade_version) = upgrade_version {
           println!(
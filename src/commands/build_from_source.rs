@@ -0,0 +1,168 @@
+use super::install::TARGET;
+use super::use_version;
+use crate::archive_cache;
+use crate::consts::{DENO_EXE, DVM_BUILD_PATH_PREFIX, DVM_VERSION_LATEST};
+use crate::hooks::{self, Hook};
+use crate::meta::DvmMeta;
+use crate::output::{self, Event};
+use crate::utils::{deno_version_path, dvm_cache_root, ensure_versions_writable, normalize_version};
+use crate::version::get_latest_version;
+use crate::{glibc, http, install_lock, interrupt, policy, provenance, signature};
+use anyhow::{anyhow, bail, Result};
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// the source tarball attached to every deno release, vendored submodules included
+const SOURCE_ARCHIVE: &str = "deno_src.tar.gz";
+
+/// the directory of the workspace inside the unpacked tarball
+fn workspace_dir(dir: &Path) -> Result<PathBuf> {
+  if dir.join("Cargo.toml").is_file() {
+    return Ok(dir.to_path_buf());
+  }
+  fs::read_dir(dir)?
+    .flatten()
+    .map(|it| it.path())
+    .find(|it| it.join("Cargo.toml").is_file())
+    .ok_or_else(|| anyhow!("{} has no Cargo.toml", SOURCE_ARCHIVE))
+}
+
+fn build(registry: &str, version: &Version) -> Result<PathBuf> {
+  let cargo = which::which("cargo").map_err(|_| anyhow!(t!("cargo is required to build deno from source")))?;
+  let Some(data) = http::block_on(async {
+    let data = archive_cache::fetch(registry, version, TARGET, SOURCE_ARCHIVE).await?;
+    if let Some(data) = &data {
+      signature::check(registry, version, TARGET, SOURCE_ARCHIVE, data).await?;
+      provenance::check(registry, version, TARGET, SOURCE_ARCHIVE, data).await?;
+    }
+    Ok(data)
+  })?
+  else {
+    bail!(t!("{} has not been found", SOURCE_ARCHIVE));
+  };
+
+  // a previous failed build is started over
+  let dir = dvm_cache_root()
+    .join(DVM_BUILD_PATH_PREFIX)
+    .join(format!("v{}", version));
+  if dir.exists() {
+    fs::remove_dir_all(&dir)?;
+  }
+  fs::create_dir_all(&dir)?;
+  let _staged = interrupt::stage(&dir);
+  let archive = dir.join(SOURCE_ARCHIVE);
+  fs::write(&archive, data)?;
+  output::event(Event::Extract { archive: &archive });
+  let status = Command::new("tar")
+    .arg("-xzf")
+    .arg(SOURCE_ARCHIVE)
+    .current_dir(&dir)
+    .status()?;
+  if !status.success() {
+    bail!("failed to extract {}: {}", SOURCE_ARCHIVE, status);
+  }
+
+  let workspace = workspace_dir(&dir)?;
+  output::info(t!("Building deno v{} with cargo, it takes a while", version));
+  let status = Command::new(cargo)
+    .args(["build", "--release", "--locked", "--bin", "deno"])
+    .current_dir(&workspace)
+    .stdin(Stdio::null())
+    .stdout(output::passthrough())
+    .status()?;
+  if !status.success() {
+    bail!(t!(
+      "Building deno v{} failed: {}, the sources are kept in {}",
+      version,
+      status,
+      dir.display()
+    ));
+  }
+  Ok(workspace.join("target").join("release").join(DENO_EXE))
+}
+
+/// the build runs and names the version it was built from in `deno --version`
+fn verify(exe: &Path, version: &Version) -> Result<()> {
+  output::event(Event::Verify { path: exe });
+  let output = Command::new(exe).arg("--version").stdin(Stdio::null()).output()?;
+  let expected = format!("deno {}", version);
+  let reported = String::from_utf8_lossy(&output.stdout);
+  let matches = reported
+    .lines()
+    .next()
+    .is_some_and(|it| it == expected || it.starts_with(&format!("{} ", expected)));
+  if !output.status.success() || !matches {
+    bail!(t!(
+      "The deno built from v{} doesn't report itself as such: {}",
+      version,
+      reported.trim()
+    ));
+  }
+  Ok(())
+}
+
+///
+/// the install of a version built from its source tarball with `cargo build --release`,
+/// for targets deno has no official binaries for, the result is registered like a download.
+pub fn exec(meta: &DvmMeta, no_use: bool, version: Option<String>) -> Result<(String, PathBuf)> {
+  let version = version.map(|it| normalize_version(&it).to_string());
+  let install_version = match version {
+    Some(ref version) => Version::parse(version).map_err(|_| anyhow!(t!("Invalid semver {}", version)))?,
+    None => get_latest_version(&meta.registry())?,
+  };
+  policy::check(&install_version)?;
+  output::event(Event::Resolve {
+    version: &install_version.to_string(),
+  });
+
+  let exe_path = deno_version_path(&install_version);
+  let mut installed = exe_path.exists();
+
+  // another process installing the same version into a shared store is waited for
+  let mut lock = None;
+  if !installed {
+    ensure_versions_writable()?;
+    glibc::check(&install_version)?;
+    lock = Some(install_lock::acquire(&install_version.to_string())?);
+    installed = exe_path.exists();
+  }
+
+  if installed {
+    output::info(t!("Version v{} is already installed", install_version));
+  } else {
+    hooks::run(Hook::PreInstall, &install_version.to_string(), &exe_path)?;
+    let built = build(&meta.registry(), &install_version)?;
+    verify(&built, &install_version)?;
+
+    fs::create_dir_all(exe_path.parent().unwrap())?;
+    let staged = interrupt::stage(&exe_path);
+    fs::copy(&built, &exe_path)?;
+    drop(staged);
+    // the target directory of a deno build is several gigabytes
+    fs::remove_dir_all(
+      dvm_cache_root()
+        .join(DVM_BUILD_PATH_PREFIX)
+        .join(format!("v{}", install_version)),
+    )
+    .ok();
+    output::success(t!("Deno v{} has been built", install_version));
+    hooks::run(Hook::PostInstall, &install_version.to_string(), &exe_path)?;
+  }
+  drop(lock);
+
+  if !no_use {
+    use_version::use_this_bin_path(
+      &exe_path,
+      &install_version,
+      version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string()),
+      false,
+    )?;
+  }
+  output::event(Event::Done {
+    version: &install_version.to_string(),
+    path: &exe_path,
+  });
+  Ok((install_version.to_string(), exe_path))
+}
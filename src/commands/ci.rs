@@ -0,0 +1,111 @@
+use crate::archive_cache::archives_root;
+use crate::cli::CiCommands;
+use crate::commands::install::TARGET;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{best_version, dvm_versions, load_dvmrc};
+use crate::version::{remote_versions, VersionArg};
+use anyhow::{anyhow, Result};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+pub fn exec(meta: &DvmMeta, command: CiCommands) -> Result<()> {
+  match command {
+    CiCommands::CacheInfo { version, json } => cache_info(meta, version, json),
+  }
+}
+
+/// resolve the version given or the one in .dvmrc to an exact version
+pub fn resolve_version(meta: &DvmMeta, version: Option<String>) -> Result<String> {
+  let version_req = match version {
+    Some(version) => meta.resolve_version_req(&version),
+    None => load_dvmrc(),
+  };
+
+  match version_req {
+    VersionArg::Exact(v) => Ok(v.to_string()),
+    VersionArg::Range(r) => {
+      let versions = remote_versions()?;
+      best_version(versions.iter().map(AsRef::as_ref), r.clone())
+        .map(|v| v.to_string())
+        .ok_or_else(|| anyhow!("No released version matches {}", r))
+    }
+  }
+}
+
+/// the cache key is stable as long as the resolved version and target stay the same
+fn cache_key(version: &str, target: &str) -> String {
+  format!("dvm-deno-{}-{}", version, target)
+}
+
+fn cache_info(meta: &DvmMeta, version: Option<String>, json: bool) -> Result<()> {
+  let version = resolve_version(meta, version)?;
+  let key = cache_key(&version, TARGET);
+  // the download cache goes along, an install from it doesn't need the network
+  let paths = vec![dvm_versions(), archives_root()];
+
+  if json {
+    let info = serde_json::json!({
+      "key": key,
+      "version": version,
+      "target": TARGET,
+      "paths": paths,
+    });
+    println!("{}", serde_json::to_string_pretty(&info)?);
+  } else {
+    println!("key: {}", key);
+    println!("paths:");
+    for path in paths {
+      println!("  {}", path.display());
+    }
+  }
+
+  Ok(())
+}
+
+/// Append a line to the file that a GitHub Actions environment variable points to,
+/// returns false if the variable is not set.
+fn append_to(var: &str, line: &str) -> Result<bool> {
+  let Some(file) = env::var_os(var) else {
+    return Ok(false);
+  };
+  let mut file = OpenOptions::new().create(true).append(true).open(file)?;
+  writeln!(file, "{}", line)?;
+  Ok(true)
+}
+
+///
+/// make an installed deno visible to the following steps of a GitHub Actions job,
+///   the directory of `exe_path` is appended to `GITHUB_PATH`,
+///   the resolved version is exported as `DENO_VERSION` and as the `deno-version` step output.
+pub fn github_output(version: &str, exe_path: &Path) -> Result<()> {
+  let bin_dir = exe_path.parent().unwrap();
+
+  let path_set = append_to("GITHUB_PATH", &bin_dir.to_string_lossy())?;
+  let env_set = append_to("GITHUB_ENV", &format!("DENO_VERSION={}", version))?;
+  append_to("GITHUB_OUTPUT", &format!("deno-version={}", version))?;
+  append_to("GITHUB_OUTPUT", &format!("deno-path={}", exe_path.display()))?;
+
+  if !path_set && !env_set {
+    output::warn("GITHUB_PATH and GITHUB_ENV are not set, are you running inside GitHub Actions?");
+    return Ok(());
+  }
+
+  println!("Exported deno {} ({}) to GitHub Actions", version, bin_dir.display());
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cache_key() {
+    assert_eq!(
+      cache_key("1.28.0", "x86_64-unknown-linux-gnu"),
+      "dvm-deno-1.28.0-x86_64-unknown-linux-gnu"
+    );
+  }
+}
@@ -1,14 +1,31 @@
-use crate::{dvm_root, DvmMeta};
+use crate::commands::rehash::active_version;
+use crate::commands::stats::{disk_usage, megabytes};
+use crate::commands::use_version::remove_stale_bins;
+use crate::config::{AutoClean, DvmConfig};
+use crate::consts::DVM_AUTO_CLEAN_CHECK_FILENAME;
+use crate::output;
+use crate::utils::{deno_version_dir, dvm_versions, is_versions_writable, mark_if_due, now, prompt_request};
+use crate::version::local_versions;
+use crate::DvmMeta;
 use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::UNIX_EPOCH;
 
 pub fn exec(meta: &mut DvmMeta) -> Result<()> {
-  let home = dvm_root();
-
-  let cache_folder = home.join("versions");
+  let cache_folder = dvm_versions();
   if !cache_folder.exists() {
     std::process::exit(0);
   }
 
+  // the projects go first, what they need now is kept whatever happens to the mappings
+  meta.resolve_project_pins();
+
   let requires = meta
     .versions
     .iter()
@@ -25,8 +42,210 @@ pub fn exec(meta: &mut DvmMeta) -> Result<()> {
     meta.delete_version_mapping(required.clone());
   }
 
-  meta.clean_files();
+  let cleanable = meta.cleanable();
+  remove_versions(meta, &cleanable);
+  remove_stale_bins();
 
-  println!("Cleaned successfully");
+  println!("{}", t!("Cleaned successfully"));
   Ok(())
 }
+
+/// the prefix of a version moved out of the store to be deleted, `local_versions` doesn't list it
+const TRASH_PREFIX: &str = ".removing-";
+
+/// where a version is moved to be deleted, the process id keeps it apart from what an earlier run left
+fn trash_dir(name: &str) -> PathBuf {
+  dvm_versions().join(format!("{}{}-{}", TRASH_PREFIX, std::process::id(), name))
+}
+
+/// the version a moved directory was, `None` for anything else
+fn trashed_version(dir_name: &str) -> Option<&str> {
+  let (_, name) = dir_name.strip_prefix(TRASH_PREFIX)?.split_once('-')?;
+  Some(name)
+}
+
+/// counts the removed versions and the bytes they took, as a bar on a terminal and a line a version elsewhere
+struct Progress {
+  total: usize,
+  done: AtomicUsize,
+  reclaimed: AtomicU64,
+  bar: bool,
+}
+
+impl Progress {
+  fn tick(&self, name: &str, bytes: u64) {
+    let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+    let reclaimed = self.reclaimed.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    if !self.bar {
+      println!("{}", t!("Cleaning version {}", name.bright_black()));
+      return;
+    }
+    let width = 24;
+    let filled = done * width / self.total.max(1);
+    eprint!(
+      "\r[{}{}] {}/{}  {}",
+      "#".repeat(filled),
+      " ".repeat(width - filled),
+      done,
+      self.total,
+      t!("{} reclaimed", megabytes(reclaimed))
+    );
+    if done == self.total {
+      eprintln!();
+    }
+  }
+}
+
+/// the removals an interrupted run left behind, they're finished with the next one
+fn leftovers() -> Vec<(String, PathBuf)> {
+  let entries = dvm_versions().read_dir().into_iter().flatten().flatten();
+  entries
+    .filter_map(|it| Some((trashed_version(it.file_name().to_str()?)?.to_string(), it.path())))
+    .collect()
+}
+
+/// delete the directories on all cores, the bytes reclaimed are returned
+fn delete_all(dirs: Vec<(String, PathBuf)>) -> u64 {
+  let progress = Progress {
+    total: dirs.len(),
+    done: AtomicUsize::new(0),
+    reclaimed: AtomicU64::new(0),
+    bar: std::io::stderr().is_terminal() && !output::is_json(),
+  };
+  let next = AtomicUsize::new(0);
+  let workers = thread::available_parallelism()
+    .map(|it| it.get())
+    .unwrap_or(4)
+    .min(dirs.len());
+  thread::scope(|scope| {
+    for _ in 0..workers {
+      scope.spawn(|| {
+        while let Some((name, dir)) = dirs.get(next.fetch_add(1, Ordering::Relaxed)) {
+          let bytes = disk_usage(dir);
+          match fs::remove_dir_all(dir) {
+            Ok(()) => progress.tick(name, bytes),
+            Err(err) => {
+              progress.tick(name, 0);
+              output::warn(t!("Failed to remove {}: {}", dir.display(), err));
+            }
+          }
+        }
+      });
+    }
+  });
+  progress.reclaimed.into_inner()
+}
+
+///
+/// remove versions from the store, the meta is updated once they're out of it and before they're deleted,
+/// so it never knows a version that's gone or misses one that's there. each version is moved aside first,
+/// which is instant, the slow deletion then runs in parallel and an interrupted one is finished next time.
+fn remove_versions(meta: &mut DvmMeta, names: &[String]) {
+  let mut removed = Vec::new();
+  let mut dirs = leftovers();
+  for name in names {
+    let trash = trash_dir(name);
+    match fs::rename(dvm_versions().join(name), &trash) {
+      Ok(()) => {
+        removed.push(name.clone());
+        dirs.push((name.clone(), trash));
+      }
+      Err(err) => output::warn(t!("Failed to remove deno v{}: {}", name, err)),
+    }
+  }
+  // the mappings of the removed versions are dropped with the reload
+  meta.forget_versions(&removed);
+  meta.save();
+  meta.reload();
+
+  if dirs.is_empty() {
+    return;
+  }
+  let count = dirs.len();
+  let reclaimed = delete_all(dirs);
+  output::success(t!("Removed {} versions, {} reclaimed", count, megabytes(reclaimed)));
+}
+
+/// when a version was used last, the versions never used since they were installed count from their install
+fn last_used_time(meta: &DvmMeta, version: &Version) -> u64 {
+  meta.last_used_time(&version.to_string()).unwrap_or_else(|| {
+    fs::metadata(deno_version_dir(version))
+      .and_then(|it| it.modified())
+      .ok()
+      .and_then(|it| it.duration_since(UNIX_EPOCH).ok())
+      .map(|it| it.as_secs())
+      .unwrap_or(0)
+  })
+}
+
+///
+/// the installed versions not used since `before`, in unix seconds,
+/// the default version and the ones pinned by linked projects are never unused.
+fn unused_versions(meta: &DvmMeta, before: u64) -> Vec<Version> {
+  let default = active_version().and_then(|it| meta.resolve_local_version(&it));
+  let mut versions = local_versions()
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .filter(|it| Some(it) != default.as_ref())
+    .filter(|it| {
+      !meta
+        .projects
+        .iter()
+        .any(|project| project.version == it.to_string() && project.exists())
+    })
+    .filter(|it| last_used_time(meta, it) < before)
+    .collect::<Vec<_>>();
+  versions.sort();
+  versions
+}
+
+///
+/// once a day, remove the versions unused for `auto-clean-unused-days`,
+/// asking first unless `auto-clean` is `remove`, a prompt is never shown outside of a terminal.
+pub fn auto_clean_if_due(meta: &mut DvmMeta) {
+  let config = DvmConfig::load();
+  let Some(days) = config.auto_clean_unused_days else {
+    return;
+  };
+  let prompt = config.auto_clean() == AutoClean::Prompt;
+  if prompt && !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+    return;
+  }
+  if !is_versions_writable() || !mark_if_due(DVM_AUTO_CLEAN_CHECK_FILENAME) {
+    return;
+  }
+
+  meta.resolve_project_pins();
+  let before = ((now() / 1000) as u64).saturating_sub(days * 24 * 60 * 60);
+  let unused = unused_versions(meta, before);
+  if unused.is_empty() {
+    return;
+  }
+  let names = unused
+    .iter()
+    .map(|it| format!("v{}", it))
+    .collect::<Vec<_>>()
+    .join(", ");
+  if prompt && !prompt_request(&t!("deno {} haven't been used for {} days, remove them?", names, days)) {
+    return;
+  }
+
+  remove_versions(meta, &unused.iter().map(Version::to_string).collect::<Vec<_>>());
+  remove_stale_bins();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_trashed_version() {
+    let trash = trash_dir("1.28.0");
+    assert_eq!(
+      trashed_version(trash.file_name().unwrap().to_str().unwrap()),
+      Some("1.28.0")
+    );
+    assert_eq!(trashed_version(".removing-42-1.28.0-rc.1"), Some("1.28.0-rc.1"));
+    assert_eq!(trashed_version("1.28.0"), None);
+  }
+}
@@ -0,0 +1,111 @@
+use crate::commands::rehash::active_exe_path;
+use crate::consts::{DENORT_EXE, DENO_EXE, DVM_COMPACT_DAYS, DVM_COMPACT_LEVEL};
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{deno_version_dir, deno_version_path, ensure_versions_writable, now};
+use crate::version::local_versions;
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// the compressed form of an executable in a version directory
+pub fn compacted_path(path: &Path) -> PathBuf {
+  let mut name = path.file_name().unwrap().to_os_string();
+  name.push(".zst");
+  path.with_file_name(name)
+}
+
+/// whether the version is installed, compacted or not
+pub fn is_installed(version: &Version) -> bool {
+  let exe_path = deno_version_path(version);
+  exe_path.exists() || compacted_path(&exe_path).exists()
+}
+
+/// write `to` through a temporary file, so an interrupted run never leaves a truncated file
+fn transcode(from: &Path, to: &Path, compress: bool) -> Result<()> {
+  let mut partial = to.file_name().unwrap().to_os_string();
+  partial.push(".partial");
+  let partial = to.with_file_name(partial);
+
+  let input = File::open(from)?;
+  let output = File::create(&partial)?;
+  if compress {
+    zstd::stream::copy_encode(input, output, DVM_COMPACT_LEVEL)?;
+  } else {
+    zstd::stream::copy_decode(input, output)?;
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+    }
+  }
+  fs::rename(partial, to)?;
+  fs::remove_file(from)?;
+  Ok(())
+}
+
+///
+/// decompress the executables of a compacted version,
+/// called before a version is activated so compaction is transparent to the user.
+pub fn restore(version: &Version) -> Result<()> {
+  for exe in [DENO_EXE, DENORT_EXE] {
+    let path = deno_version_dir(version).join(exe);
+    let compacted = compacted_path(&path);
+    if compacted.exists() && !path.exists() {
+      eprintln!("{}", t!("Decompressing {} v{}", exe, version));
+      transcode(&compacted, &path, false)?;
+    }
+  }
+  Ok(())
+}
+
+/// compress the executables of versions not used in `days` days
+pub fn exec(meta: &DvmMeta, days: Option<u64>) -> Result<()> {
+  ensure_versions_writable()?;
+  let days = days.unwrap_or(DVM_COMPACT_DAYS);
+  let active = active_exe_path(meta);
+  let threshold = now().saturating_sub(days as u128 * 24 * 60 * 60 * 1000);
+
+  let mut reclaimed = 0;
+  for version in local_versions() {
+    let parsed = Version::parse(&version)?;
+    if meta.last_used_time(&version).unwrap_or(0) as u128 * 1000 > threshold
+      || active.as_ref() == Some(&deno_version_path(&parsed))
+    {
+      continue;
+    }
+
+    for exe in [DENO_EXE, DENORT_EXE] {
+      let path = deno_version_dir(&parsed).join(exe);
+      if !path.exists() {
+        continue;
+      }
+      println!("{}", t!("Compressing {} v{}", exe, version.bright_black()));
+      let size = fs::metadata(&path)?.len();
+      let compacted = compacted_path(&path);
+      transcode(&path, &compacted, true)?;
+      reclaimed += size.saturating_sub(fs::metadata(compacted)?.len());
+    }
+  }
+
+  output::success(t!(
+    "Reclaimed {} MB",
+    format!("{:.1}", reclaimed as f64 / 1024.0 / 1024.0)
+  ));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_compacted_path() {
+    assert_eq!(
+      compacted_path(Path::new("versions/1.28.0/deno")),
+      PathBuf::from("versions/1.28.0/deno.zst")
+    );
+  }
+}
@@ -1,8 +1,162 @@
-use anyhow::Result;
+use crate::output;
+use crate::utils::shell_quote;
+use anyhow::{anyhow, Result};
 use clap::Command;
 use clap_complete::{generate, Shell};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn exec(app: &mut Command, shell: Shell) -> Result<()> {
+/// where the script of a shell goes, and the line a profile needs so the shell finds it
+#[derive(Debug, Eq, PartialEq)]
+struct Destination {
+  script: PathBuf,
+  profile: Option<(PathBuf, String)>,
+}
+
+///
+/// the completion directory of a shell, `var` looks up the environment:
+///   bash: the user directory of bash-completion, which loads `dvm` from it on first use,
+///   zsh: `~/.zfunc`, put into `fpath` at the top of `.zshrc` so it comes before `compinit`,
+///   fish: `completions` of the fish config, fish loads it on its own,
+///   powershell: a script next to `$PROFILE` that the profile dot-sources.
+fn destination(shell: Shell, home: &Path, var: impl Fn(&str) -> Option<String>) -> Option<Destination> {
+  let dir = |name: &str, default: &str| var(name).map(PathBuf::from).unwrap_or_else(|| home.join(default));
+  match shell {
+    Shell::Bash => {
+      let dir = var("BASH_COMPLETION_USER_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| dir("XDG_DATA_HOME", ".local/share").join("bash-completion"));
+      Some(Destination {
+        script: dir.join("completions").join("dvm"),
+        profile: None,
+      })
+    }
+    Shell::Zsh => {
+      let zdotdir = var("ZDOTDIR").map(PathBuf::from).unwrap_or_else(|| home.to_path_buf());
+      let functions = zdotdir.join(".zfunc");
+      let line = format!("fpath=({} $fpath)", shell_quote(&functions.to_string_lossy()));
+      Some(Destination {
+        script: functions.join("_dvm"),
+        profile: Some((zdotdir.join(".zshrc"), line)),
+      })
+    }
+    Shell::Fish => Some(Destination {
+      script: dir("XDG_CONFIG_HOME", ".config").join("fish/completions/dvm.fish"),
+      profile: None,
+    }),
+    Shell::PowerShell => {
+      let profile_dir = if cfg!(windows) {
+        home.join("Documents").join("PowerShell")
+      } else {
+        dir("XDG_CONFIG_HOME", ".config").join("powershell")
+      };
+      let script = profile_dir.join("dvm-completions.ps1");
+      let line = format!(". \"{}\"", script.display());
+      Some(Destination {
+        script,
+        profile: Some((profile_dir.join("Microsoft.PowerShell_profile.ps1"), line)),
+      })
+    }
+    _ => None,
+  }
+}
+
+/// the profile with `line` added, `None` if it has it already, zsh needs it before `compinit` so it goes first
+fn with_line(profile: &str, line: &str, shell: Shell) -> Option<String> {
+  if profile.lines().any(|it| it.trim() == line) {
+    return None;
+  }
+  let comment = "# completions of dvm, added by `dvm completions --install`";
+  Some(match shell {
+    Shell::Zsh => format!("{}\n{}\n{}", comment, line, profile),
+    _ if profile.is_empty() || profile.ends_with('\n') => format!("{}{}\n{}\n", profile, comment, line),
+    _ => format!("{}\n{}\n{}\n", profile, comment, line),
+  })
+}
+
+/// write the completion script where `shell` looks for it, running it again refreshes it
+fn install(app: &mut Command, shell: Shell) -> Result<()> {
+  let home = dirs::home_dir().ok_or_else(|| anyhow!(t!("Unable to find the home directory")))?;
+  let Some(destination) = destination(shell, &home, |name| {
+    std::env::var(name).ok().filter(|it| !it.is_empty())
+  }) else {
+    return Err(anyhow!(t!(
+      "{} has no known completion directory, redirect `dvm completions {}` to a file it loads",
+      shell,
+      shell
+    )));
+  };
+
+  let mut script = Vec::new();
+  generate(shell, app, "dvm", &mut script);
+  if fs::read(&destination.script).ok().as_deref() == Some(script.as_slice()) {
+    output::info(t!("The completions in {} are up to date", destination.script.display()));
+  } else {
+    fs::create_dir_all(destination.script.parent().unwrap())?;
+    fs::write(&destination.script, script)?;
+    output::success(t!(
+      "The completions have been written to {}",
+      destination.script.display()
+    ));
+  }
+
+  if let Some((profile, line)) = destination.profile {
+    let content = fs::read_to_string(&profile).unwrap_or_default();
+    if let Some(content) = with_line(&content, &line, shell) {
+      fs::create_dir_all(profile.parent().unwrap())?;
+      fs::write(&profile, content)?;
+      output::success(t!("{} loads them now", profile.display()));
+    }
+  }
+  println!("{}", t!("Open a new shell to use them"));
+  Ok(())
+}
+
+pub fn exec(app: &mut Command, shell: Shell, install_script: bool) -> Result<()> {
+  if install_script {
+    return install(app, shell);
+  }
   generate(shell, app, "dvm", &mut std::io::stdout());
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_destination() {
+    let home = Path::new("/home/me");
+    let none = |_: &str| None;
+    assert_eq!(
+      destination(Shell::Bash, home, none).unwrap().script,
+      PathBuf::from("/home/me/.local/share/bash-completion/completions/dvm")
+    );
+    let fish = destination(Shell::Fish, home, |name| {
+      (name == "XDG_CONFIG_HOME").then(|| "/cfg".to_string())
+    });
+    assert_eq!(fish.unwrap().script, PathBuf::from("/cfg/fish/completions/dvm.fish"));
+    let zsh = destination(Shell::Zsh, home, none).unwrap();
+    assert_eq!(zsh.script, PathBuf::from("/home/me/.zfunc/_dvm"));
+    assert_eq!(
+      zsh.profile,
+      Some((
+        PathBuf::from("/home/me/.zshrc"),
+        "fpath=(/home/me/.zfunc $fpath)".to_string()
+      ))
+    );
+    assert_eq!(destination(Shell::Elvish, home, none), None);
+  }
+
+  #[test]
+  fn test_with_line() {
+    let line = "fpath=(/home/me/.zfunc $fpath)";
+    let zshrc = with_line("autoload -U compinit; compinit\n", line, Shell::Zsh).unwrap();
+    assert!(zshrc.ends_with(&format!("{}\nautoload -U compinit; compinit\n", line)));
+    assert_eq!(with_line(&zshrc, line, Shell::Zsh), None);
+    assert_eq!(
+      with_line("Set-Alias g git", ". \"x\"", Shell::PowerShell).unwrap(),
+      "Set-Alias g git\n# completions of dvm, added by `dvm completions --install`\n. \"x\"\n"
+    );
+  }
+}
@@ -0,0 +1,98 @@
+use crate::cli::ConfigCommands;
+use crate::config::{self, SECRET_KEYS};
+use crate::utils::prompt_request;
+use crate::{http, output, source};
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::fs;
+use toml::Value;
+
+fn display(value: &Value) -> String {
+  match value {
+    Value::String(s) => s.clone(),
+    value => value.to_string(),
+  }
+}
+
+/// secrets are as hidden on import as on `config list`
+fn display_of(key: &str, value: &Value) -> String {
+  match SECRET_KEYS.contains(&key) {
+    true => "***".to_string(),
+    false => display(value),
+  }
+}
+
+/// a remote config can set the registry, plain http would let anyone on the way pick it
+fn read_source(source: &str) -> Result<String> {
+  if source.starts_with("http://") {
+    bail!(t!("{} is not https, a config is only imported over https", source));
+  }
+  if !source.starts_with("https://") {
+    return Ok(fs::read_to_string(source)?);
+  }
+  let Some(data) = http::block_on(source::fetch(&[source.to_string()]))? else {
+    bail!(t!("{} has not been found", source));
+  };
+  Ok(String::from_utf8(data)?)
+}
+
+/// print what an import changes, then apply it once confirmed unless `dry_run`
+fn import(source: &str, dry_run: bool) -> Result<()> {
+  let text = read_source(source)?;
+  let changes = config::import(&text, false)?;
+  if changes.is_empty() {
+    output::success(t!("The config already matches {}", source));
+    return Ok(());
+  }
+  for change in &changes {
+    match &change.old {
+      Some(old) => println!(
+        "{} {} = {} -> {}",
+        "~".yellow(),
+        change.key,
+        display_of(&change.key, old),
+        display_of(&change.key, &change.new)
+      ),
+      None => println!(
+        "{} {} = {}",
+        "+".green(),
+        change.key,
+        display_of(&change.key, &change.new)
+      ),
+    }
+  }
+  if dry_run {
+    return Ok(());
+  }
+  if !prompt_request(&t!("Apply these changes?")) {
+    output::info(t!("The config has been left unchanged"));
+    return Ok(());
+  }
+  config::import(&text, true)?;
+  output::success(t!("{} config keys have been imported", changes.len()));
+  Ok(())
+}
+
+pub fn exec(command: ConfigCommands) -> Result<()> {
+  match command {
+    ConfigCommands::Get { key } => {
+      if let Some(value) = config::get(&key)? {
+        println!("{}", display(&value));
+      }
+      Ok(())
+    }
+    ConfigCommands::Set { key, value } => config::set(&key, &value),
+    ConfigCommands::Unset { key } => config::unset(&key),
+    ConfigCommands::List => {
+      for (key, value) in config::list()? {
+        println!("{} = {}", key.bright_black(), display(&value));
+      }
+      Ok(())
+    }
+    ConfigCommands::Export { include_secrets } => {
+      print!("{}", config::export(include_secrets)?);
+      Ok(())
+    }
+    ConfigCommands::Import { source, dry_run } => import(&source, dry_run),
+  }
+}
@@ -0,0 +1,83 @@
+use crate::commands::rehash::active_version;
+use crate::consts::DVM_DENO_VERSION_ENV;
+use crate::consts::{DVM_CONFIG_FILENAME, DVM_VERSION_CANARY};
+use crate::meta::DvmMeta;
+use crate::project::ProjectConfig;
+use crate::utils::{check_is_deactivated, session_version, session_version_first};
+use anyhow::Result;
+
+/// glyphs of the channels in `--prompt`, the stable one is deno itself
+const GLYPH_STABLE: &str = "🦕";
+const GLYPH_RC: &str = "🧪";
+const GLYPH_CANARY: &str = "🐤";
+
+/// the version a deno launched here would be, and where it comes from
+pub fn resolve(meta: &DvmMeta) -> Option<(String, String)> {
+  let project = || {
+    ProjectConfig::load().and_then(|(dir, config)| {
+      config
+        .version
+        .map(|it| (it, dir.join(DVM_CONFIG_FILENAME).display().to_string()))
+    })
+  };
+  let session = || session_version().map(|it| (it, DVM_DENO_VERSION_ENV.to_string()));
+  let found = if session_version_first() {
+    session().or_else(project)
+  } else {
+    project().or_else(session)
+  };
+  let (raw, source) = match found {
+    Some(found) => found,
+    None => (
+      active_version()?,
+      dirs::home_dir()?.join(DVM_CONFIG_FILENAME).display().to_string(),
+    ),
+  };
+  let version = if raw == DVM_VERSION_CANARY {
+    raw.clone()
+  } else {
+    meta.resolve_local_version(&raw)?.to_string()
+  };
+  let source = if raw == version {
+    source
+  } else {
+    format!("{} in {}", raw, source)
+  };
+  Some((version, source))
+}
+
+fn glyph(version: &str) -> &'static str {
+  if version == DVM_VERSION_CANARY {
+    GLYPH_CANARY
+  } else if version.contains('-') {
+    GLYPH_RC
+  } else {
+    GLYPH_STABLE
+  }
+}
+
+///
+/// print the active version from the `.dvmrc` files alone, deno isn't run and nothing is downloaded,
+/// so `--prompt` is cheap enough for every prompt render.
+pub fn exec(meta: &DvmMeta, prompt: bool) -> Result<()> {
+  let active = (!check_is_deactivated()).then(|| resolve(meta)).flatten();
+  match (active, prompt) {
+    (Some((version, _)), true) => println!("{} {}", glyph(&version), version),
+    (Some((version, source)), false) => println!("{} ({})", version, source),
+    (None, true) => {}
+    (None, false) => println!("{}", t!("No version is active")),
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_glyph() {
+    assert_eq!(glyph("1.28.0"), GLYPH_STABLE);
+    assert_eq!(glyph("1.29.0-rc.1"), GLYPH_RC);
+    assert_eq!(glyph("canary"), GLYPH_CANARY);
+  }
+}
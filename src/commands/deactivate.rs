@@ -1,18 +1,64 @@
-use crate::utils::check_is_deactivated;
+use crate::commands::use_version::remove_bin;
+use crate::consts::DVM_CONFIG_FILENAME;
+use crate::output;
+use crate::project;
+use crate::utils::{check_is_deactivated, which_deno};
 use crate::{deno_bin_path, dvm_root};
-use anyhow::{Ok, Result};
+use anyhow::Result;
+use colored::Colorize;
 
-pub fn exec() -> Result<()> {
+///
+/// stop putting dvm's deno first, `global` also forgets the default version in `~/.dvmrc`,
+/// every step that is undone is reported, then where `deno` resolves to now.
+pub fn exec(global: bool) -> Result<()> {
   let home = dvm_root();
-  if check_is_deactivated() {
-    println!("Dvm has already been deactivated, exiting.");
+  if check_is_deactivated() && !global {
+    println!("{}", t!("Dvm has already been deactivated, exiting."));
     return Ok(());
   }
 
-  std::fs::write(home.join(".deactivated"), "").unwrap();
-  std::fs::remove_file(deno_bin_path()).unwrap();
+  std::fs::write(home.join(".deactivated"), "")?;
+  let bin_path = deno_bin_path();
+  if bin_path.exists() {
+    remove_bin(&bin_path)?;
+    output::info(t!("Removed the deno shim {}", bin_path.display()));
+  } else {
+    output::info(t!("There is no deno shim at {}", bin_path.display()));
+  }
+
+  if global {
+    let dvmrc = dirs::home_dir().unwrap().join(DVM_CONFIG_FILENAME);
+    match project::clear_version(&dvmrc)? {
+      Some(version) => output::info(t!(
+        "Forgot the global default version {} in {}",
+        version.bright_black(),
+        dvmrc.display()
+      )),
+      None => output::info(t!("No global default version is set")),
+    }
+  }
 
-  println!("Dvm is now deacvated.");
-  println!("Deno that was previously installed on your system will be activated now.");
+  println!("{}", t!("Dvm is now deacvated."));
+  // the PATH of running shells can't be changed from here, the bin directory is just left empty
+  let bin_dir = bin_path.parent().unwrap();
+  match which_deno() {
+    Some(deno) if !deno.starts_with(bin_dir) => {
+      println!(
+        "{}",
+        t!("Deno that was previously installed on your system will be activated now.")
+      );
+      output::info(t!("deno now resolves to {}", deno.display()));
+    }
+    _ => output::info(t!("No other deno is found in PATH")),
+  }
+  let in_path = std::env::var_os("PATH")
+    .map(|path| std::env::split_paths(&path).any(|it| it == bin_dir))
+    .unwrap_or(false);
+  if in_path {
+    output::info(t!(
+      "{} stays in PATH with no deno in it, run `dvm activate` to bring dvm back",
+      bin_dir.display()
+    ));
+  }
   Ok(())
 }
@@ -0,0 +1,175 @@
+use crate::assets::{self, Format};
+use crate::commands::ci::resolve_version;
+use crate::commands::dockerfile::{archive_path, sh_quote, unpack_command};
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::plan::known_sha256;
+use crate::source;
+use anyhow::{bail, Result};
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `uname -m` patterns of the linux targets a container can run, docker on apple silicon runs arm64
+const MACHINES: &[(&str, &str)] = &[
+  ("x86_64 | amd64", "x86_64-unknown-linux-gnu"),
+  ("aarch64 | arm64", "aarch64-unknown-linux-gnu"),
+];
+
+/// the id of the feature, it's referenced as `./<id>` from devcontainer.json
+const FEATURE_ID: &str = "deno";
+
+/// a linux target of the feature: the `uname -m` pattern, its urls, the sha256 and the format of the archive
+struct Machine {
+  pattern: &'static str,
+  urls: Vec<String>,
+  sha256: String,
+  format: Format,
+}
+
+///
+/// the `install.sh` of the feature, it runs as root while the image is built,
+/// curl and unzip are installed if the base image lacks them.
+fn install_script(version: &Version, machines: &[Machine]) -> String {
+  let mut script = format!(
+    "#!/bin/sh\n# deno v{}, generated by `dvm devcontainer`\nset -e\n\ncase \"$(uname -m)\" in\n",
+    version
+  );
+  for machine in machines {
+    let urls = machine.urls.iter().map(|it| sh_quote(it)).collect::<Vec<_>>().join(" ");
+    script.push_str(&format!(
+      "  {})\n    sha256={}\n    archive={}\n    unpack() {{ {}; }}\n    set -- {}\n    ;;\n",
+      machine.pattern,
+      machine.sha256,
+      archive_path(machine.format),
+      unpack_command(machine.format, "\"$archive\""),
+      urls
+    ));
+  }
+  script.push_str(&format!(
+    "  *)\n    echo \"deno v{} has no build for $(uname -m)\" >&2\n    exit 1\n    ;;\nesac\n",
+    version
+  ));
+  script.push_str(
+    r#"
+if ! command -v curl >/dev/null || ! command -v unzip >/dev/null; then
+  apt-get update
+  apt-get install -y --no-install-recommends ca-certificates curl unzip
+  rm -rf /var/lib/apt/lists/*
+fi
+
+for url in "$@"; do
+  curl -fsSL -o "$archive" "$url" && break
+done
+echo "$sha256  $archive" | sha256sum -c -
+unpack
+rm "$archive"
+deno --version
+"#,
+  );
+  script
+}
+
+fn feature_manifest(version: &Version) -> String {
+  let manifest = serde_json::json!({
+    "id": FEATURE_ID,
+    "version": version.to_string(),
+    "name": "deno",
+    "description": format!("deno v{} as pinned for this project, generated by `dvm devcontainer`", version),
+    "containerEnv": { "DENO_VERSION": version.to_string() },
+  });
+  serde_json::to_string_pretty(&manifest).unwrap() + "\n"
+}
+
+/// the part of devcontainer.json that turns the feature on
+fn fragment() -> serde_json::Value {
+  serde_json::json!({ "features": { format!("./{}", FEATURE_ID): {} } })
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+  fs::create_dir_all(path.parent().unwrap())?;
+  fs::write(path, content)?;
+  Ok(())
+}
+
+///
+/// write a local devcontainer feature installing the pinned deno into `dir`, `.devcontainer` by default,
+/// a devcontainer.json is created if there is none, otherwise the fragment to add to it is printed.
+pub fn exec(meta: &DvmMeta, version: Option<String>, dir: Option<PathBuf>) -> Result<()> {
+  let version = Version::parse(&resolve_version(meta, version)?)?;
+  let mut machines = Vec::new();
+  for (pattern, target) in MACHINES {
+    let Some(asset) = assets::deno(&version, target) else {
+      output::warn(t!("deno v{} has no build for {}", version, target));
+      continue;
+    };
+    match known_sha256(&meta.registry(), &version, &asset.target, &asset.name)? {
+      Some(sha256) => machines.push(Machine {
+        pattern,
+        urls: source::release_urls(&meta.registry(), &version, &asset.target, &asset.name),
+        sha256,
+        format: asset.format,
+      }),
+      None => output::warn(t!(
+        "deno v{} publishes no sha256 of {}, it's left out",
+        version,
+        asset.name
+      )),
+    }
+  }
+  if machines.is_empty() {
+    bail!(t!("deno v{} publishes no sha256 of its linux builds", version));
+  }
+
+  let dir = dir.unwrap_or_else(|| PathBuf::from(".devcontainer"));
+  let feature = dir.join(FEATURE_ID);
+  write(&feature.join("devcontainer-feature.json"), &feature_manifest(&version))?;
+  let script = feature.join("install.sh");
+  write(&script, &install_script(&version, &machines))?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+  }
+  output::success(t!(
+    "The devcontainer feature installing deno v{} has been written to {}",
+    version,
+    feature.display()
+  ));
+
+  let config = dir.join("devcontainer.json");
+  if config.exists() {
+    println!("{}", t!("Add it to the features of {}:", config.display()));
+    println!("{}", serde_json::to_string_pretty(&fragment())?);
+  } else {
+    let mut devcontainer = fragment();
+    devcontainer["name"] = "deno".into();
+    devcontainer["image"] = "mcr.microsoft.com/devcontainers/base:debian".into();
+    write(&config, &(serde_json::to_string_pretty(&devcontainer)? + "\n"))?;
+    output::success(t!("{} has been created", config.display()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_install_script() {
+    let version = Version::parse("1.28.0").unwrap();
+    let machines = vec![Machine {
+      pattern: "x86_64 | amd64",
+      urls: vec!["https://a/deno.zip".to_string(), "https://b/deno.zip".to_string()],
+      sha256: "ab".repeat(32),
+      format: Format::Zip,
+    }];
+    let script = install_script(&version, &machines);
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains(&format!(
+      "  x86_64 | amd64)\n    sha256={}\n    archive=/tmp/deno.zip\n    unpack() {{ unzip -q -o \"$archive\" -d /usr/local/bin; }}\n    set -- 'https://a/deno.zip' 'https://b/deno.zip'\n    ;;\n",
+      "ab".repeat(32)
+    )));
+    assert!(script.contains("echo \"$sha256  $archive\" | sha256sum -c -\n"));
+  }
+}
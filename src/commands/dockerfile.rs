@@ -0,0 +1,149 @@
+use crate::assets::{self, Asset, Format};
+use crate::commands::ci::resolve_version;
+use crate::commands::prefetch_targets::resolve_target;
+use crate::meta::DvmMeta;
+use crate::plan::known_sha256;
+use crate::source;
+use anyhow::{bail, Result};
+use semver::Version;
+
+/// where deno is installed inside the image
+const INSTALL_DIR: &str = "/usr/local/bin";
+
+/// the image the full Dockerfile starts from, it has neither curl nor unzip
+const BASE_IMAGE: &str = "debian:bookworm-slim";
+
+/// quoted for the `/bin/sh` of the image, whatever the host is
+pub fn sh_quote(value: &str) -> String {
+  format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn is_sha256(hash: &str) -> bool {
+  hash.len() == 64 && hash.chars().all(|it| it.is_ascii_hexdigit())
+}
+
+/// where the archive is downloaded to inside the image
+pub fn archive_path(format: Format) -> String {
+  format!("/tmp/{}", format.archive_file())
+}
+
+/// the shell command putting deno from `archive` into `/usr/local/bin`
+pub fn unpack_command(format: Format, archive: &str) -> String {
+  match format {
+    Format::Zip => format!("unzip -q -o {} -d {}", archive, INSTALL_DIR),
+    Format::Gzip => format!(
+      "gzip -dc {archive} > {dir}/deno && chmod +x {dir}/deno",
+      archive = archive,
+      dir = INSTALL_DIR
+    ),
+  }
+}
+
+///
+/// the instructions that install deno into `/usr/local/bin`,
+/// the urls are tried in order and the archive is checked before it's unpacked.
+fn snippet(version: &Version, target: &str, asset: &Asset, urls: &[String], sha256: &str) -> String {
+  let archive = archive_path(asset.format);
+  let download = urls
+    .iter()
+    .map(|url| format!("curl -fsSL -o {} {}", archive, sh_quote(url)))
+    .collect::<Vec<_>>()
+    .join(" || ");
+  format!(
+    "# deno v{version} for {target}, generated by `dvm dockerfile`\n\
+     ENV DENO_VERSION={version}\n\
+     RUN ({download}) \\\n  \
+     && echo '{sha256}  {archive}' | sha256sum -c - \\\n  \
+     && {unpack} \\\n  \
+     && rm {archive} \\\n  \
+     && deno --version\n",
+    unpack = unpack_command(asset.format, &archive),
+  )
+}
+
+fn dockerfile(snippet: &str) -> String {
+  format!(
+    "FROM {}\n\
+     RUN apt-get update \\\n  \
+     && apt-get install -y --no-install-recommends ca-certificates curl unzip \\\n  \
+     && rm -rf /var/lib/apt/lists/*\n\
+     {}",
+    BASE_IMAGE, snippet
+  )
+}
+
+/// print a Dockerfile, or just its deno part, installing the same archive `dvm install` would
+pub fn exec(
+  meta: &DvmMeta,
+  version: Option<String>,
+  target: Option<String>,
+  sha256: Option<String>,
+  snippet_only: bool,
+) -> Result<()> {
+  let target = resolve_target(target.as_deref().unwrap_or("linux-x64"))?;
+  if !target.contains("linux") {
+    bail!(t!("Containers run linux, {} is no linux target", target));
+  }
+  let version = Version::parse(&resolve_version(meta, version)?)?;
+  let Some(asset) = assets::deno(&version, target) else {
+    bail!(t!("deno v{} has no build for {}", version, target));
+  };
+
+  let sha256 = match sha256 {
+    Some(hash) if is_sha256(&hash) => hash.to_lowercase(),
+    Some(hash) => bail!(t!("{} is not a sha256", hash)),
+    None => match known_sha256(&meta.registry(), &version, &asset.target, &asset.name)? {
+      Some(hash) => hash,
+      None => bail!(t!(
+        "deno v{} publishes no sha256 of {}, pass it with --sha256",
+        version,
+        asset.name
+      )),
+    },
+  };
+
+  let urls = source::release_urls(&meta.registry(), &version, &asset.target, &asset.name);
+  let snippet = snippet(&version, target, &asset, &urls, &sha256);
+  if snippet_only {
+    print!("{}", snippet);
+  } else {
+    print!("{}", dockerfile(&snippet));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_snippet() {
+    let version = Version::parse("1.28.0").unwrap();
+    let urls = vec!["https://a/deno.zip".to_string(), "https://b/deno.zip".to_string()];
+    let hash = "ab".repeat(32);
+    let asset = assets::deno(&version, "x86_64-unknown-linux-gnu").unwrap();
+    let snippet = snippet(&version, "x86_64-unknown-linux-gnu", &asset, &urls, &hash);
+    assert!(snippet.contains("ENV DENO_VERSION=1.28.0\n"));
+    assert!(snippet.contains(
+      "RUN (curl -fsSL -o /tmp/deno.zip 'https://a/deno.zip' || curl -fsSL -o /tmp/deno.zip 'https://b/deno.zip')"
+    ));
+    assert!(snippet.contains(&format!("echo '{}  /tmp/deno.zip' | sha256sum -c -", hash)));
+    assert!(snippet.contains("&& unzip -q -o /tmp/deno.zip -d /usr/local/bin"));
+    assert!(dockerfile(&snippet).starts_with("FROM debian:bookworm-slim\n"));
+  }
+
+  #[test]
+  fn test_unpack_command() {
+    assert_eq!(
+      unpack_command(Format::Gzip, "/tmp/deno.gz"),
+      "gzip -dc /tmp/deno.gz > /usr/local/bin/deno && chmod +x /usr/local/bin/deno"
+    );
+  }
+
+  #[test]
+  fn test_is_sha256() {
+    assert!(is_sha256(&"0f".repeat(32)));
+    assert!(!is_sha256("0f0f"));
+    assert!(!is_sha256(&"zz".repeat(32)));
+  }
+}
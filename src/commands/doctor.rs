@@ -1,30 +1,46 @@
 use crate::consts::DVM_CACHE_PATH_PREFIX;
-use anyhow::Result;
-use colored::Colorize;
+use crate::output;
+use anyhow::{anyhow, Result};
 use std::fs;
+use std::path::Path;
 
 use crate::meta::DvmMeta;
-use crate::utils::{deno_bin_path, dvm_root, is_exact_version};
+use crate::utils::{deno_bin_path, dvm_root, external_deno, is_exact_version, layout, shell_quote, which_deno, Layout};
 
-pub fn exec(meta: &mut DvmMeta) -> Result<()> {
+/// paths written to the shell profile have to be text
+fn profile_value(path: &Path) -> Result<&str> {
+  path
+    .to_str()
+    .ok_or_else(|| anyhow!("{} is not valid UTF-8, please set it up manually", path.display()))
+}
+
+pub fn exec(meta: &mut DvmMeta, force_external: bool) -> Result<()> {
   // Init enviroments if need
   // actually set DVM_DIR env var if not exist.
   let home_path = dvm_root();
-  set_env::check_or_set("DVM_DIR", home_path.to_str().unwrap()).unwrap();
-  let path = set_env::get("PATH").unwrap();
-  let looking_for = deno_bin_path().parent().unwrap().to_str().unwrap().to_string();
-  let current = which::which("deno");
-
-  if let Ok(current) = current {
-    if current.to_str().unwrap().starts_with(&looking_for) {
-      println!("{}", "DVM deno bin is already set correctly.".green());
+  // `DVM_DIR` forces the classic layout, so it's not set for the XDG layout.
+  if let Layout::Classic(_) = layout() {
+    set_env::check_or_set("DVM_DIR", shell_quote(profile_value(&home_path)?)).unwrap();
+  }
+  let bin_dir = deno_bin_path().parent().unwrap().to_path_buf();
+  let in_path = std::env::var_os("PATH")
+    .map(|path| std::env::split_paths(&path).any(|it| it == bin_dir))
+    .unwrap_or(false);
+  if let Some(current) = which_deno() {
+    if current.starts_with(&bin_dir) {
+      output::success(t!("DVM deno bin is already set correctly."));
+    } else if external_deno().is_some() && !force_external {
+      output::warn(t!(
+        "{} is not managed by dvm, pass `--force-external` to put dvm's deno before it in PATH",
+        current.display()
+      ));
     } else {
-      set_env::prepend("PATH", looking_for.as_str()).unwrap();
-      println!("{}", "Please restart your shell of choice to take effects.".red());
+      set_env::prepend("PATH", profile_value(&bin_dir)?).unwrap();
+      output::error(t!("Please restart your shell of choice to take effects."));
     }
-  } else if !path.contains(looking_for.as_str()) {
-    set_env::prepend("PATH", looking_for.as_str()).unwrap();
-    println!("{}", "Please restart your shell of choice to take effects.".red());
+  } else if !in_path {
+    set_env::prepend("PATH", profile_value(&bin_dir)?).unwrap();
+    output::error(t!("Please restart your shell of choice to take effects."));
   }
 
   // migrating from old dvm cache.
@@ -40,12 +56,17 @@ pub fn exec(meta: &mut DvmMeta) -> Result<()> {
     let entry = entry.unwrap();
     let path = entry.path();
     if path.is_dir() {
-      let name = path.file_name().unwrap().to_str().unwrap();
+      let Some(name) = path.file_name().and_then(|it| it.to_str()) else {
+        continue;
+      };
       if is_exact_version(name) {
         // move to `versions` subdir
         println!(
-          "Found old dvm cache of version `{}`, migrating to new dvm cache location...",
-          name
+          "{}",
+          t!(
+            "Found old dvm cache of version `{}`, migrating to new dvm cache location...",
+            name
+          )
         );
         fs::rename(path.clone(), path.parent().unwrap().join("versions").join(name)).unwrap();
       }
@@ -53,9 +74,9 @@ pub fn exec(meta: &mut DvmMeta) -> Result<()> {
   }
 
   if dvm_root().exists() {
-    super::use_version::exec(meta, None, false).unwrap();
+    super::use_version::exec(meta, None, false, false).unwrap();
   }
 
-  println!("{}", "All fixes applied, DVM is ready to use.".green());
+  output::success(t!("All fixes applied, DVM is ready to use."));
   Ok(())
 }
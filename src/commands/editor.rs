@@ -0,0 +1,363 @@
+use crate::cli::Editor;
+use crate::meta::DvmMeta;
+use crate::utils::{best_version, deno_version_path, load_dvmrc};
+use crate::version::{local_versions, VersionArg};
+use anyhow::{anyhow, bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VSCODE_SETTINGS: &str = ".vscode/settings.json";
+const ZED_SETTINGS: &str = ".zed/settings.json";
+const JETBRAINS_SETTINGS: &str = ".idea/deno.xml";
+
+pub fn exec(meta: &DvmMeta, editor: Editor, version: Option<String>) -> Result<()> {
+  let exe_path = resolve_exe_path(meta, version)?;
+  match editor {
+    Editor::Vscode => vscode(&exe_path),
+    Editor::Jetbrains => jetbrains(&exe_path),
+    Editor::Zed => zed(&exe_path),
+  }
+}
+
+/// update a JSONC settings file in place, creating it if needed
+fn update_jsonc(file: &str, members: &[(&[&str], String)]) -> Result<()> {
+  let path = PathBuf::from(file);
+  let mut content = if path.exists() {
+    fs::read_to_string(&path)?
+  } else {
+    String::new()
+  };
+  for (key, value) in members {
+    content = set_jsonc_value(&content, key, value).map_err(|e| anyhow!("{} is not valid JSONC: {}", file, e))?;
+  }
+
+  fs::create_dir_all(path.parent().unwrap())?;
+  fs::write(&path, content)?;
+  Ok(())
+}
+
+fn vscode(exe_path: &Path) -> Result<()> {
+  let json_path = serde_json::to_string(&exe_path.to_string_lossy())?;
+  update_jsonc(
+    VSCODE_SETTINGS,
+    &[(&["deno.enable"], "true".to_string()), (&["deno.path"], json_path)],
+  )?;
+  println!("Set deno.path to {} in {}", exe_path.display(), VSCODE_SETTINGS);
+  Ok(())
+}
+
+fn zed(exe_path: &Path) -> Result<()> {
+  let json_path = serde_json::to_string(&exe_path.to_string_lossy())?;
+  update_jsonc(
+    ZED_SETTINGS,
+    &[
+      (&["lsp", "deno", "binary", "path"], json_path),
+      (&["lsp", "deno", "binary", "arguments"], "[\"lsp\"]".to_string()),
+    ],
+  )?;
+  println!("Set lsp.deno.binary to {} in {}", exe_path.display(), ZED_SETTINGS);
+  Ok(())
+}
+
+/// the Deno plugin keeps its project settings in `.idea/deno.xml`, which is owned
+/// by the plugin only, so it's regenerated as a whole.
+fn jetbrains(exe_path: &Path) -> Result<()> {
+  let path = PathBuf::from(JETBRAINS_SETTINGS);
+  fs::create_dir_all(path.parent().unwrap())?;
+  fs::write(&path, jetbrains_settings(exe_path))?;
+  println!("Set denoPath to {} in {}", exe_path.display(), JETBRAINS_SETTINGS);
+  Ok(())
+}
+
+fn jetbrains_settings(exe_path: &Path) -> String {
+  let exe_path = exe_path
+    .to_string_lossy()
+    .replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;");
+  format!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<project version="4">
+  <component name="DenoSettings">
+    <option name="denoPath" value="{}" />
+    <option name="useDeno" value="true" />
+  </component>
+</project>
+"#,
+    exe_path
+  )
+}
+
+/// resolve the version given or the one in .dvmrc to the executable of an installed version
+fn resolve_exe_path(meta: &DvmMeta, version: Option<String>) -> Result<PathBuf> {
+  let version_req = match version {
+    Some(version) => meta.resolve_version_req(&version),
+    None => load_dvmrc(),
+  };
+
+  let version = match version_req {
+    VersionArg::Exact(v) => v,
+    VersionArg::Range(r) => {
+      let versions = local_versions();
+      best_version(versions.iter().map(AsRef::as_ref), r.clone())
+        .ok_or_else(|| anyhow!("No installed version matches {}, run `dvm install` first", r))?
+    }
+  };
+
+  let exe_path = deno_version_path(&version);
+  if !exe_path.exists() {
+    bail!(
+      "deno v{} is not installed, run `dvm install {}` first",
+      version,
+      version
+    );
+  }
+  Ok(exe_path)
+}
+
+fn skip_trivia(src: &[u8], mut i: usize) -> usize {
+  while i < src.len() {
+    match src[i] {
+      b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+      b'/' if src.get(i + 1) == Some(&b'/') => {
+        while i < src.len() && src[i] != b'\n' {
+          i += 1;
+        }
+      }
+      b'/' if src.get(i + 1) == Some(&b'*') => {
+        i += 2;
+        while i + 1 < src.len() && !(src[i] == b'*' && src[i + 1] == b'/') {
+          i += 1;
+        }
+        i += 2;
+      }
+      _ => break,
+    }
+  }
+  i.min(src.len())
+}
+
+/// returns the index right after the closing quote of the string starting at `i`
+fn skip_string(src: &[u8], mut i: usize) -> Result<usize> {
+  i += 1;
+  while i < src.len() {
+    match src[i] {
+      b'\\' => i += 2,
+      b'"' => return Ok(i + 1),
+      _ => i += 1,
+    }
+  }
+  bail!("unterminated string")
+}
+
+/// returns the index right after the value starting at `i`
+fn skip_value(src: &[u8], mut i: usize) -> Result<usize> {
+  match src.get(i) {
+    Some(b'"') => skip_string(src, i),
+    Some(b'{') | Some(b'[') => {
+      let mut depth = 0;
+      while i < src.len() {
+        match src[i] {
+          b'"' => {
+            i = skip_string(src, i)?;
+            continue;
+          }
+          b'/' => {
+            let next = skip_trivia(src, i);
+            if next > i {
+              i = next;
+              continue;
+            }
+          }
+          b'{' | b'[' => depth += 1,
+          b'}' | b']' => {
+            depth -= 1;
+            if depth == 0 {
+              return Ok(i + 1);
+            }
+          }
+          _ => {}
+        }
+        i += 1;
+      }
+      bail!("unterminated object or array")
+    }
+    Some(_) => {
+      while i < src.len() && !matches!(src[i], b' ' | b'\t' | b'\r' | b'\n' | b',' | b'}' | b']' | b'/') {
+        i += 1;
+      }
+      Ok(i)
+    }
+    None => bail!("unexpected end of input"),
+  }
+}
+
+/// leading whitespace of the line that `index` is on
+fn line_indent(content: &str, index: usize) -> &str {
+  let line_start = content[..index].rfind('\n').map(|it| it + 1).unwrap_or(0);
+  let line = &content[line_start..];
+  &line[..line.len() - line.trim_start().len()]
+}
+
+///
+/// set the member at `path` of a JSONC document to the given raw json value,
+/// missing parent objects are created, comments and formatting of the rest of the
+/// document are kept as-is.
+pub fn set_jsonc_value(content: &str, path: &[&str], value: &str) -> Result<String> {
+  if content.trim().is_empty() {
+    return set_jsonc_value("{}\n", path, value);
+  }
+
+  let src = content.as_bytes();
+  let open = skip_trivia(src, 0);
+  if src.get(open) != Some(&b'{') {
+    bail!("expected an object");
+  }
+  set_in_object(content, open, path, value)
+}
+
+/// set the member at `path` of the object opened at `open`
+fn set_in_object(content: &str, open: usize, path: &[&str], value: &str) -> Result<String> {
+  let src = content.as_bytes();
+  let key = path[0];
+  let mut i = open + 1;
+  let mut last_value_end = None;
+  let mut indent = None;
+  loop {
+    i = skip_trivia(src, i);
+    match src.get(i) {
+      Some(b'}') => break,
+      Some(b',') => {
+        i += 1;
+        continue;
+      }
+      Some(b'"') => {}
+      _ => bail!("expected a member name"),
+    }
+
+    let key_start = i;
+    i = skip_string(src, i)?;
+    let name: String = serde_json::from_slice(&src[key_start..i])?;
+    let line_start = content[..key_start].rfind('\n').map(|it| it + 1).unwrap_or(0);
+    if indent.is_none() && content[line_start..key_start].trim().is_empty() {
+      indent = Some(content[line_start..key_start].to_string());
+    }
+
+    i = skip_trivia(src, i);
+    if src.get(i) != Some(&b':') {
+      bail!("expected `:`");
+    }
+    i = skip_trivia(src, i + 1);
+    let value_start = i;
+    i = skip_value(src, i)?;
+
+    if name == key {
+      if path.len() > 1 && src[value_start] == b'{' {
+        return set_in_object(content, value_start, &path[1..], value);
+      }
+      let value = nested_value(&path[1..], value, line_indent(content, key_start))?;
+      return Ok(format!("{}{}{}", &content[..value_start], value, &content[i..]));
+    }
+    last_value_end = Some(i);
+  }
+
+  let parent_indent = line_indent(content, open);
+  let indent = indent.unwrap_or_else(|| format!("{}  ", parent_indent));
+  let member = format!(
+    "{}{}: {}",
+    indent,
+    serde_json::to_string(key)?,
+    nested_value(&path[1..], value, &indent)?
+  );
+  Ok(match last_value_end {
+    Some(end) => format!("{},\n{}{}", &content[..end], member, &content[end..]),
+    None => format!(
+      "{}\n{}\n{}{}",
+      &content[..open + 1],
+      member,
+      parent_indent,
+      content[open + 1..].trim_start()
+    ),
+  })
+}
+
+/// wrap a raw json value into objects for the remaining `path`, indented to sit at `indent`
+fn nested_value(path: &[&str], value: &str, indent: &str) -> Result<String> {
+  if path.is_empty() {
+    return Ok(value.to_string());
+  }
+  let mut nested: serde_json::Value = serde_json::from_str(value)?;
+  for key in path.iter().rev() {
+    nested = serde_json::json!({ *key: nested });
+  }
+  let pretty = serde_json::to_string_pretty(&nested)?;
+  Ok(pretty.replace('\n', &format!("\n{}", indent)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_set_jsonc_value_empty() {
+    assert_eq!(
+      set_jsonc_value("", &["deno.enable"], "true").unwrap(),
+      "{\n  \"deno.enable\": true\n}\n"
+    );
+    assert_eq!(
+      set_jsonc_value("{}", &["deno.enable"], "true").unwrap(),
+      "{\n  \"deno.enable\": true\n}"
+    );
+  }
+
+  #[test]
+  fn test_set_jsonc_value_replace() {
+    let content = "{\n  // use dvm\n  \"deno.path\": \"/usr/bin/deno\", /* old */\n  \"deno.lint\": true,\n}\n";
+    assert_eq!(
+      set_jsonc_value(content, &["deno.path"], "\"/home/dvm/deno\"").unwrap(),
+      "{\n  // use dvm\n  \"deno.path\": \"/home/dvm/deno\", /* old */\n  \"deno.lint\": true,\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_set_jsonc_value_append() {
+    let content = "{\n    \"editor.rulers\": [80, 120], // rulers\n    \"files.exclude\": { \"a\": true }\n}\n";
+    assert_eq!(
+      set_jsonc_value(content, &["deno.enable"], "true").unwrap(),
+      "{\n    \"editor.rulers\": [80, 120], // rulers\n    \"files.exclude\": { \"a\": true },\n    \"deno.enable\": true\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_set_jsonc_value_nested() {
+    assert_eq!(
+      set_jsonc_value("{\n  \"theme\": \"One\"\n}\n", &["lsp", "deno", "path"], "\"deno\"").unwrap(),
+      "{\n  \"theme\": \"One\",\n  \"lsp\": {\n    \"deno\": {\n      \"path\": \"deno\"\n    }\n  }\n}\n"
+    );
+    assert_eq!(
+      set_jsonc_value(
+        "{\n  \"lsp\": {\n    \"deno\": { \"path\": \"old\" }\n  }\n}\n",
+        &["lsp", "deno", "path"],
+        "\"new\""
+      )
+      .unwrap(),
+      "{\n  \"lsp\": {\n    \"deno\": { \"path\": \"new\" }\n  }\n}\n"
+    );
+    assert_eq!(
+      set_jsonc_value("{\n  \"lsp\": {}\n}\n", &["lsp", "deno"], "1").unwrap(),
+      "{\n  \"lsp\": {\n    \"deno\": 1\n  }\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_jetbrains_settings() {
+    assert!(jetbrains_settings(Path::new("/home/a&b/deno")).contains("value=\"/home/a&amp;b/deno\""));
+  }
+
+  #[test]
+  fn test_set_jsonc_value_invalid() {
+    assert!(set_jsonc_value("[]", &["deno.enable"], "true").is_err());
+    assert!(set_jsonc_value("{ \"a\" 1 }", &["deno.enable"], "true").is_err());
+  }
+}
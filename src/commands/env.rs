@@ -0,0 +1,99 @@
+use crate::cli::EnvShell;
+use crate::commands::hook::{fish_quote, powershell_quote};
+use crate::utils::{deno_bin_path, layout, parent_process_name, shell_quote, Layout};
+use anyhow::Result;
+use std::env;
+
+/// what the install script puts in a posix profile, in the syntax of `shell`
+pub fn snippet(shell: EnvShell) -> String {
+  let bin_dir = deno_bin_path().parent().unwrap().to_string_lossy().into_owned();
+  // a dvm dir outside of the xdg layout is only found through `DVM_DIR`
+  let root = match layout() {
+    Layout::Classic(root) => Some(root.to_string_lossy().into_owned()),
+    Layout::Xdg => None,
+  };
+
+  let mut snippet = String::new();
+  match shell {
+    EnvShell::Fish => {
+      if let Some(root) = root {
+        snippet.push_str(&format!("set -q DVM_DIR; or set -gx DVM_DIR {}\n", fish_quote(&root)));
+      }
+      let bin_dir = fish_quote(&bin_dir);
+      snippet.push_str(&format!(
+        "contains -- {} $PATH; or set -gx PATH {} $PATH\n",
+        bin_dir, bin_dir
+      ));
+    }
+    EnvShell::Powershell => {
+      if let Some(root) = root {
+        snippet.push_str(&format!(
+          "if (-not $env:DVM_DIR) {{ $env:DVM_DIR = {} }}\n",
+          powershell_quote(&root)
+        ));
+      }
+      let bin_dir = powershell_quote(&bin_dir);
+      snippet.push_str(&format!(
+        "if (($env:PATH -split [IO.Path]::PathSeparator) -notcontains {}) {{\n  $env:PATH = {} + [IO.Path]::PathSeparator + $env:PATH\n}}\n",
+        bin_dir, bin_dir
+      ));
+    }
+    EnvShell::Cmd => {
+      // only `set` lines, `dvm-activate.cmd` runs them with `for /f ... do call`, which can't run an `if`,
+      // the root is `DVM_DIR` already when it's defined, so it's set again unconditionally
+      if let Some(root) = root {
+        snippet.push_str(&format!("set \"DVM_DIR={}\"\n", root));
+      }
+      snippet.push_str(&format!("set \"PATH={};%PATH%\"\n", bin_dir));
+    }
+    EnvShell::Bash | EnvShell::Zsh => {
+      if let Some(root) = root {
+        snippet.push_str(&format!(
+          "[ -n \"${{DVM_DIR-}}\" ] || export DVM_DIR={}\n",
+          shell_quote(&root)
+        ));
+      }
+      snippet.push_str(&format!(
+        "case \":$PATH:\" in *:{}:*) ;; *) export PATH={}:\"$PATH\" ;; esac\n",
+        shell_quote(&bin_dir),
+        shell_quote(&bin_dir)
+      ));
+    }
+  }
+  snippet
+}
+
+/// the shell dvm runs in, as far as the environment tells
+fn detect_shell() -> EnvShell {
+  if cfg!(windows) {
+    // `PSModulePath` is set system wide, only the process dvm runs in tells powershell apart,
+    // anything else gets the `ComSpec` shell, which is cmd
+    return match parent_process_name().map(|it| it.to_lowercase()).as_deref() {
+      Some("pwsh.exe" | "powershell.exe") => EnvShell::Powershell,
+      _ => EnvShell::Cmd,
+    };
+  }
+  match env::var("SHELL").unwrap_or_default().rsplit('/').next() {
+    Some("zsh") => EnvShell::Zsh,
+    Some("fish") => EnvShell::Fish,
+    _ => EnvShell::Bash,
+  }
+}
+
+/// print the environment dvm needs, to eval in a profile or to paste into a shell
+pub fn exec(shell: Option<EnvShell>) -> Result<()> {
+  print!("{}", snippet(shell.unwrap_or_else(detect_shell)));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_snippet() {
+    let bin_dir = deno_bin_path().parent().unwrap().to_string_lossy().into_owned();
+    assert!(snippet(EnvShell::Cmd).ends_with(&format!("set \"PATH={};%PATH%\"\n", bin_dir)));
+    assert!(snippet(EnvShell::Fish).contains(&format!("set -gx PATH {} $PATH", fish_quote(&bin_dir))));
+  }
+}
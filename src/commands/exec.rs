@@ -1,48 +1,263 @@
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use crate::{
-  consts::DVM_VERSION_LATEST,
+  config::DvmConfig,
+  consts::{DVM_ALLOW_DENO_UPGRADE_ENV, DVM_ARCH_ENV, DVM_VERSION_CANARY, DVM_VERSION_LATEST},
   meta::DvmMeta,
-  utils::{best_version, deno_version_path, is_exact_version, prompt_request},
+  output,
+  project::ProjectConfig,
+  utils::{
+    best_version, deno_dir_path, deno_target_path, deno_version_path, denort_version_path, normalize_version,
+    prompt_request, session_version, shim_target_path,
+  },
   version::{remote_versions, VersionArg},
 };
-use anyhow::Result;
-use colored::Colorize;
+use anyhow::{anyhow, bail, Result};
 use semver::Version;
+use std::fs;
 
-use super::install;
+use super::hook::nearest_config;
+use super::prefetch_targets::resolve_arch;
+use super::{compact, install};
 
-pub fn exec(meta: &mut DvmMeta, version: Option<String>, args: Vec<String>) -> Result<()> {
-  let versions = remote_versions().expect("Failed to get remote versions");
-  let version = version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string());
-  let v = version.clone();
-
-  let Some(version) = is_exact_version(&version).then_some(version).or_else(|| {
-        meta.has_alias(&v).then(|| {
-          let version_req = meta.resolve_version_req(&v);
-          match version_req {
-            VersionArg::Exact(v) => v.to_string(),
-            VersionArg::Range(r) => best_version(versions.iter().map(AsRef::as_ref), r).unwrap().to_string(),
-          }
-        })
+/// the `DENO_DIR` dvm gives to deno, the project local one takes precedence over the per-version one
+pub fn managed_deno_dir(project: &Option<(PathBuf, ProjectConfig)>, version: &str) -> Option<PathBuf> {
+  project
+    .as_ref()
+    .and_then(|(dir, config)| config.deno_dir(dir))
+    .or_else(|| DvmConfig::load().isolate_deno_dir().then(|| deno_dir_path(version)))
+}
+
+/// what a `deno upgrade` asked for, dvm installs it instead
+#[derive(Debug, Default, Eq, PartialEq)]
+struct UpgradeRequest {
+  /// the latest stable if not present
+  version: Option<String>,
+  dry_run: bool,
+}
+
+///
+/// the request of `deno upgrade` arguments, `None` for any other command and for `--output`,
+/// which writes the new deno elsewhere and leaves the managed one alone.
+fn upgrade_request(args: &[String]) -> Option<Result<UpgradeRequest>> {
+  let index = args.iter().position(|it| !it.starts_with('-'))?;
+  if args[index] != "upgrade" {
+    return None;
+  }
+  let mut request = UpgradeRequest::default();
+  let mut rest = args[index + 1..].iter();
+  while let Some(arg) = rest.next() {
+    match arg.as_str() {
+      "--output" => return None,
+      it if it.starts_with("--output=") => return None,
+      "--version" => request.version = rest.next().cloned(),
+      it if it.starts_with("--version=") => request.version = Some(it["--version=".len()..].to_string()),
+      "--canary" | "canary" => request.version = Some(DVM_VERSION_CANARY.to_string()),
+      "--dry-run" => request.dry_run = true,
+      "stable" => request.version = None,
+      "rc" | "--rc" => {
+        return Some(Err(anyhow!(t!(
+          "dvm can't upgrade to the rc channel, pass the rc version instead"
+        ))))
+      }
+      it if !it.starts_with('-') => request.version = Some(it.to_string()),
+      _ => {}
     }
-  ) else {
-    eprintln!("{}", "No such alias or version found.".red());
+  }
+  Some(Ok(request))
+}
+
+/// `deno upgrade` would overwrite the executable in the versions store, the new version is installed next to it
+fn intercept_upgrade(meta: &mut DvmMeta, args: &[String]) -> Option<Result<()>> {
+  if std::env::var_os(DVM_ALLOW_DENO_UPGRADE_ENV).is_some() {
+    return None;
+  }
+  let request = match upgrade_request(args)? {
+    Ok(request) => request,
+    Err(err) => return Some(Err(err)),
+  };
+  output::info(t!(
+    "`deno upgrade` would overwrite a version managed by dvm, running `dvm install` instead, set {} to let deno do it",
+    DVM_ALLOW_DENO_UPGRADE_ENV
+  ));
+  if request.dry_run {
+    return Some(install::dry_run(meta, request.version, false));
+  }
+  Some(install::exec(meta, false, request.version))
+}
+
+pub fn exec(meta: &mut DvmMeta, version: Option<String>, args: Vec<String>) -> Result<()> {
+  if let Some(result) = intercept_upgrade(meta, &args) {
+    return result;
+  }
+  exec_in(meta, version, args, ProjectConfig::load())
+}
+
+/// whether dvm runs as the shim of `activation = "shim"`, i.e. as the deno of the dvm bin directory
+pub fn is_shim() -> bool {
+  std::env::args_os()
+    .next()
+    .map(PathBuf::from)
+    .and_then(|it| it.file_stem().map(|stem| stem == "deno"))
+    .unwrap_or(false)
+}
+
+///
+/// run the executable `dvm use` put behind the shim, a version of the store runs through `exec`
+/// like `dvm exec --version <version>` does, canary has no version and runs as it is.
+/// `DVM_DENO_VERSION` takes the place of the version `dvm use` activated.
+pub fn shim(meta: &mut DvmMeta, args: Vec<String>) -> Result<()> {
+  if let Some(version) = session_version() {
+    return exec(meta, Some(version), args);
+  }
+  let target = fs::read_to_string(shim_target_path())
+    .map_err(|err| anyhow!(t!("{} is not a dvm shim: {}", shim_target_path().display(), err)))?;
+  let exe_path = PathBuf::from(target.trim_end());
+  let version = exe_path
+    .parent()
+    .and_then(Path::file_name)
+    .and_then(|it| it.to_str())
+    .filter(|it| Version::parse(it).is_ok());
+  if let Some(version) = version {
+    return exec(meta, Some(version.to_string()), args);
+  }
+  if let Some(result) = intercept_upgrade(meta, &args) {
+    return result;
+  }
+  let status = std::process::Command::new(exe_path).args(args).status()?;
+  if !status.success() {
+    std::process::exit(status.code().unwrap_or(1));
+  }
+  Ok(())
+}
+
+/// a script run through a `#!/usr/bin/env -S dvm exec --shebang` line, the first argument not being a deno flag
+fn script_index(args: &[String]) -> Option<usize> {
+  args.iter().position(|it| !it.starts_with('-'))
+}
+
+/// whether the arguments of `dvm exec --` are the ones of a shebang line, i.e. a script file follows the flags
+pub fn is_script(args: &[String]) -> bool {
+  script_index(args)
+    .map(|it| Path::new(&args[it]).is_file())
+    .unwrap_or(false)
+}
+
+///
+/// `deno run` a script with the version pinned by the `.dvmrc` nearest to the script rather than the working directory,
+/// the arguments before the script are flags of `deno run`, e.g. `#!/usr/bin/env -S dvm exec --shebang --allow-net`.
+pub fn exec_script(meta: &mut DvmMeta, version: Option<String>, args: Vec<String>) -> Result<()> {
+  let Some(index) = script_index(&args) else {
+    bail!(t!("No script given to run"));
+  };
+  let script = fs::canonicalize(&args[index])?;
+  let project = script
+    .parent()
+    .and_then(nearest_config)
+    .and_then(|config| ProjectConfig::load_in(config.parent()?.to_path_buf()));
+  // a range in the `.dvmrc` runs the best installed match
+  let pinned = project
+    .as_ref()
+    .and_then(|(_, config)| config.version.clone())
+    .map(|required| {
+      meta
+        .resolve_local_version(&required)
+        .map(|it| it.to_string())
+        .unwrap_or(required)
+    });
+  let mut deno_args = vec!["run".to_string()];
+  deno_args.extend(args);
+  exec_in(meta, version.or(pinned), deno_args, project)
+}
+
+fn exec_in(
+  meta: &mut DvmMeta,
+  version: Option<String>,
+  args: Vec<String>,
+  project: Option<(PathBuf, ProjectConfig)>,
+) -> Result<()> {
+  // `DVM_DENO_VERSION` pins the session above the default alias
+  let version = version
+    .or_else(session_version)
+    .unwrap_or_else(|| DVM_VERSION_LATEST.to_string());
+  let v = normalize_version(&version);
+
+  let Some(parsed) = Version::parse(v).ok().or_else(|| {
+    meta.has_alias(v).then(|| {
+      let version_req = meta.resolve_version_req(v);
+      match version_req {
+        VersionArg::Exact(v) => v,
+        VersionArg::Range(r) => {
+          let versions = remote_versions().expect("Failed to get remote versions");
+          best_version(versions.iter().map(AsRef::as_ref), r).unwrap()
+        }
+      }
+    })
+  }) else {
+    output::error(t!("No such alias or version found."));
+    output::suggest(v, &meta.version_names());
     std::process::exit(1);
   };
+  let version = parsed.to_string();
 
-  let executable_path = deno_version_path(&Version::parse(&version).unwrap());
+  compact::restore(&parsed)?;
+  // `DVM_ARCH` runs another architecture installed with `dvm install --arch`
+  let target = match std::env::var(DVM_ARCH_ENV) {
+    Ok(arch) => Some(resolve_arch(&arch)?).filter(|it| *it != install::TARGET),
+    Err(_) => None,
+  };
+  let executable_path = match target {
+    Some(target) => deno_target_path(&parsed, target),
+    None => deno_version_path(&parsed),
+  };
 
+  if let Some(target) = target.filter(|_| !executable_path.exists()) {
+    bail!(t!(
+      "deno v{} for {} is not installed, run `dvm install --arch {} {}`",
+      version,
+      target,
+      target,
+      version
+    ));
+  }
   if !executable_path.exists() {
-    if prompt_request(format!("deno v{} is not installed. do you want to install it?", version).as_str()) {
+    if prompt_request(t!("deno v{} is not installed. do you want to install it?", version).as_str()) {
       install::exec(meta, true, Some(version.clone())).unwrap_or_else(|_| panic!("Failed to install deno {}", version));
     } else {
-      eprintln!("{}", "No such version found.".red());
+      output::error(t!("No such version found."));
       std::process::exit(1);
     }
   }
 
-  let mut cmd = std::process::Command::new(executable_path)
+  meta.record_use(&version);
+  let mut cmd = std::process::Command::new(executable_path);
+  if std::env::var_os("DENO_DIR").is_none() {
+    if let Some(deno_dir) = managed_deno_dir(&project, &version) {
+      std::fs::create_dir_all(&deno_dir)?;
+      cmd.env("DENO_DIR", deno_dir);
+    }
+  }
+
+  // `deno compile` picks up the managed runtime instead of downloading one
+  let denort_path = denort_version_path(&parsed);
+  if denort_path.exists() && std::env::var_os("DENORT_BIN").is_none() {
+    cmd.env("DENORT_BIN", denort_path);
+  }
+
+  // variables of the project override the ones of the version,
+  // and both never override what's already set in the environment.
+  let mut env = meta.version_env(&parsed);
+  if let Some((_, config)) = &project {
+    env.extend(config.env.iter().cloned());
+  }
+  for (name, value) in env {
+    if std::env::var_os(&name).is_none() {
+      cmd.env(name, value);
+    }
+  }
+
+  let mut cmd = cmd
     .args(args)
     .stderr(Stdio::inherit())
     .stdout(Stdio::inherit())
@@ -50,6 +265,40 @@ pub fn exec(meta: &mut DvmMeta, version: Option<String>, args: Vec<String>) -> R
     .spawn()
     .unwrap();
 
-  cmd.wait().unwrap();
+  // scripts run through dvm exit like deno did
+  let status = cmd.wait()?;
+  if !status.success() {
+    std::process::exit(status.code().unwrap_or(1));
+  }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn request(args: &[&str]) -> Option<UpgradeRequest> {
+    upgrade_request(&args.iter().map(ToString::to_string).collect::<Vec<_>>()).map(Result::unwrap)
+  }
+
+  #[test]
+  fn test_upgrade_request() {
+    assert_eq!(request(&["run", "upgrade.ts"]), None);
+    assert_eq!(request(&["upgrade"]), Some(UpgradeRequest::default()));
+    assert_eq!(
+      request(&["upgrade", "--version", "1.29.0", "--dry-run"]),
+      Some(UpgradeRequest {
+        version: Some("1.29.0".to_string()),
+        dry_run: true
+      })
+    );
+    assert_eq!(
+      request(&["upgrade", "--canary"]).unwrap().version.as_deref(),
+      Some("canary")
+    );
+    assert_eq!(request(&["upgrade", "--output", "/tmp/deno"]), None);
+    assert!(upgrade_request(&["upgrade".to_string(), "rc".to_string()])
+      .unwrap()
+      .is_err());
+  }
+}
@@ -0,0 +1,30 @@
+use crate::meta::DvmMeta;
+use crate::utils::format_timestamp;
+use anyhow::Result;
+use colored::Colorize;
+
+/// the recorded activations, newest first, `limit` keeps only the last ones
+pub fn exec(meta: &DvmMeta, limit: Option<usize>) -> Result<()> {
+  if meta.history.is_empty() {
+    println!("{}", t!("No version has been activated yet"));
+    return Ok(());
+  }
+
+  let width = meta.history.iter().map(|it| it.version.len()).max().unwrap_or_default();
+  for entry in meta.history.iter().rev().take(limit.unwrap_or(usize::MAX)) {
+    let required = if entry.required == entry.version {
+      String::new()
+    } else {
+      format!(" ({})", entry.required)
+    };
+    println!(
+      "{}  {:<width$}  {}{}",
+      format_timestamp(entry.time).bright_black(),
+      entry.version,
+      entry.dir,
+      required.bright_black(),
+      width = width
+    );
+  }
+  Ok(())
+}
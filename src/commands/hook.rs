@@ -0,0 +1,308 @@
+use crate::cli::HookShell;
+use crate::consts::{
+  DENO_EXE, DVM_CANARY_PATH_PREFIX, DVM_CONFIG_FILENAME, DVM_RESOLVE_CACHE_FILENAME, DVM_VERSION_CANARY,
+  DVM_VERSION_SYSTEM,
+};
+use crate::meta::DvmMeta;
+use crate::project::ProjectConfig;
+use crate::utils::{deno_version_dir, dvm_cache_root, dvm_root, session_version, session_version_first, shell_quote};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+/// `__DVM__` is replaced with the path of dvm, `_DVM_PATH` is what the hook put in front of PATH
+const BASH_HOOK: &str = r#"_dvm_hook() {
+  local dir
+  dir="$(__DVM__ resolve-for-cwd)"
+  if [ "$dir" != "${_DVM_PATH-}" ]; then
+    if [ -n "${_DVM_PATH-}" ]; then
+      PATH="${PATH//"$_DVM_PATH:"/}"
+    fi
+    if [ -n "$dir" ]; then
+      PATH="$dir:$PATH"
+    fi
+    _DVM_PATH="$dir"
+  fi
+}
+case ";${PROMPT_COMMAND-};" in
+  *";_dvm_hook;"*) ;;
+  *) PROMPT_COMMAND="_dvm_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+_dvm_hook
+"#;
+
+const ZSH_HOOK: &str = r#"_dvm_hook() {
+  local dir
+  dir="$(__DVM__ resolve-for-cwd)"
+  if [[ "$dir" != "${_DVM_PATH-}" ]]; then
+    if [[ -n "${_DVM_PATH-}" ]]; then
+      PATH="${PATH//"$_DVM_PATH:"/}"
+    fi
+    if [[ -n "$dir" ]]; then
+      PATH="$dir:$PATH"
+    fi
+    _DVM_PATH="$dir"
+  fi
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _dvm_hook
+add-zsh-hook precmd _dvm_hook
+_dvm_hook
+"#;
+
+const FISH_HOOK: &str = r#"function __dvm_hook --on-event fish_prompt
+  set -l dir (__DVM__ resolve-for-cwd)
+  if test "$dir" != "$__dvm_path"
+    if test -n "$__dvm_path"; and set -l index (contains -i -- $__dvm_path $PATH)
+      set -e PATH[$index]
+    end
+    if test -n "$dir"
+      set -gx PATH $dir $PATH
+    end
+    set -g __dvm_path $dir
+  end
+end
+__dvm_hook
+"#;
+
+const POWERSHELL_HOOK: &str = r#"function global:__dvm_hook {
+  $dir = __DVM__ resolve-for-cwd
+  if ("$dir" -ne "$env:_DVM_PATH") {
+    $separator = [IO.Path]::PathSeparator
+    $paths = $env:PATH -split $separator | Where-Object { $_ -and $_ -ne $env:_DVM_PATH }
+    if ($dir) {
+      $paths = @($dir) + $paths
+    }
+    $env:PATH = $paths -join $separator
+    $env:_DVM_PATH = $dir
+  }
+}
+# wrapped once, evaluating the hook again, e.g. with `. $PROFILE`, would make the prompt call itself
+if (-not $global:__dvm_hooked) {
+  $global:__dvm_hooked = $true
+  # the prompt picks up a `.dvmrc` changed while the shell sits in the project, e.g. by `git pull`
+  $global:__dvm_previous_prompt = $function:prompt
+  function global:prompt {
+    __dvm_hook
+    & $global:__dvm_previous_prompt
+  }
+  $__dvm_previous_action = $ExecutionContext.InvokeCommand.LocationChangedAction
+  $ExecutionContext.InvokeCommand.LocationChangedAction = {
+    __dvm_hook
+    if ($__dvm_previous_action) {
+      & $__dvm_previous_action @args
+    }
+  }.GetNewClosure()
+}
+__dvm_hook
+"#;
+
+/// a powershell string literal, single quotes are doubled
+pub fn powershell_quote(value: &str) -> String {
+  format!("'{}'", value.replace('\'', "''"))
+}
+
+/// a fish string literal, single quotes only need `\` and `'` escaped
+pub fn fish_quote(value: &str) -> String {
+  format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// the path of this very dvm, the hooks and wrappers call it without a PATH lookup
+pub fn dvm_exe() -> String {
+  env::current_exe()
+    .map(|it| it.to_string_lossy().into_owned())
+    .unwrap_or_else(|_| "dvm".to_string())
+}
+
+/// the hook of a shell
+pub fn script(shell: HookShell) -> String {
+  let dvm = dvm_exe();
+  let (hook, dvm) = match shell {
+    HookShell::Bash => (BASH_HOOK, format!("command {}", shell_quote(&dvm))),
+    HookShell::Zsh => (ZSH_HOOK, format!("command {}", shell_quote(&dvm))),
+    HookShell::Fish => (FISH_HOOK, format!("command {}", fish_quote(&dvm))),
+    HookShell::Powershell => (POWERSHELL_HOOK, format!("& {}", powershell_quote(&dvm))),
+  };
+  hook.replace("__DVM__", &dvm)
+}
+
+/// print the hook to eval, or how to set it up
+pub fn exec(shell: HookShell, eval: bool) -> Result<()> {
+  if eval {
+    print!("{}", script(shell));
+    return Ok(());
+  }
+  let (profile, line) = match shell {
+    HookShell::Bash => ("~/.bashrc", r#"eval "$(dvm hook bash --eval)""#),
+    HookShell::Zsh => ("~/.zshrc", r#"eval "$(dvm hook zsh --eval)""#),
+    HookShell::Fish => ("~/.config/fish/config.fish", "dvm hook fish --eval | source"),
+    HookShell::Powershell => (
+      "$PROFILE",
+      "Invoke-Expression (& dvm hook powershell --eval | Out-String)",
+    ),
+  };
+  println!("{}", t!("Add this line to {} to switch deno per project:", profile));
+  println!("  {}", line);
+  Ok(())
+}
+
+/// what a `.dvmrc` resolved to, valid while neither it nor the meta changes, `None` if it's not installed
+#[derive(Deserialize, Serialize)]
+struct Memo {
+  mtime: u128,
+  meta_mtime: u128,
+  dir: Option<PathBuf>,
+}
+
+fn mtime(path: &Path) -> Option<u128> {
+  let modified = fs::metadata(path).ok()?.modified().ok()?;
+  Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+/// the nearest `.dvmrc` from `dir` up, the one in home holds the global version and is no project
+pub fn nearest_config(dir: &Path) -> Option<PathBuf> {
+  let home = dirs::home_dir();
+  dir
+    .ancestors()
+    .take_while(|it| Some(*it) != home.as_deref())
+    .map(|it| it.join(DVM_CONFIG_FILENAME))
+    .find(|it| it.is_file())
+}
+
+/// the version a `.dvmrc` asks for
+fn required(config: &Path) -> Option<String> {
+  let content = fs::read_to_string(config).ok()?;
+  ProjectConfig::from_str(&content).ok()?.version
+}
+
+/// the bin directory of the deno a `.dvmrc` asks for, `None` if it's not installed
+fn resolve(required: &str) -> Option<PathBuf> {
+  let dir = match required {
+    DVM_VERSION_CANARY => dvm_root().join(DVM_CANARY_PATH_PREFIX),
+    DVM_VERSION_SYSTEM => return None,
+    required => deno_version_dir(&DvmMeta::new().resolve_local_version(required)?),
+  };
+  dir.join(DENO_EXE).is_file().then_some(dir)
+}
+
+/// what to tell about a `.dvmrc` that changed since the last prompt, nothing the first time it's seen
+fn notice(config: &Path, changed: bool, required: Option<&str>, dir: Option<&Path>) -> Option<String> {
+  let required = required.filter(|_| changed)?;
+  Some(match dir {
+    None if required != DVM_VERSION_SYSTEM => t!(
+      "dvm: {} changed, deno {} is not installed yet, run `dvm install` in {}",
+      config.display(),
+      required,
+      config.parent().unwrap_or(config).display()
+    ),
+    _ => t!("dvm: {} changed, now using deno {}", config.display(), required),
+  })
+}
+
+///
+/// print the bin directory of the nearest project, nothing outside of projects unless the session pins one,
+/// the shell hook runs it on every prompt, so it's memoized by the mtimes of the `.dvmrc` and the meta.
+/// a `.dvmrc` changed while the shell sits in the project, e.g. by `git pull`, takes effect on the next prompt,
+/// saying so on stderr, a version it asks for that isn't installed is looked up again once the meta changes.
+/// `DVM_DENO_VERSION` is used outside of projects, and in them too with `deno-version-precedence = "env"`.
+pub fn resolve_for_cwd() -> Result<()> {
+  let config = env::current_dir().ok().and_then(|it| nearest_config(&it));
+  // the variable can change between two prompts, it's not memoized
+  if let Some(session) = session_version().filter(|_| config.is_none() || session_version_first()) {
+    if let Some(dir) = resolve(&session) {
+      println!("{}", dir.display());
+    }
+    return Ok(());
+  }
+  let Some(config) = config else {
+    return Ok(());
+  };
+  let Some(config_mtime) = mtime(&config) else {
+    return Ok(());
+  };
+  let meta_mtime = mtime(&DvmMeta::path()).unwrap_or_default();
+
+  let memo_path = dvm_cache_root().join(DVM_RESOLVE_CACHE_FILENAME);
+  let mut memos: HashMap<PathBuf, Memo> = fs::read(&memo_path)
+    .ok()
+    .and_then(|it| serde_json::from_slice(&it).ok())
+    .unwrap_or_default();
+  let previous = memos.get(&config);
+  if let Some(memo) = previous {
+    if memo.mtime == config_mtime && memo.meta_mtime == meta_mtime && memo.dir.as_ref().is_none_or(|it| it.is_dir()) {
+      if let Some(dir) = &memo.dir {
+        println!("{}", dir.display());
+      }
+      return Ok(());
+    }
+  }
+
+  let changed = previous.is_some_and(|it| it.mtime != config_mtime);
+  let required = required(&config);
+  let dir = required.as_deref().and_then(resolve);
+  if let Some(dir) = &dir {
+    println!("{}", dir.display());
+  }
+  if let Some(notice) = notice(&config, changed, required.as_deref(), dir.as_deref()) {
+    eprintln!("{}", notice);
+  }
+  memos.insert(
+    config,
+    Memo {
+      mtime: config_mtime,
+      meta_mtime,
+      dir,
+    },
+  );
+  memos.retain(|path, _| path.is_file());
+  fs::create_dir_all(memo_path.parent().unwrap())?;
+  fs::write(memo_path, serde_json::to_vec(&memos)?)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_powershell_quote() {
+    assert_eq!(powershell_quote(r"C:\Users\me\dvm.exe"), r"'C:\Users\me\dvm.exe'");
+    assert_eq!(powershell_quote("it's"), "'it''s'");
+  }
+
+  #[test]
+  fn test_fish_quote() {
+    assert_eq!(fish_quote("/home/me/.dvm/bin"), "'/home/me/.dvm/bin'");
+    assert_eq!(fish_quote(r"it's C:\dvm"), r"'it\'s C:\\dvm'");
+  }
+
+  #[test]
+  fn test_nearest_config() {
+    let root = env::temp_dir().join(format!("dvm-nearest-{}", std::process::id()));
+    let nested = root.join("src").join("lib");
+    fs::create_dir_all(&nested).unwrap();
+    assert_eq!(nearest_config(&nested), None);
+    fs::write(root.join(DVM_CONFIG_FILENAME), "1.28.0\n").unwrap();
+    assert_eq!(nearest_config(&nested), Some(root.join(DVM_CONFIG_FILENAME)));
+    fs::remove_dir_all(root).unwrap();
+  }
+
+  #[test]
+  fn test_notice() {
+    let config = Path::new("/work/app/.dvmrc");
+    assert_eq!(notice(config, false, Some("1.28.0"), None), None);
+    assert_eq!(
+      notice(config, true, Some("1.28.0"), Some(Path::new("/dvm/versions/1.28.0"))),
+      Some("dvm: /work/app/.dvmrc changed, now using deno 1.28.0".to_string())
+    );
+    assert!(notice(config, true, Some("1.29.0"), None)
+      .unwrap()
+      .ends_with("run `dvm install` in /work/app"));
+    assert_eq!(notice(config, true, None, None), None);
+  }
+}
@@ -10,5 +10,8 @@ pub fn exec() -> Result<()> {
     version::current_version().unwrap_or_else(|| String::from("-")),
     utils::dvm_root().as_path().to_string_lossy(),
   );
+  if let Some(system_root) = utils::dvm_system_root() {
+    println!("dvm system dir {}", system_root.to_string_lossy());
+  }
   Ok(())
 }
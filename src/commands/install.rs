@@ -1,128 +1,484 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 // Copyright 2020-2022 justjavac. All rights reserved. MIT license.
-use super::use_version;
-use crate::consts::{DVM_CACHE_PATH_PREFIX, DVM_CANARY_PATH_PREFIX, DVM_VERSION_CANARY, DVM_VERSION_LATEST};
+use super::prefetch_targets::resolve_target;
+use super::{compact, use_version};
+use crate::archive::{self, UnpackError};
+use crate::archive_cache;
+use crate::assets::{self, Asset};
+use crate::config::DvmConfig;
+use crate::consts::{DVM_CANARY_PATH_PREFIX, DVM_VERSION_CANARY, DVM_VERSION_LATEST, DVM_VERSION_LTS};
+use crate::hooks::{self, Hook};
 use crate::meta::DvmMeta;
-use crate::utils::{deno_canary_path, deno_version_path, dvm_root};
-use crate::version::get_latest_canary;
-use anyhow::Result;
+use crate::output::{self, Event};
+use crate::plan::{self, Plan};
+use crate::timings::{self, Phase};
+use crate::utils::{
+  deno_canary_path, deno_target_path, deno_version_dir, deno_version_path, denort_version_path, dvm_root,
+  ensure_versions_writable, normalize_version,
+};
+use crate::version::{get_latest_canary, remote_versions};
+use crate::{delta, glibc, http, http_cache, install_lock, interrupt, lts, policy, provenance, signature, source};
+use anyhow::{anyhow, bail, Result};
 use cfg_if::cfg_if;
 use semver::Version;
 use std::fs;
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::path::PathBuf;
 use std::string::String;
 
+const UNSUPPORTED_TARGET: &str = "unsupported";
+
 cfg_if! {
   if #[cfg(windows)] {
-    const ARCHIVE_NAME: &str = "deno-x86_64-pc-windows-msvc.zip";
+    pub const TARGET: &str = "x86_64-pc-windows-msvc";
+    pub const ARCHIVE_NAME: &str = "deno-x86_64-pc-windows-msvc.zip";
   } else if #[cfg(all(target_os = "macos", target_arch = "aarch64"))] {
-    const ARCHIVE_NAME: &str = "deno-aarch64-apple-darwin.zip";
+    pub const TARGET: &str = "aarch64-apple-darwin";
+    pub const ARCHIVE_NAME: &str = "deno-aarch64-apple-darwin.zip";
   } else if #[cfg(all(target_os = "macos", target_arch = "x86_64"))] {
-    const ARCHIVE_NAME: &str = "deno-x86_64-apple-darwin.zip";
-  } else if #[cfg(target_os = "linux")] {
-    const ARCHIVE_NAME: &str = "deno-x86_64-unknown-linux-gnu.zip";
+    pub const TARGET: &str = "x86_64-apple-darwin";
+    pub const ARCHIVE_NAME: &str = "deno-x86_64-apple-darwin.zip";
+  } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+    pub const TARGET: &str = "aarch64-unknown-linux-gnu";
+    pub const ARCHIVE_NAME: &str = "deno-aarch64-unknown-linux-gnu.zip";
+  } else if #[cfg(all(target_os = "linux", target_arch = "x86_64"))] {
+    pub const TARGET: &str = "x86_64-unknown-linux-gnu";
+    pub const ARCHIVE_NAME: &str = "deno-x86_64-unknown-linux-gnu.zip";
+  } else {
+    // no official binaries, `install --build-from-source` is the way there
+    pub const TARGET: &str = UNSUPPORTED_TARGET;
+    pub const ARCHIVE_NAME: &str = "deno-unsupported.zip";
   }
 }
 
 pub fn exec(meta: &DvmMeta, no_use: bool, version: Option<String>) -> Result<()> {
+  install(meta, no_use, false, version).map(|_| ())
+}
+
+/// install the given version (or the latest one when absent),
+/// returns the resolved version and the path of the installed executable.
+/// `denort` downloads the runtime of `deno compile` as well, regardless of the config.
+pub fn install(meta: &DvmMeta, no_use: bool, denort: bool, version: Option<String>) -> Result<(String, PathBuf)> {
+  if TARGET == UNSUPPORTED_TARGET {
+    bail!(t!(
+      "deno has no binaries for this platform, pass `--build-from-source` to build it"
+    ));
+  }
+  let version = version.map(|it| normalize_version(&it).to_string());
   if let Some(version) = version.clone() {
     if version == *DVM_VERSION_CANARY {
+      policy::check_canary()?;
       let canary_path = deno_canary_path();
       std::fs::create_dir_all(canary_path.parent().unwrap())?;
-      let hash = get_latest_canary(&meta.registry).expect("Failed to get latest canary");
-      let data = download_canary(&meta.registry, &hash)?;
+      let hash = {
+        let _timer = timings::start(Phase::Resolve);
+        get_latest_canary(&meta.registry()).expect("Failed to get latest canary")
+      };
+      output::event(Event::Resolve {
+        version: DVM_VERSION_CANARY,
+      });
+      hooks::run(Hook::PreInstall, DVM_VERSION_CANARY, &canary_path)?;
+      let lock = install_lock::acquire(DVM_VERSION_CANARY)?;
+      let data = http::block_on(download_canary(&meta.registry(), &hash))?;
+      let extracting = timings::start(Phase::Extract);
       unpack_canary(data)?;
+      drop(extracting);
+      drop(lock);
+      hooks::run(Hook::PostInstall, DVM_VERSION_CANARY, &canary_path)?;
+      if denort {
+        output::warn(t!("denort is not available for canary, skipped"));
+      }
 
       if !no_use {
         use_version::use_canary_bin_path(false).unwrap();
       }
 
-      return Ok(());
+      output::event(Event::Done {
+        version: DVM_VERSION_CANARY,
+        path: &canary_path,
+      });
+      return Ok((DVM_VERSION_CANARY.to_string(), canary_path));
     }
   }
 
+  let resolving = timings::start(Phase::Resolve);
   let install_version = match version {
+    Some(ref passed_version) if passed_version == DVM_VERSION_LTS => lts::latest()?,
     Some(ref passed_version) => match Version::parse(passed_version) {
       Ok(ver) => ver,
       Err(_) => {
-        eprintln!("Invalid semver {}", passed_version);
+        eprintln!("{}", t!("Invalid semver {}", passed_version));
         std::process::exit(1)
       }
     },
 
-    None => get_latest_version(&meta.registry)?,
+    None => get_latest_version(&meta.registry())?,
   };
 
+  output::event(Event::Resolve {
+    version: &install_version.to_string(),
+  });
+  policy::check(&install_version)?;
+  drop(resolving);
+  compact::restore(&install_version)?;
   let exe_path = deno_version_path(&install_version);
+  let denort_path = denort_version_path(&install_version);
+  let wants_denort = denort || DvmConfig::load().denort();
+  let mut installed = exe_path.exists();
+  let mut with_denort = wants_denort && !denort_path.exists();
+
+  // another process installing the same version into a shared store is waited for
+  let mut lock = None;
+  if !installed || with_denort {
+    ensure_versions_writable()?;
+    if !installed {
+      glibc::check(&install_version)?;
+    }
+    lock = Some(install_lock::acquire(&install_version.to_string())?);
+    installed = exe_path.exists();
+    with_denort = wants_denort && !denort_path.exists();
+  }
 
-  if exe_path.exists() {
-    println!("Version v{} is already installed", install_version);
-  } else {
-    let archive_data = download_package(&compose_url_to_exec(&meta.registry, &install_version), &install_version)?;
-    unpack(archive_data, &install_version)?;
+  if installed {
+    output::info(t!("Version v{} is already installed", install_version));
+  }
+  if !installed || with_denort {
+    if !installed {
+      hooks::run(Hook::PreInstall, &install_version.to_string(), &exe_path)?;
+    }
+    // deno and denort are downloaded and unpacked side by side
+    let deno = async {
+      if installed {
+        return Ok(true);
+      }
+      install_deno(&meta.registry(), &install_version).await
+    };
+    let denort = async {
+      // old versions have no denort release, which shouldn't fail the install
+      if with_denort {
+        if let Err(err) = install_denort(&meta.registry(), &install_version).await {
+          output::warn(t!("denort is not available for deno v{}: {}", install_version, err));
+        }
+      }
+      Ok(())
+    };
+    let (found, ()) = http::block_on(async { tokio::try_join!(deno, denort) })?;
+    if !found {
+      // exiting skips the drop
+      drop(lock.take());
+      output::info(t!("Version has not been found, aborting"));
+      if let Ok(versions) = remote_versions() {
+        output::suggest(&install_version.to_string(), &versions);
+      }
+      std::process::exit(1)
+    }
+    if !installed {
+      hooks::run(Hook::PostInstall, &install_version.to_string(), &exe_path)?;
+    }
   }
+  drop(lock);
 
   if !no_use {
     use_version::use_this_bin_path(
       &exe_path,
       &install_version,
-      version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string()),
+      // `lts` isn't read back from a .dvmrc, the version it resolved to is written instead
+      match version {
+        Some(version) if version == DVM_VERSION_LTS => install_version.to_string(),
+        version => version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string()),
+      },
       false,
     )?;
   }
 
-  Ok(())
+  output::event(Event::Done {
+    version: &install_version.to_string(),
+    path: &exe_path,
+  });
+  Ok((install_version.to_string(), exe_path))
 }
 
-fn get_latest_version(registry: &str) -> Result<Version> {
-  println!("Checking for latest version");
+/// the url of a fork, `owner/name` on GitHub or a repo on a host with GitHub's releases layout
+fn repo_url(repo: &str) -> Result<String> {
+  if repo.starts_with("http://") || repo.starts_with("https://") {
+    return Ok(repo.trim_end_matches('/').to_string());
+  }
+  match repo.split('/').collect::<Vec<_>>().as_slice() {
+    [owner, name] if !owner.is_empty() && !name.is_empty() => Ok(format!("https://github.com/{}/{}", owner, name)),
+    _ => bail!(t!("`{}` is neither `owner/name` nor the url of a repository", repo)),
+  }
+}
 
-  let response = tinyget::get(format!("{}release-latest.txt", registry)).send()?;
+///
+/// install a build of a fork from the releases of its repo, the assets are named like deno's own,
+/// the version is tagged with the repo it came from, so `dvm list` tells it apart from upstream.
+/// forks publish no signatures, `--require-signature` refuses them.
+pub fn install_from_repo(
+  meta: &mut DvmMeta,
+  repo: &str,
+  no_use: bool,
+  version: Option<String>,
+) -> Result<(String, PathBuf)> {
+  let repo = repo_url(repo)?;
+  let Some(version) = version else {
+    bail!(t!("A fork has no latest version, pass the version to install"));
+  };
+  let version = Version::parse(normalize_version(&version)).map_err(|_| anyhow!(t!("Invalid semver {}", version)))?;
+  if signature::is_required() {
+    bail!(t!("Builds from {} can't be verified", repo));
+  }
+  policy::check(&version)?;
+  let exe_path = deno_version_path(&version);
+  let lock = install_lock::acquire(&version.to_string())?;
+  if exe_path.exists() {
+    // a build of upstream or of another fork is never tagged as this one's
+    match meta.origins.get(&version.to_string()) {
+      Some(origin) if *origin == repo => output::info(t!("Version v{} is already installed", version)),
+      origin => bail!(t!(
+        "deno v{} is already installed from {}, run `dvm uninstall {}` first",
+        version,
+        origin.map_or("upstream", String::as_str),
+        version
+      )),
+    }
+  } else {
+    ensure_versions_writable()?;
+    let asset = deno_asset(&version)?;
+    let url = format!("{}/releases/download/v{}/{}", repo, version, asset.name);
+    hooks::run(Hook::PreInstall, &version.to_string(), &exe_path)?;
+    let Some(data) = http::block_on(source::fetch(std::slice::from_ref(&url)))? else {
+      bail!(t!("{} has not been found", url));
+    };
+    unpack(data, asset.format.archive_file(), &version)?;
+    meta.set_origin(&version.to_string(), &repo);
+    hooks::run(Hook::PostInstall, &version.to_string(), &exe_path)?;
+  }
+  drop(lock);
 
-  let body = response.as_str()?;
-  let v = body.trim().replace('v', "");
-  println!("The latest version is v{}", &v);
-  Ok(Version::parse(&v).unwrap())
+  if !no_use {
+    use_version::use_this_bin_path(&exe_path, &version, version.to_string(), false)?;
+  }
+  Ok((version.to_string(), exe_path))
 }
 
-fn download_package(url: &str, version: &Version) -> Result<Vec<u8>> {
-  println!("downloading {}", &url);
+///
+/// install the build of another architecture of this os next to the native one, e.g. the x86_64 build
+/// on apple silicon for native extensions that only exist for it, both are kept side by side.
+pub fn install_arch(
+  meta: &mut DvmMeta,
+  no_use: bool,
+  version: Option<String>,
+  target: &str,
+) -> Result<(String, PathBuf)> {
+  if target == TARGET {
+    return install(meta, no_use, false, version);
+  }
+  let version = match version.as_deref().map(normalize_version) {
+    Some(DVM_VERSION_CANARY) => bail!(t!("The canary has no builds for other architectures")),
+    Some(DVM_VERSION_LTS) => lts::latest()?,
+    Some(version) => Version::parse(version).map_err(|_| anyhow!(t!("Invalid semver {}", version)))?,
+    None => get_latest_version(&meta.registry())?,
+  };
+  policy::check(&version)?;
+  let asset =
+    assets::deno(&version, target).ok_or_else(|| anyhow!(t!("deno v{} has no build for {}", version, target)))?;
+  let exe_path = deno_target_path(&version, target);
+  let lock = install_lock::acquire(&format!("{}-{}", version, target))?;
+  if exe_path.exists() {
+    output::info(t!("deno v{} for {} is already installed", version, target));
+  } else {
+    ensure_versions_writable()?;
+    hooks::run(Hook::PreInstall, &version.to_string(), &exe_path)?;
+    let Some(data) = http::block_on(archive_cache::fetch(
+      &meta.registry(),
+      &version,
+      &asset.target,
+      &asset.name,
+    ))?
+    else {
+      bail!(t!("{} has not been found", asset.name));
+    };
+    http::block_on(async {
+      signature::check(&meta.registry(), &version, &asset.target, &asset.name, &data).await?;
+      provenance::check(&meta.registry(), &version, &asset.target, &asset.name, &data).await
+    })?;
+    let target_dir = exe_path.parent().unwrap().to_path_buf();
+    let _staged = interrupt::stage(&target_dir);
+    fs::create_dir_all(&target_dir)?;
+    unpack_impl(data, asset.format.archive_file(), target_dir, exe_path.clone())?;
+    output::info(t!("deno v{} for {} has been installed", version, target));
+    hooks::run(Hook::PostInstall, &version.to_string(), &exe_path)?;
+  }
+  drop(lock);
+  meta.add_arch(&version.to_string(), target);
+
+  if !no_use {
+    use_version::use_this_bin_path(&exe_path, &version, version.to_string(), false)?;
+  }
+  Ok((version.to_string(), exe_path))
+}
 
-  let response = match tinyget::get(url).send() {
-    Ok(response) => response,
-    Err(error) => {
-      println!("Network error {}", &error);
-      std::process::exit(1)
-    }
+///
+/// download the archive of a version, or take it from the cache, and check it against the published sha256,
+/// a signature too and that a provenance matches it if there are any, nothing is installed. whatever fails makes the command fail,
+/// so CI can check that a mirror serves a release intact before relying on it.
+pub fn verify_only(meta: &DvmMeta, version: Option<String>, target: Option<String>) -> Result<()> {
+  let target = match target {
+    Some(target) => resolve_target(&target)?,
+    None => TARGET,
+  };
+  let version = match version.as_deref().map(normalize_version) {
+    Some(DVM_VERSION_CANARY) => bail!(t!("Canary builds publish no checksums")),
+    Some(DVM_VERSION_LTS) => lts::latest()?,
+    Some(version) => Version::parse(version).map_err(|_| anyhow!(t!("Invalid semver {}", version)))?,
+    None => get_latest_version(&meta.registry())?,
   };
+  let asset =
+    assets::deno(&version, target).ok_or_else(|| anyhow!(t!("deno v{} has no build for {}", version, target)))?;
+
+  http::block_on(async {
+    let Some(data) = archive_cache::fetch(&meta.registry(), &version, &asset.target, &asset.name).await? else {
+      bail!(t!("{} has not been found", asset.name));
+    };
+    let Some(published) = plan::published_sha256(&meta.registry(), &version, &asset.target, &asset.name).await else {
+      bail!(t!(
+        "deno v{} publishes no sha256 of {}, it can't be verified",
+        version,
+        asset.name
+      ));
+    };
+    let actual = archive_cache::sha256_hex(&data);
+    if actual != published {
+      bail!(t!(
+        "The sha256 of {} is {}, deno v{} published {}",
+        asset.name,
+        actual,
+        version,
+        published
+      ));
+    }
+    signature::check(&meta.registry(), &version, &asset.target, &asset.name, &data).await?;
+    provenance::check(&meta.registry(), &version, &asset.target, &asset.name, &data).await?;
+    output::success(t!("{} of deno v{} is intact, sha256 {}", asset.name, version, actual));
+    Ok(())
+  })
+}
 
-  if response.status_code == 404 {
-    println!("Version has not been found, aborting");
-    std::process::exit(1)
+/// print what `install` would do without downloading anything
+pub fn dry_run(meta: &DvmMeta, version: Option<String>, json: bool) -> Result<()> {
+  let install = plan::install(meta, version.as_deref())?;
+  Plan {
+    installs: vec![install],
+    ..Default::default()
   }
+  .print(json);
+  Ok(())
+}
+
+fn get_latest_version(registry: &str) -> Result<Version> {
+  output::info(t!("Checking for latest version"));
+
+  let body = http::block_on(async {
+    http_cache::get(&source::latest_release_url(registry))
+      .await?
+      .text()
+      .await
+  })?;
+  let v = body.trim().replace('v', "");
+  output::info(t!("The latest version is v{}", &v));
+  Ok(Version::parse(&v).unwrap())
+}
 
-  if response.status_code >= 400 && response.status_code <= 599 {
-    println!("Download '{}' failed: {}", &url, response.status_code);
-    std::process::exit(1)
+/// the deno asset of a version for this platform, under the name it was released with
+pub fn deno_asset(version: &Version) -> Result<Asset> {
+  assets::deno(version, TARGET).ok_or_else(|| anyhow!(t!("deno v{} has no build for {}", version, TARGET)))
+}
+
+/// download and unpack a version, `false` if the release doesn't exist
+async fn install_deno(registry: &str, version: &Version) -> Result<bool> {
+  match delta::install(version).await {
+    Ok(true) => return Ok(true),
+    Ok(false) => {}
+    Err(err) => output::warn(t!(
+      "The delta to deno v{} can't be used, downloading it in full: {}",
+      version,
+      err
+    )),
+  }
+  let asset = deno_asset(version)?;
+  let Some(data) = archive_cache::fetch(registry, version, &asset.target, &asset.name).await? else {
+    return Ok(false);
+  };
+  let verifying = timings::start(Phase::Verify);
+  signature::check(registry, version, &asset.target, &asset.name, &data).await?;
+  let provenance = provenance::check(registry, version, &asset.target, &asset.name, &data).await?;
+  drop(verifying);
+
+  output::info(t!("Version has been found"));
+  output::info(t!("Deno v{} has been downloaded", &version));
+
+  let _timer = timings::start(Phase::Extract);
+  let unpacked = version.clone();
+  tokio::task::spawn_blocking(move || unpack(data, asset.format.archive_file(), &unpacked)).await??;
+  if let Some(provenance) = provenance {
+    provenance::record(version, &provenance)?;
   }
+  Ok(true)
+}
 
-  println!("Version has been found");
-  println!("Deno v{} has been downloaded", &version);
+pub fn is_prefetched(version: &Version) -> bool {
+  deno_asset(version)
+    .map(|asset| archive_cache::is_cached(version, &asset.name))
+    .unwrap_or(false)
+}
 
-  Ok(response.into_bytes())
+/// download the archive of a version into the cache without installing it
+pub fn prefetch(registry: &str, version: &Version) -> Result<()> {
+  let asset = deno_asset(version)?;
+  if http::block_on(archive_cache::fetch(registry, version, &asset.target, &asset.name))?.is_none() {
+    bail!(t!("{} has not been found", asset.name));
+  }
+  Ok(())
 }
 
-fn compose_url_to_exec(registry: &str, version: &Version) -> String {
-  format!("{}release/v{}/{}", registry, version, ARCHIVE_NAME)
+/// download the `denort` runtime next to an installed version
+async fn install_denort(registry: &str, version: &Version) -> Result<()> {
+  let asset = format!("denort-{}.zip", TARGET);
+  let Some(data) = archive_cache::fetch(registry, version, TARGET, &asset).await? else {
+    bail!(t!("{} has not been found", asset));
+  };
+  let verifying = timings::start(Phase::Verify);
+  signature::check(registry, version, TARGET, &asset, &data).await?;
+  provenance::check(registry, version, TARGET, &asset, &data).await?;
+  drop(verifying);
+
+  let _timer = timings::start(Phase::Extract);
+  let version_dir = deno_version_dir(version);
+  let path = denort_version_path(version);
+  tokio::task::spawn_blocking(move || {
+    let _staged = interrupt::stage(&path);
+    fs::create_dir_all(&version_dir)?;
+    unpack_impl(data, "denort.zip", version_dir, path)
+  })
+  .await??;
+  output::info(t!("denort v{} has been downloaded", version));
+  Ok(())
 }
 
-fn unpack(archive_data: Vec<u8>, version: &Version) -> Result<PathBuf> {
-  let version_dir = dvm_root().join(format!("{}/{}", DVM_CACHE_PATH_PREFIX, version));
-  fs::create_dir_all(&version_dir)?;
+fn unpack(archive_data: Vec<u8>, archive_file: &str, version: &Version) -> Result<PathBuf> {
+  let version_dir = deno_version_dir(version);
   let exe_path = deno_version_path(version);
+  // a version interrupted while unpacking isn't left half installed, denort unpacked before stays
+  let _staged = interrupt::stage(if version_dir.exists() { &exe_path } else { &version_dir });
+  fs::create_dir_all(&version_dir)?;
 
-  unpack_impl(archive_data, version_dir, exe_path)
+  let version_dir = unpack_impl(archive_data, archive_file, version_dir, exe_path)?;
+  #[cfg(unix)]
+  if crate::utils::dvm_system_root().is_some() {
+    // versions in the shared store have to be usable by all users
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&version_dir, fs::Permissions::from_mode(0o755))?;
+  }
+  Ok(version_dir)
 }
 
 fn unpack_canary(archive_data: Vec<u8>) -> Result<PathBuf> {
@@ -130,62 +486,46 @@ fn unpack_canary(archive_data: Vec<u8>) -> Result<PathBuf> {
   fs::create_dir_all(&canary_dir)?;
   let exe_path = deno_canary_path();
 
-  if exe_path.exists() {
-    fs::remove_file(exe_path.clone())?;
-  }
-
-  unpack_impl(archive_data, canary_dir, exe_path)
-}
-
-fn unpack_impl(archive_data: Vec<u8>, version_dir: PathBuf, path: PathBuf) -> Result<PathBuf> {
-  let archive_ext = Path::new(ARCHIVE_NAME)
-    .extension()
-    .and_then(|ext| ext.to_str())
-    .unwrap();
-  let unpack_status = match archive_ext {
-    "zip" if cfg!(windows) => {
-      let archive_path = version_dir.join("deno.zip");
-      fs::write(&archive_path, &archive_data)?;
-      Command::new("powershell.exe")
-        .arg("-NoLogo")
-        .arg("-NoProfile")
-        .arg("-NonInteractive")
-        .arg("-Command")
-        .arg(
-          "& {
-            param($Path, $DestinationPath)
-            trap { $host.ui.WriteErrorLine($_.Exception); exit 1 }
-            Add-Type -AssemblyName System.IO.Compression.FileSystem
-            [System.IO.Compression.ZipFile]::ExtractToDirectory(
-              $Path,
-              $DestinationPath
-            );
-          }",
-        )
-        .arg("-Path")
-        .arg(format!("'{}'", &archive_path.to_str().unwrap()))
-        .arg("-DestinationPath")
-        .arg(format!("'{}'", &version_dir.to_str().unwrap()))
-        .spawn()?
-        .wait()?
-    }
-    "zip" => {
-      let archive_path = version_dir.join("deno.zip");
-      fs::write(&archive_path, &archive_data)?;
-      Command::new("unzip")
-        .current_dir(&version_dir)
-        .arg(archive_path)
-        .spawn()?
-        .wait()?
-    }
-    ext => panic!("Unsupported archive type: '{}'", ext),
-  };
-  assert!(unpack_status.success());
-  assert!(path.exists());
+  // the canary it replaces is kept until the new one runs
+  use_version::replace_verified(&exe_path, None, || {
+    let _staged = interrupt::stage(&exe_path);
+    unpack_impl(archive_data, "deno.zip", canary_dir.clone(), exe_path.clone()).map(|_| ())
+  })?;
+  Ok(canary_dir)
+}
+
+///
+/// unpack an archive in `version_dir` with the handler of its format, `path` is the executable it holds,
+/// the formats are registered in `archive`.
+pub fn unpack_impl(archive_data: Vec<u8>, archive_file: &str, version_dir: PathBuf, path: PathBuf) -> Result<PathBuf> {
+  let handler = archive::handler(archive_file)?;
+  let archive_path = version_dir.join(archive_file);
+  output::event(Event::Extract { archive: &archive_path });
+  fs::write(&archive_path, &archive_data)?;
+  handler.unpack(&archive_path, &version_dir, &path)?;
+  if !path.exists() {
+    return Err(
+      UnpackError::MissingExecutable {
+        archive: archive_path,
+        path,
+      }
+      .into(),
+    );
+  }
+  #[cfg(unix)]
+  {
+    // gzip and zstd keep or set the mode of the archive, which isn't executable
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+  }
+  output::event(Event::Verify { path: &path });
   Ok(version_dir)
 }
 
-fn download_canary(registry: &str, hash: &str) -> Result<Vec<u8>> {
+async fn download_canary(registry: &str, hash: &str) -> Result<Vec<u8>> {
+  if signature::is_required() {
+    bail!(t!("Canary builds aren't signed"));
+  }
   // TODO: remove this when deno canary support m1 chip,
   let archive_name = if ARCHIVE_NAME == "deno-aarch64-apple-darwin.zip" {
     "deno-x86_64-apple-darwin.zip"
@@ -193,10 +533,10 @@ fn download_canary(registry: &str, hash: &str) -> Result<Vec<u8>> {
     ARCHIVE_NAME
   };
 
-  let url = format!("{}canary/{}/{}", registry, hash, archive_name);
-
-  let resp = tinyget::get(url).send()?;
-  Ok(resp.into_bytes())
+  let Some(data) = source::fetch(&[source::canary_url(registry, hash, archive_name)]).await? else {
+    bail!(t!("{} has not been found", archive_name));
+  };
+  Ok(data)
 }
 
 #[test]
@@ -205,7 +545,7 @@ fn test_compose_url_to_exec() {
   use asserts_rs::asserts_eq_one_of;
 
   let v = Version::parse("1.7.0").unwrap();
-  let url = compose_url_to_exec(REGISTRY_OFFICIAL, &v);
+  let url = source::release_urls(REGISTRY_OFFICIAL, &v, TARGET, ARCHIVE_NAME).remove(0);
 
   cfg_if! {
     if #[cfg(windows)] {
@@ -231,3 +571,48 @@ fn test_compose_url_to_exec() {
     }
   }
 }
+
+#[test]
+fn test_compose_url_to_denort() {
+  use crate::consts::REGISTRY_OFFICIAL;
+
+  let v = Version::parse("2.0.0").unwrap();
+  assert_eq!(
+    source::release_urls(REGISTRY_OFFICIAL, &v, TARGET, &format!("denort-{}.zip", TARGET))[0],
+    format!("https://dl.deno.land/release/v2.0.0/denort-{}.zip", TARGET)
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_unpack_errors() {
+    let dir = std::env::temp_dir().join(format!("dvm-unpack-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let err = unpack_impl(vec![], "deno.tar.xz", dir.clone(), dir.join("deno")).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported archive type: 'xz'");
+
+    let err = unpack_impl(b"not a zip".to_vec(), "deno.zip", dir.clone(), dir.join("deno")).unwrap_err();
+    assert!(matches!(
+      err.downcast_ref::<UnpackError>(),
+      Some(UnpackError::Extract { .. })
+    ));
+    assert!(err.to_string().contains(&dir.join("deno.zip").display().to_string()));
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
+
+#[test]
+fn test_repo_url() {
+  assert_eq!(repo_url("someorg/deno").unwrap(), "https://github.com/someorg/deno");
+  assert_eq!(
+    repo_url("https://git.corp/platform/deno/").unwrap(),
+    "https://git.corp/platform/deno"
+  );
+  assert!(repo_url("someorg").is_err());
+  assert!(repo_url("someorg/deno/releases").is_err());
+}
@@ -1,36 +1,252 @@
-use crate::version::{current_version, local_versions, remote_versions};
+use crate::commands::compact;
+use crate::commands::current;
+use crate::commands::rehash::active_version;
+use crate::consts::DVM_VERSION_SYSTEM;
+use crate::lts;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{external_deno, format_timestamp, normalize_version};
+use crate::version::{current_version, local_versions, releases, remote_versions, version_of, Release};
 use anyhow::Result;
-use semver::Version;
+use colored::Colorize;
+use semver::{Version, VersionReq};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
-pub fn exec() -> Result<()> {
+/// what the installed versions are to the user right now
+#[derive(Default)]
+pub struct Roles {
+  /// the one a deno launched here would be
+  pub active: Option<String>,
+  /// the one of the home `.dvmrc`
+  pub default: Option<String>,
+  /// the linked projects pinning each version
+  pub pinned: HashMap<String, Vec<String>>,
+}
+
+impl Roles {
+  pub fn of(meta: &DvmMeta) -> Self {
+    let mut pinned: HashMap<String, Vec<String>> = HashMap::new();
+    for project in meta.projects.iter().filter(|it| it.exists()) {
+      pinned
+        .entry(project.version.clone())
+        .or_default()
+        .push(project.dir.clone());
+    }
+    Roles {
+      active: current::resolve(meta).map(|(version, _)| version),
+      default: active_version()
+        .and_then(|it| meta.resolve_local_version(&it))
+        .map(|it| it.to_string()),
+      pinned,
+    }
+  }
+
+  /// the markers of a version, e.g. `active  default  pinned by ~/app`
+  fn tags(&self, version: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    if self.active.as_deref() == Some(version) {
+      tags.push(t!("active").bright_green().to_string());
+    }
+    if self.default.as_deref() == Some(version) {
+      tags.push(t!("default").cyan().to_string());
+    }
+    match self.pinned.get(version).map(Vec::as_slice) {
+      None | Some([]) => {}
+      Some([dir]) => tags.push(t!("pinned by {}", dir).yellow().to_string()),
+      Some(dirs) => tags.push(t!("pinned by {} projects", dirs.len()).yellow().to_string()),
+    }
+    tags
+  }
+}
+
+/// the versions by `major.minor`, the newest group and the newest version of a group first
+fn group(versions: &[String]) -> Vec<(String, Vec<Version>)> {
+  let mut versions = versions
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .collect::<Vec<_>>();
+  versions.sort_by(|a, b| b.cmp(a));
+  let mut groups: Vec<(String, Vec<Version>)> = Vec::new();
+  for version in versions {
+    let key = format!("{}.{}", version.major, version.minor);
+    match groups.last_mut() {
+      Some((last, members)) if *last == key => members.push(version),
+      _ => groups.push((key, vec![version])),
+    }
+  }
+  groups
+}
+
+///
+/// the installed versions newest first, grouped by `major.minor` with the newest of each in bold,
+/// the active, default and project pinned ones are marked, with when they were used last.
+/// a deno installed by other means is listed as `~system` with its path, dvm never touches it.
+pub fn exec(meta: &mut DvmMeta) -> Result<()> {
   let versions = local_versions();
+  // what the linked projects pin now, not when they were linked
+  meta.resolve_project_pins();
+  let roles = Roles::of(meta);
+  let details = versions
+    .iter()
+    .filter_map(|v| {
+      let channel = meta
+        .channels
+        .get(v)
+        .filter(|it| *it == "lts")
+        .map(|it| it.green().to_string());
+      let used = meta
+        .last_used_time(v)
+        .map(|time| t!("last used {}", format_timestamp(time)).bright_black().to_string());
+      let origin = meta.origins.get(v).map(|it| t!("from {}", it).yellow().to_string());
+      // a version installed with `install --arch` alone has no native build
+      let arches = meta.arches.get(v).map(|it| {
+        let triples = it
+          .iter()
+          .map(|triple| format!("+{}", triple))
+          .collect::<Vec<_>>()
+          .join(" ");
+        let native = Version::parse(v).map_or(true, |it| compact::is_installed(&it));
+        if native { triples } else { t!("{} only", triples) }.cyan().to_string()
+      });
+      let details = roles
+        .tags(v)
+        .into_iter()
+        .chain(channel)
+        .chain(origin)
+        .chain(arches)
+        .chain(used)
+        .collect::<Vec<_>>();
+      (!details.is_empty()).then(|| (v.clone(), details.join("  ")))
+    })
+    .collect::<HashMap<_, _>>();
 
-  print_versions(versions);
+  let width = versions.iter().map(|it| it.len()).max().unwrap_or_default();
+  for (minor, members) in group(&versions) {
+    println!("{}", format!("{}.x", minor).bright_black());
+    for (i, version) in members.iter().enumerate() {
+      let name = version.to_string();
+      let details = details.get(&name).map(|it| format!("  {}", it)).unwrap_or_default();
+      let padded = format!("{:<width$}", name, width = width);
+      if roles.active.as_deref() == Some(name.as_str()) {
+        println!("{}{}", output::highlight(&format!("* {}", padded)), details);
+      } else if i == 0 {
+        println!("  {}{}", padded.bold(), details);
+      } else {
+        println!("  {}{}", padded, details);
+      }
+    }
+  }
+  if let Some(path) = external_deno() {
+    println!(
+      "{}{}  {}  {}",
+      "~".bright_black(),
+      DVM_VERSION_SYSTEM,
+      version_of(&path).unwrap_or_else(|| "-".to_string()),
+      t!("{}, not managed by dvm", path.display()).bright_black()
+    );
+  }
   Ok(())
 }
 
-pub fn exec_remote() -> Result<()> {
-  let versions = remote_versions().unwrap();
+pub fn exec_remote(lts: bool) -> Result<()> {
+  let mut versions = remote_versions()?;
+  if lts {
+    versions.retain(|it| Version::parse(it).map(|it| lts::channel(&it) == "lts").unwrap_or(false));
+  }
+  // the dates are a nicety, the versions are listed without them
+  let releases = releases().unwrap_or_else(|err| {
+    output::warn(t!("Failed to fetch the release dates: {}", err));
+    HashMap::new()
+  });
+  let details = releases
+    .iter()
+    .map(|(v, release)| (v.clone(), release_details(release)))
+    .collect();
 
-  print_versions(versions);
+  print_versions(versions, &details);
   Ok(())
 }
 
-fn print_versions(mut versions: Vec<String>) {
-  let current_version = match current_version() {
-    Some(v) => v,
-    _ => String::from(""),
+fn release_details(release: &Release) -> String {
+  let lts = Version::parse(&release.version)
+    .map(|it| lts::channel(&it) == "lts")
+    .unwrap_or(false);
+  let channel = if release.channel == "rc" {
+    release.channel.yellow()
+  } else if lts {
+    "lts".green()
+  } else {
+    release.channel.normal()
+  };
+  format!("{}  {}", release.date.bright_black(), channel)
+}
+
+/// whether a version is a hit of `dvm search`, patterns that start like a range are matched as one
+fn is_match(pattern: &str, version: &str) -> bool {
+  if !pattern.starts_with(['<', '>', '=', '~', '^', '*']) && !pattern.contains(',') {
+    return version.contains(pattern);
+  }
+  match (VersionReq::parse(pattern), Version::parse(version)) {
+    (Ok(required), Ok(version)) => required.matches(&version),
+    _ => false,
+  }
+}
+
+///
+/// the released versions matching a substring like `1.39` or `rc`, or a range like `>=1.40, <1.42`,
+/// looked up in the cached release list, the installed ones are marked.
+pub fn exec_search(pattern: String) -> Result<()> {
+  let pattern = normalize_version(&pattern);
+  let releases = releases().unwrap_or_else(|err| {
+    output::warn(t!("Failed to fetch the release dates: {}", err));
+    HashMap::new()
+  });
+  let versions = if releases.is_empty() {
+    remote_versions()?
+  } else {
+    releases.keys().cloned().collect()
   };
+  let versions = versions
+    .into_iter()
+    .filter(|it| is_match(pattern, it))
+    .collect::<Vec<_>>();
+  if versions.is_empty() {
+    println!("{}", t!("No released version matches {}", pattern));
+    return Ok(());
+  }
+
+  let installed = local_versions();
+  let details = versions
+    .iter()
+    .map(|v| {
+      let mut details = releases.get(v).map(release_details).unwrap_or_default();
+      if installed.contains(v) {
+        details = format!("{}  {}", details, t!("installed").bright_green());
+      }
+      (v.clone(), details.trim_start().to_string())
+    })
+    .collect();
+  print_versions(versions, &details);
+  Ok(())
+}
+
+/// one version a line, `details` are aligned after them
+fn print_versions(mut versions: Vec<String>, details: &HashMap<String, String>) {
+  let current_version = current_version().unwrap_or_default();
 
   versions.sort_by(|a, b| sort_semver_version(b, a).reverse());
 
+  let width = versions.iter().map(|it| it.len()).max().unwrap_or_default();
   for v in &versions {
+    let details = match details.get(v) {
+      Some(details) => format!("{:<width$}  {}", "", details, width = width - v.len()),
+      None => String::new(),
+    };
     if *v == current_version {
-      // display current used version with bright green
-      println!("\x1b[0;92m*{}\x1b[0m", v);
+      println!("{}{}", output::highlight(&format!("*{}", v)), details);
     } else {
-      println!(" {}", v)
+      println!(" {}{}", v, details)
     }
   }
 }
@@ -46,6 +262,33 @@ fn sort_semver_version(s1: &str, s2: &str) -> Ordering {
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_group() {
+    let versions = ["1.28.0", "1.41.1", "1.41.3", "1.28.2", "2.0.0-rc.1"].map(String::from);
+    let groups = group(&versions)
+      .into_iter()
+      .map(|(minor, members)| (minor, members.iter().map(Version::to_string).collect::<Vec<_>>()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      groups,
+      vec![
+        ("2.0".to_string(), vec!["2.0.0-rc.1".to_string()]),
+        ("1.41".to_string(), vec!["1.41.3".to_string(), "1.41.1".to_string()]),
+        ("1.28".to_string(), vec!["1.28.2".to_string(), "1.28.0".to_string()]),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_is_match() {
+    assert!(is_match("1.39", "1.39.4"));
+    assert!(is_match("rc", "1.40.0-rc.1"));
+    assert!(!is_match("rc", "1.40.0"));
+    assert!(is_match(">=1.40, <1.42", "1.41.3"));
+    assert!(!is_match(">=1.40, <1.42", "1.42.0"));
+    assert!(!is_match(">=nonsense", "1.42.0"));
+  }
+
   #[test]
   fn sort_version() {
     let v1 = String::from("0.2.10-beta");
@@ -0,0 +1,133 @@
+use crate::archive_cache::sha256_hex;
+use crate::cli::LockCommands;
+use crate::commands::compact;
+use crate::commands::install::TARGET;
+use crate::consts::DVM_LOCK_FILENAME;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{active_bin_path, best_version, deno_version_path, load_dvmrc};
+use crate::version::{local_versions, VersionArg};
+use anyhow::{anyhow, bail, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+///
+/// `dvm.lock` pins the exact deno a project runs with,
+/// the hash is the one of the unpacked executable, not of the archive.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+  pub version: String,
+  pub target: String,
+  pub sha256: String,
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+  fs::read(path).ok().map(|it| sha256_hex(&it))
+}
+
+/// the failed checks of a lock, given the hashes of the installed and the active executable
+fn check(lock: &Lockfile, installed: Option<&str>, active: Option<&str>) -> Vec<String> {
+  let mut failures = Vec::new();
+  if lock.target != TARGET {
+    failures.push(t!("the lock is for {}, this machine is {}", lock.target, TARGET));
+  }
+  match installed {
+    None => failures.push(t!("deno v{} is not installed", lock.version)),
+    Some(hash) if hash != lock.sha256 => failures.push(t!(
+      "the installed deno v{} has the sha256 {} instead of {}",
+      lock.version,
+      hash,
+      lock.sha256
+    )),
+    _ => {}
+  }
+  if active != Some(lock.sha256.as_str()) {
+    failures.push(t!("deno v{} is not the active version", lock.version));
+  }
+  failures
+}
+
+/// the version given, or the one of the `.dvmrc`, resolved to an installed version
+fn resolve(meta: &DvmMeta, version: Option<String>) -> Result<Version> {
+  let version_req = match version {
+    Some(version) => meta.resolve_version_req(&version),
+    None => load_dvmrc(),
+  };
+  match version_req {
+    VersionArg::Exact(v) => Ok(v),
+    VersionArg::Range(r) => {
+      let versions = local_versions();
+      best_version(versions.iter().map(AsRef::as_ref), r.clone())
+        .ok_or_else(|| anyhow!(t!("no installed version matches `{}`", r)))
+    }
+  }
+}
+
+fn write(meta: &DvmMeta, version: Option<String>) -> Result<()> {
+  let version = resolve(meta, version)?;
+  compact::restore(&version)?;
+  let Some(sha256) = hash_file(&deno_version_path(&version)) else {
+    bail!(t!("deno `{}` is not installed", version));
+  };
+  let lock = Lockfile {
+    version: version.to_string(),
+    target: TARGET.to_string(),
+    sha256,
+  };
+  fs::write(DVM_LOCK_FILENAME, toml::to_string(&lock)?)?;
+  output::success(t!("Locked deno v{} in {}", version, DVM_LOCK_FILENAME));
+  Ok(())
+}
+
+fn verify(file: Option<PathBuf>) -> Result<()> {
+  let file = file.unwrap_or_else(|| PathBuf::from(DVM_LOCK_FILENAME));
+  let lock: Lockfile = toml::from_str(&fs::read_to_string(&file)?)
+    .map_err(|err| anyhow!("invalid lock file {}: {}", file.display(), err))?;
+  let version = Version::parse(&lock.version)?;
+
+  compact::restore(&version)?;
+  let installed = hash_file(&deno_version_path(&version));
+  let active = hash_file(&active_bin_path());
+  let failures = check(&lock, installed.as_deref(), active.as_deref());
+  if !failures.is_empty() {
+    for failure in &failures {
+      output::error(failure);
+    }
+    bail!(t!("{} doesn't match the installed deno", file.display()));
+  }
+  output::success(t!("deno v{} matches {}", lock.version, file.display()));
+  Ok(())
+}
+
+pub fn exec(meta: &DvmMeta, command: LockCommands) -> Result<()> {
+  match command {
+    LockCommands::Write { version } => write(meta, version),
+    LockCommands::Verify { file } => verify(file),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check() {
+    let lock = Lockfile {
+      version: "1.28.0".to_string(),
+      target: TARGET.to_string(),
+      sha256: "abc".to_string(),
+    };
+    assert!(check(&lock, Some("abc"), Some("abc")).is_empty());
+    assert_eq!(check(&lock, Some("abc"), Some("def")).len(), 1);
+    assert_eq!(check(&lock, None, None).len(), 2);
+    let other = Lockfile {
+      target: "riscv64gc-unknown-linux-gnu".to_string(),
+      ..lock.clone()
+    };
+    assert_eq!(check(&other, Some("def"), Some("abc")).len(), 2);
+    assert!(toml::from_str::<Lockfile>("version = \"1.28.0\"").is_err());
+  }
+}
@@ -0,0 +1,208 @@
+use crate::commands::hook::nearest_config;
+use crate::commands::rehash::active_version;
+use crate::consts::{DVM_CONFIG_FILENAME, DVM_VERSION_CANARY};
+use crate::meta::DvmMeta;
+use crate::project::ProjectConfig;
+use crate::utils::{deno_canary_path, deno_version_path};
+use crate::version::local_versions;
+use anyhow::Result;
+use semver::Version;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// the version asked for isn't installed
+const NOT_INSTALLED: i64 = -32000;
+
+type RpcError = (i64, String);
+
+///
+/// the next message on `input`, `None` at the end of it, and whether it was framed by LSP style
+/// `Content-Length` headers, otherwise a message is a line of json.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<(String, bool)>> {
+  let mut line = String::new();
+  while line.trim().is_empty() {
+    line.clear();
+    if input.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+  }
+  let Some(length) = line
+    .trim()
+    .to_ascii_lowercase()
+    .strip_prefix("content-length:")
+    .map(|it| it.trim().parse::<usize>())
+  else {
+    return Ok(Some((line, false)));
+  };
+  let length = length.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+  // the other headers, up to the empty line
+  loop {
+    line.clear();
+    if input.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    if line.trim().is_empty() {
+      break;
+    }
+  }
+  let mut body = vec![0; length];
+  input.read_exact(&mut body)?;
+  Ok(Some((String::from_utf8_lossy(&body).into_owned(), true)))
+}
+
+fn write_message(output: &mut impl Write, message: &Value, framed: bool) -> io::Result<()> {
+  let body = message.to_string();
+  if framed {
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+  } else {
+    writeln!(output, "{}", body)?;
+  }
+  output.flush()
+}
+
+/// the executable of an installed version, canary included
+fn exe_path(meta: &DvmMeta, required: &str) -> Option<(String, PathBuf)> {
+  let (version, path) = if required == DVM_VERSION_CANARY {
+    (required.to_string(), deno_canary_path())
+  } else {
+    let version = meta.resolve_local_version(required)?;
+    (version.to_string(), deno_version_path(&version))
+  };
+  path.exists().then_some((version, path))
+}
+
+///
+/// the deno a file or directory gets: the one of the nearest `.dvmrc` up from it, the default one outside of projects,
+/// `version` and `path` are null if it's not installed.
+fn resolve_version_for_path(meta: &DvmMeta, path: &Path) -> Value {
+  let dir = if path.is_dir() {
+    path
+  } else {
+    path.parent().unwrap_or(path)
+  };
+  let project = nearest_config(dir).and_then(|config| {
+    let content = fs::read_to_string(&config).ok()?;
+    Some((ProjectConfig::from_str(&content).ok()?.version?, config))
+  });
+  let (required, source) = match project {
+    Some((required, config)) => (Some(required), Some(config)),
+    None => (
+      active_version(),
+      dirs::home_dir().map(|it| it.join(DVM_CONFIG_FILENAME)),
+    ),
+  };
+  let installed = required.as_deref().and_then(|it| exe_path(meta, it));
+  json!({
+    "required": required,
+    "source": source,
+    "version": installed.as_ref().map(|(version, _)| version),
+    "path": installed.as_ref().map(|(_, path)| path),
+  })
+}
+
+fn list_installed() -> Value {
+  let mut versions = local_versions()
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .collect::<Vec<_>>();
+  versions.sort();
+  let mut installed = versions
+    .iter()
+    .map(|it| json!({ "version": it.to_string(), "path": deno_version_path(it) }))
+    .collect::<Vec<_>>();
+  if deno_canary_path().exists() {
+    installed.push(json!({ "version": DVM_VERSION_CANARY, "path": deno_canary_path() }));
+  }
+  Value::Array(installed)
+}
+
+fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+  params[name]
+    .as_str()
+    .map(str::to_string)
+    .ok_or_else(|| (INVALID_PARAMS, format!("`{}` should be a string", name)))
+}
+
+/// the meta is read for every request, so what other dvm commands change meanwhile shows up
+fn dispatch(method: &str, params: &Value) -> Result<Value, RpcError> {
+  match method {
+    "resolveVersionForPath" => Ok(resolve_version_for_path(
+      &DvmMeta::new(),
+      Path::new(&string_param(params, "path")?),
+    )),
+    "listInstalled" => Ok(list_installed()),
+    "pathForVersion" => {
+      let version = string_param(params, "version")?;
+      match exe_path(&DvmMeta::new(), &version) {
+        Some((version, path)) => Ok(json!({ "version": version, "path": path })),
+        None => Err((NOT_INSTALLED, format!("no installed version matches `{}`", version))),
+      }
+    }
+    method => Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+  }
+}
+
+/// the response to a message, notifications have none
+fn respond(message: &str) -> Option<Value> {
+  let request: Value = match serde_json::from_str(message) {
+    Ok(request) => request,
+    Err(err) => {
+      return Some(
+        json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": err.to_string() } }),
+      )
+    }
+  };
+  let id = request.get("id")?.clone();
+  let method = request["method"].as_str().unwrap_or_default();
+  Some(match dispatch(method, &request["params"]) {
+    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+  })
+}
+
+///
+/// answer JSON-RPC requests on stdin until it's closed, so editor plugins can keep asking dvm
+/// without starting a process for every question: `resolveVersionForPath {path}`, `listInstalled`
+/// and `pathForVersion {version}`.
+pub fn exec() -> Result<()> {
+  let stdin = io::stdin();
+  let mut input = stdin.lock();
+  let mut output = io::stdout().lock();
+  while let Some((message, framed)) = read_message(&mut input)? {
+    if let Some(response) = respond(&message) {
+      write_message(&mut output, &response, framed)?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_read_message() {
+    let mut input = io::Cursor::new("Content-Length: 2\r\nContent-Type: json\r\n\r\n{}\n{\"id\":1}\n");
+    assert_eq!(read_message(&mut input).unwrap(), Some(("{}".to_string(), true)));
+    assert_eq!(
+      read_message(&mut input).unwrap(),
+      Some(("{\"id\":1}\n".to_string(), false))
+    );
+    assert_eq!(read_message(&mut input).unwrap(), None);
+  }
+
+  #[test]
+  fn test_respond() {
+    let response = respond(r#"{"jsonrpc":"2.0","id":7,"method":"unknown"}"#).unwrap();
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    assert_eq!(respond(r#"{"jsonrpc":"2.0","method":"initialized"}"#), None);
+    assert_eq!(respond("not json").unwrap()["error"]["code"], PARSE_ERROR);
+  }
+}
@@ -0,0 +1,166 @@
+use crate::consts::{
+  DVM_ARCHIVES_PATH_PREFIX, DVM_AUTO_CLEAN_CHECK_FILENAME, DVM_BUILD_PATH_PREFIX, DVM_CACHE_PATH_PREFIX,
+  DVM_CANARY_PATH_PREFIX, DVM_CONFIG_TOML_FILENAME, DVM_DENO_DIR_PATH_PREFIX, DVM_HOOKS_PATH_PREFIX,
+  DVM_HTTP_CACHE_PATH_PREFIX, DVM_LAST_COMMAND_LOG_FILENAME, DVM_POLICY_FILENAME_PREFIX, DVM_RELEASES_FILENAME,
+  DVM_RESOLVE_CACHE_FILENAME, DVM_SELF_UPDATE_PATH_PREFIX, DVM_STUBS_PATH_PREFIX, DVM_TELEMETRY_NOTICE_FILENAME,
+  DVM_UPDATE_CHECK_FILENAME,
+};
+use crate::output;
+use crate::utils::{classic_root, xdg_cache_dir, xdg_config_dir, xdg_data_dir};
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// which of the XDG base directories an entry of the classic root belongs in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Root {
+  /// read from `dvm_cache_root`, safe to remove
+  Cache,
+  /// read from `dvm_config_root`
+  Config,
+  /// read from `dvm_root`
+  Data,
+}
+
+///
+/// where each entry of the classic root goes, by the root it's read from after the migration,
+/// a name ending with `-` is a prefix. anything not listed is data, so an unknown entry is never lost
+/// in the cache. an entry added under another root has to be added here as well.
+const ENTRIES: &[(&str, Root)] = &[
+  (DVM_DENO_DIR_PATH_PREFIX, Root::Cache),
+  (DVM_ARCHIVES_PATH_PREFIX, Root::Cache),
+  (DVM_HTTP_CACHE_PATH_PREFIX, Root::Cache),
+  (DVM_SELF_UPDATE_PATH_PREFIX, Root::Cache),
+  (DVM_BUILD_PATH_PREFIX, Root::Cache),
+  (DVM_RELEASES_FILENAME, Root::Cache),
+  (DVM_RESOLVE_CACHE_FILENAME, Root::Cache),
+  (DVM_LAST_COMMAND_LOG_FILENAME, Root::Cache),
+  (DVM_UPDATE_CHECK_FILENAME, Root::Cache),
+  (DVM_AUTO_CLEAN_CHECK_FILENAME, Root::Cache),
+  (DVM_POLICY_FILENAME_PREFIX, Root::Cache),
+  (DVM_CONFIG_TOML_FILENAME, Root::Config),
+  (DVM_HOOKS_PATH_PREFIX, Root::Config),
+  (DVM_TELEMETRY_NOTICE_FILENAME, Root::Config),
+  (DVM_CACHE_PATH_PREFIX, Root::Data),
+  (DVM_CANARY_PATH_PREFIX, Root::Data),
+  (DVM_STUBS_PATH_PREFIX, Root::Data),
+];
+
+fn root_of(name: &str) -> Root {
+  ENTRIES
+    .iter()
+    .find(|(entry, _)| name == *entry || (entry.ends_with('-') && name.starts_with(entry)))
+    .map(|(_, root)| *root)
+    .unwrap_or(Root::Data)
+}
+
+/// the XDG base directories entries are moved to
+struct Targets {
+  data: PathBuf,
+  cache: PathBuf,
+  config: PathBuf,
+}
+
+impl Targets {
+  fn dir(&self, root: Root) -> &Path {
+    match root {
+      Root::Cache => &self.cache,
+      Root::Config => &self.config,
+      Root::Data => &self.data,
+    }
+  }
+}
+
+/// move every entry of `source` to where it belongs, `source` is removed once it's empty
+fn migrate(source: &Path, targets: &Targets) -> Result<()> {
+  for entry in fs::read_dir(source)? {
+    let entry = entry?;
+    let name = entry.file_name();
+    let root = root_of(&name.to_string_lossy());
+    move_entry(&entry.path(), &targets.dir(root).join(&name))?;
+  }
+  fs::remove_dir(source)?;
+  Ok(())
+}
+
+/// move `~/.dvm` (or `$DVM_DIR`) over to the XDG base directories
+pub fn exec() -> Result<()> {
+  let source = classic_root();
+  if !source.is_dir() {
+    bail!("{} does not exist, nothing to migrate", source.display());
+  }
+
+  let targets = Targets {
+    data: xdg_data_dir(),
+    cache: xdg_cache_dir(),
+    config: xdg_config_dir(),
+  };
+  migrate(&source, &targets)?;
+
+  output::success("Migrated to the XDG layout:");
+  println!("  data   {}", targets.data.display());
+  println!("  cache  {}", targets.cache.display());
+  println!("  config {}", targets.config.display());
+  if std::env::var_os("DVM_DIR").is_some() {
+    output::warn("Please unset `DVM_DIR`, it always selects the classic layout.");
+  }
+  output::warn(format!(
+    "Please put {} in your PATH instead of {}, `dvm doctor` does that for you.",
+    targets.data.join("bin").display(),
+    source.join("bin").display()
+  ));
+  Ok(())
+}
+
+fn move_entry(from: &Path, to: &Path) -> Result<()> {
+  if to.exists() {
+    bail!("{} already exists, will not overwrite it", to.display());
+  }
+  fs::create_dir_all(to.parent().unwrap())?;
+  println!(
+    "Moving {} to {}",
+    from.display().to_string().bright_black(),
+    to.display()
+  );
+  fs::rename(from, to)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_migrate() {
+    let dir = std::env::temp_dir().join(format!("dvm-migrate-{}", std::process::id()));
+    let source = dir.join(".dvm");
+    let targets = Targets {
+      data: dir.join("data"),
+      cache: dir.join("cache"),
+      config: dir.join("config"),
+    };
+    let name = |entry: &str| match entry.strip_suffix('-') {
+      Some(prefix) => format!("{}-0123456789abcdef.json", prefix),
+      None => entry.to_string(),
+    };
+    fs::create_dir_all(&source).unwrap();
+    for (entry, _) in ENTRIES {
+      fs::write(source.join(name(entry)), entry).unwrap();
+    }
+    fs::write(source.join("dvm-metadata.json"), "{}").unwrap();
+
+    migrate(&source, &targets).unwrap();
+    assert!(!source.exists());
+    for (entry, root) in ENTRIES {
+      assert_eq!(
+        fs::read_to_string(targets.dir(*root).join(name(entry))).ok().as_deref(),
+        Some(*entry)
+      );
+    }
+    assert!(targets.data.join("dvm-metadata.json").exists());
+    assert_eq!(root_of("hooks"), Root::Config);
+    assert_eq!(root_of("archives"), Root::Cache);
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
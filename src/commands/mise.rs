@@ -0,0 +1,131 @@
+use crate::cli::MiseCommands;
+use crate::commands::dockerfile::sh_quote;
+use crate::commands::install;
+use crate::consts::DENO_EXE;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{deno_version_dir, dvm_root, normalize_version};
+use crate::version::remote_versions;
+use anyhow::{bail, Result};
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the scripts of the asdf style plugin mise loads, each one hands over to dvm
+const SCRIPTS: &[(&str, &str)] = &[
+  ("list-all", "mise list-versions"),
+  (
+    "install",
+    "mise install \"$ASDF_INSTALL_VERSION\" \"$ASDF_INSTALL_PATH\"",
+  ),
+];
+
+fn script(dvm: &str, command: &str) -> String {
+  format!(
+    "#!/bin/sh\n# generated by `dvm mise plugin`\nexec {} {}\n",
+    sh_quote(dvm),
+    command
+  )
+}
+
+///
+/// write the plugin into `dir`, `mise-plugin` of the dvm root by default,
+/// the scripts run the dvm writing them, so they keep working when dvm isn't in PATH.
+fn plugin(dir: Option<PathBuf>) -> Result<()> {
+  let dir = dir.unwrap_or_else(|| dvm_root().join("mise-plugin"));
+  let dvm = std::env::current_exe()?.display().to_string();
+  let bin = dir.join("bin");
+  fs::create_dir_all(&bin)?;
+  for (name, command) in SCRIPTS {
+    let path = bin.join(name);
+    fs::write(&path, script(&dvm, command))?;
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+  }
+  output::success(t!("The mise plugin has been written to {}", dir.display()));
+  println!(
+    "{}",
+    t!(
+      "Run `mise plugins link --force deno {}`, then `mise use deno@<version>` installs through dvm",
+      dir.display()
+    )
+  );
+  Ok(())
+}
+
+/// every release, oldest first and on one line, the way `list-all` prints them
+fn list_versions() -> Result<()> {
+  let mut versions = remote_versions()?
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .collect::<Vec<_>>();
+  versions.sort();
+  let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+  println!("{}", versions.join(" "));
+  Ok(())
+}
+
+/// `<install path>/bin/deno` points into the dvm store, mise and dvm share one copy
+fn link(exe_path: &Path, install_path: &Path) -> Result<()> {
+  let bin = install_path.join("bin");
+  fs::create_dir_all(&bin)?;
+  let link = bin.join(DENO_EXE);
+  if link.symlink_metadata().is_ok() {
+    fs::remove_file(&link)?;
+  }
+  #[cfg(unix)]
+  std::os::unix::fs::symlink(exe_path, &link)?;
+  #[cfg(not(unix))]
+  fs::copy(exe_path, &link)?;
+  Ok(())
+}
+
+///
+/// what mise calls to install a version into its install path,
+/// the install path is linked like a project, so `dvm clean` keeps the version while mise has it.
+fn install_into(meta: &mut DvmMeta, version: String, install_path: PathBuf) -> Result<()> {
+  let version = normalize_version(&version).to_string();
+  if Version::parse(&version).is_err() {
+    bail!(t!("Invalid semver {}", version));
+  }
+  let (version, exe_path) = install::install(meta, true, false, Some(version))?;
+  link(&exe_path, &install_path)?;
+  meta.set_project_pin(install_path.to_string_lossy().to_string(), version);
+  Ok(())
+}
+
+fn where_version(version: String) -> Result<()> {
+  let version = Version::parse(normalize_version(&version))?;
+  let dir = deno_version_dir(&version);
+  if !dir.join(DENO_EXE).exists() {
+    bail!(t!("deno `{}` is not installed", version));
+  }
+  println!("{}", dir.display());
+  Ok(())
+}
+
+/// the mise integration, dvm acts as the installer of a mise deno plugin
+pub fn exec(meta: &mut DvmMeta, command: MiseCommands) -> Result<()> {
+  match command {
+    MiseCommands::Plugin { dir } => plugin(dir),
+    MiseCommands::ListVersions => list_versions(),
+    MiseCommands::Install { version, path } => install_into(meta, version, path),
+    MiseCommands::Where { version } => where_version(version),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_script() {
+    assert_eq!(
+      script("/opt/dvm", SCRIPTS[1].1),
+      "#!/bin/sh\n# generated by `dvm mise plugin`\nexec '/opt/dvm' mise install \"$ASDF_INSTALL_VERSION\" \"$ASDF_INSTALL_PATH\"\n"
+    );
+  }
+}
@@ -1,14 +1,46 @@
 pub mod activate;
 pub mod alias;
+pub mod build_from_source;
+pub mod ci;
 pub mod clean;
+pub mod compact;
 pub mod completions;
+pub mod config;
+pub mod current;
 pub mod deactivate;
+pub mod devcontainer;
+pub mod dockerfile;
 pub mod doctor;
+pub mod editor;
+pub mod env;
 pub mod exec;
+pub mod history;
+pub mod hook;
 pub mod info;
 pub mod install;
 pub mod list;
+pub mod lock;
+pub mod lsp_helper;
+pub mod migrate_layout;
+pub mod mise;
+pub mod paths;
+pub mod prefetch_latest;
+pub mod prefetch_targets;
+pub mod projects;
+pub mod proto_plugin;
 pub mod registry;
+pub mod rehash;
+pub mod report;
+pub mod rollback;
+pub mod sbom;
+pub mod self_update;
+pub mod serve;
+pub mod shell_plugin;
+pub mod stats;
 pub mod uninstall;
+pub mod update_pin;
 pub mod upgrade;
 pub mod use_version;
+pub mod version_env;
+pub mod which;
+pub mod why;
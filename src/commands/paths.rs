@@ -0,0 +1,47 @@
+use crate::archive_cache;
+use crate::cli::DvmPath;
+use crate::config::DvmConfig;
+use crate::utils::{dvm_root, dvm_versions};
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// where dvm keeps things, so scripts and ci caching steps don't have to guess
+#[derive(Serialize)]
+struct Paths {
+  root: PathBuf,
+  versions: PathBuf,
+  cache: PathBuf,
+  config: PathBuf,
+}
+
+impl Paths {
+  fn new() -> Self {
+    Paths {
+      root: dvm_root(),
+      versions: dvm_versions(),
+      cache: archive_cache::archives_root(),
+      config: DvmConfig::path(),
+    }
+  }
+
+  fn get(self, path: DvmPath) -> PathBuf {
+    match path {
+      DvmPath::Root => self.root,
+      DvmPath::Versions => self.versions,
+      DvmPath::Cache => self.cache,
+      DvmPath::Config => self.config,
+    }
+  }
+}
+
+/// print one path, or all of them as json
+pub fn exec(path: Option<DvmPath>, json: bool) -> Result<()> {
+  let paths = Paths::new();
+  if json {
+    println!("{}", serde_json::to_string_pretty(&paths)?);
+  } else {
+    println!("{}", paths.get(path.unwrap_or(DvmPath::Root)).display());
+  }
+  Ok(())
+}
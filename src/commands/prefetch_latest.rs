@@ -0,0 +1,39 @@
+use crate::commands::install::{is_prefetched, prefetch};
+use crate::config::DvmConfig;
+use crate::consts::DVM_UPDATE_CHECK_FILENAME;
+use crate::meta::DvmMeta;
+use crate::utils::{deno_version_path, mark_if_due};
+use crate::version::get_latest_version;
+use anyhow::Result;
+use std::process::{Command, Stdio};
+
+///
+/// start `dvm prefetch-latest` as a detached process once a day if `prefetch-latest` is enabled,
+/// so the command being run is never slowed down by it.
+pub fn spawn_if_due() {
+  if !DvmConfig::load().prefetch_latest() || !mark_if_due(DVM_UPDATE_CHECK_FILENAME) {
+    return;
+  }
+  if let Ok(exe) = std::env::current_exe() {
+    Command::new(exe)
+      .arg("prefetch-latest")
+      .stdin(Stdio::null())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn()
+      .ok();
+  }
+}
+
+/// download the latest stable into the cache, unless it's installed or cached already
+pub fn exec(meta: &DvmMeta) -> Result<()> {
+  let latest = get_latest_version(&meta.registry())?;
+  if deno_version_path(&latest).exists() || is_prefetched(&latest) {
+    println!("{}", t!("deno v{} is ready already", latest));
+    return Ok(());
+  }
+
+  prefetch(&meta.registry(), &latest)?;
+  println!("{}", t!("deno v{} has been prefetched", latest));
+  Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::archive_cache;
+use crate::commands::exec::managed_deno_dir;
+use crate::commands::install::TARGET;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::project::ProjectConfig;
+use crate::{http, interrupt};
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use futures_util::future::try_join_all;
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// short target names accepted besides the full rust triples
+const TARGET_ALIASES: &[(&str, &str)] = &[
+  ("linux-x64", "x86_64-unknown-linux-gnu"),
+  ("linux-arm64", "aarch64-unknown-linux-gnu"),
+  ("windows-x64", "x86_64-pc-windows-msvc"),
+  ("macos-x64", "x86_64-apple-darwin"),
+  ("macos-arm64", "aarch64-apple-darwin"),
+];
+
+pub fn resolve_target(target: &str) -> Result<&'static str> {
+  TARGET_ALIASES
+    .iter()
+    .find(|(alias, triple)| *alias == target || *triple == target)
+    .map(|(_, triple)| *triple)
+    .ok_or_else(|| {
+      let names = TARGET_ALIASES.iter().map(|(alias, _)| *alias).collect::<Vec<_>>();
+      anyhow!("unknown target `{}`, available targets: {}", target, names.join(", "))
+    })
+}
+
+/// the os part of a triple, `unknown-linux-gnu` of `x86_64-unknown-linux-gnu`
+fn os_of(triple: &str) -> &str {
+  triple.split_once('-').map(|(_, os)| os).unwrap_or_default()
+}
+
+///
+/// the triple of another architecture of this os, `x64`, `arm64` or a target as `resolve_target` takes it,
+/// builds for another os can't run here, apple silicon runs the x86_64 build through rosetta.
+pub fn resolve_arch(arch: &str) -> Result<&'static str> {
+  let cpu = match arch {
+    "x64" | "amd64" | "x86_64" => Some("x86_64"),
+    "arm64" | "aarch64" => Some("aarch64"),
+    _ => None,
+  };
+  let triple = match cpu {
+    Some(cpu) => resolve_target(&format!("{}-{}", cpu, os_of(TARGET)))?,
+    None => resolve_target(arch)?,
+  };
+  if os_of(triple) != os_of(TARGET) {
+    bail!(t!("{} builds can't run on {}", triple, TARGET));
+  }
+  Ok(triple)
+}
+
+///
+/// the `DENO_DIR` deno would use when launched through dvm,
+/// falls back to deno's own default location.
+fn deno_dir(version: &str) -> Option<PathBuf> {
+  std::env::var_os("DENO_DIR")
+    .map(PathBuf::from)
+    .or_else(|| managed_deno_dir(&ProjectConfig::load(), version))
+    .or_else(|| dirs::cache_dir().map(|it| it.join("deno")))
+}
+
+/// download the archive into `dest`, `false` if the release doesn't have it
+async fn fetch(registry: &str, version: &Version, triple: &str, archive: &str, dest: &Path) -> Result<bool> {
+  let Some(data) = archive_cache::fetch(registry, version, triple, archive).await? else {
+    return Ok(false);
+  };
+
+  // write then rename, so an interrupted download never looks cached
+  let partial = dest.join(format!("{}.partial", archive));
+  let _staged = interrupt::stage(&partial);
+  fs::write(&partial, data)?;
+  fs::rename(partial, dest.join(archive))?;
+  Ok(true)
+}
+
+async fn prefetch_target(registry: &str, version: &Version, triple: &str, dest: &Path) -> Result<()> {
+  let archives = [format!("denort-{}.zip", triple), format!("deno-{}.zip", triple)];
+  if let Some(cached) = archives.iter().find(|it| dest.join(it).exists()) {
+    println!("{}", t!("{} is cached already", cached.bright_black()));
+    return Ok(());
+  }
+
+  let fetched = fetch(registry, version, triple, &archives[0], dest).await?
+    || fetch(registry, version, triple, &archives[1], dest).await?;
+  if !fetched {
+    bail!(t!("deno v{} has no release for {}", version, triple));
+  }
+  Ok(())
+}
+
+///
+/// put the runtime archives of foreign targets where `deno compile --target` looks for them,
+/// i.e. `$DENO_DIR/dl/release/v<version>/`, newer releases ship `denort`, older ones `deno`.
+pub fn exec(meta: &DvmMeta, version: String, targets: Vec<String>) -> Result<()> {
+  let Some(resolved) = meta.resolve_local_version(&version) else {
+    bail!(
+      "`{}` doesn't match an installed version, give an exact version instead",
+      version
+    );
+  };
+  let triples = targets
+    .iter()
+    .map(|it| resolve_target(it))
+    .collect::<Result<Vec<_>>>()?;
+
+  let Some(deno_dir) = deno_dir(&resolved.to_string()) else {
+    bail!("unable to determine DENO_DIR, set it explicitly");
+  };
+  let dest = deno_dir.join("dl").join("release").join(format!("v{}", resolved));
+  fs::create_dir_all(&dest)?;
+
+  // the targets are downloaded concurrently, the first failure cancels the others
+  let registry = meta.registry();
+  http::block_on(try_join_all(
+    triples
+      .into_iter()
+      .map(|triple| prefetch_target(&registry, &resolved, triple, &dest)),
+  ))?;
+
+  output::success(t!("Prefetched into {}", dest.display()));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_resolve_target() {
+    assert_eq!(resolve_target("linux-x64").unwrap(), "x86_64-unknown-linux-gnu");
+    assert_eq!(resolve_target("aarch64-apple-darwin").unwrap(), "aarch64-apple-darwin");
+    assert!(resolve_target("linux-mips").is_err());
+  }
+
+  #[test]
+  fn test_resolve_arch() {
+    let native = resolve_arch(TARGET.split('-').next().unwrap());
+    if TARGET.contains("-linux-") || TARGET.contains("-apple-") {
+      assert_eq!(native.unwrap(), TARGET);
+      assert_eq!(os_of(resolve_arch("arm64").unwrap()), os_of(TARGET));
+    }
+    assert!(resolve_arch("sparc").is_err());
+  }
+}
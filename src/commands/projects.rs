@@ -0,0 +1,62 @@
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::project::ProjectConfig;
+use crate::utils::{deno_version_path, is_exact_version, normalize_version};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use semver::Version;
+
+///
+/// register the current directory against a version, the one of its `.dvmrc` if none is given,
+/// so what the project needs is known to `dvm projects` and `dvm clean`.
+pub fn link(meta: &mut DvmMeta, version: Option<String>) -> Result<()> {
+  let dir = std::env::current_dir()?;
+  let Some(required) = version.or_else(|| ProjectConfig::load().and_then(|(_, config)| config.version)) else {
+    return Err(anyhow!(t!(
+      "No version given and there is no .dvmrc in {}",
+      dir.display()
+    )));
+  };
+  let version = if is_exact_version(&required) {
+    Version::parse(normalize_version(&required))?
+  } else {
+    meta.resolve_local_version(&required).ok_or_else(|| {
+      anyhow!(output::with_suggestion(
+        t!("no installed version matches `{}`", required),
+        &required,
+        &meta.version_names()
+      ))
+    })?
+  };
+
+  meta.set_project_pin(dir.to_string_lossy().to_string(), version.to_string());
+  output::success(t!("Linked {} to deno {}", dir.display(), version));
+  Ok(())
+}
+
+/// the registered projects, with whether their directory and their version are still there
+pub fn list(meta: &DvmMeta) -> Result<()> {
+  if meta.projects.is_empty() {
+    println!("{}", t!("No project is linked, run `dvm link` in a project"));
+    return Ok(());
+  }
+
+  let width = meta
+    .projects
+    .iter()
+    .map(|it| it.version.len())
+    .max()
+    .unwrap_or_default();
+  for project in &meta.projects {
+    let installed = Version::parse(&project.version)
+      .map(|it| deno_version_path(&it).exists())
+      .unwrap_or(false);
+    let state = match (project.exists(), installed) {
+      (false, _) => t!("directory missing").red(),
+      (true, false) => t!("not installed").yellow(),
+      (true, true) => t!("installed").green(),
+    };
+    println!("{:<width$}  {}  {}", project.version, state, project.dir, width = width);
+  }
+  Ok(())
+}
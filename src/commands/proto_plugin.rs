@@ -0,0 +1,78 @@
+use crate::meta::DvmMeta;
+use crate::output;
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+/// the target triples of the release archives, by the os names of proto
+const PLATFORMS: &[(&str, &str)] = &[
+  ("linux", "{arch}-unknown-linux-gnu"),
+  ("macos", "{arch}-apple-darwin"),
+  ("windows", "{arch}-pc-windows-msvc"),
+];
+
+///
+/// a proto TOML plugin downloading deno from `registry`, so a monorepo on proto gets the releases
+/// from the same registry or `dvm serve` mirror dvm uses, versions are resolved from the deno git tags.
+fn schema(registry: &str) -> String {
+  let mut schema = String::from(
+    "# deno from the dvm registry, generated by `dvm proto-plugin`\nname = \"deno\"\ntype = \"language\"\n",
+  );
+  for (os, target) in PLATFORMS {
+    let exe = if *os == "windows" { "deno.exe" } else { "deno" };
+    schema.push_str(&format!(
+      "\n[platform.{}]\ndownload-file = \"deno-{}.zip\"\nchecksum-file = \"deno-{}.zip.sha256sum\"\nexe-path = \"{}\"\n",
+      os, target, target, exe
+    ));
+  }
+  schema.push_str(&format!(
+    r#"
+[install]
+download-url = "{registry}release/v{{version}}/{{download_file}}"
+checksum-url = "{registry}release/v{{version}}/{{checksum_file}}"
+
+[install.arch]
+aarch64 = "aarch64"
+x86_64 = "x86_64"
+
+[resolve]
+git-url = "https://github.com/denoland/deno"
+"#,
+    registry = registry
+  ));
+  schema
+}
+
+/// print the proto plugin of the configured registry, or write it to `file`
+pub fn exec(meta: &DvmMeta, file: Option<PathBuf>) -> Result<()> {
+  let schema = schema(&meta.registry());
+  let Some(path) = file else {
+    print!("{}", schema);
+    return Ok(());
+  };
+  fs::write(&path, schema)?;
+  output::success(t!("The proto plugin has been written to {}", path.display()));
+  println!(
+    "{}",
+    t!(
+      "Add `deno = \"file://{}\"` to the [plugins] of .prototools",
+      path.display()
+    )
+  );
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::consts::REGISTRY_OFFICIAL;
+
+  #[test]
+  fn test_schema() {
+    let schema = schema(REGISTRY_OFFICIAL);
+    assert!(schema.contains("[platform.linux]\ndownload-file = \"deno-{arch}-unknown-linux-gnu.zip\"\n"));
+    assert!(schema.contains("exe-path = \"deno.exe\"\n"));
+    assert!(schema.contains("download-url = \"https://dl.deno.land/release/v{version}/{download_file}\"\n"));
+    assert!(toml::from_str::<toml::Value>(&schema).is_ok());
+  }
+}
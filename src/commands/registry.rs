@@ -1,36 +1,106 @@
 use std::process;
+use std::time::{Duration, Instant};
 
+use crate::config::DvmConfig;
 use crate::consts::REGISTRY_CN;
 use crate::consts::REGISTRY_NAME_CN;
 use crate::consts::REGISTRY_NAME_OFFICIAL;
 use crate::consts::REGISTRY_OFFICIAL;
 use crate::DvmMeta;
+use crate::{http, output, source};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use colored::Colorize;
+use futures_util::future::join_all;
+
+/// how long a registry gets to answer before it's considered unreachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub fn exec(meta: &mut DvmMeta, registry: Option<String>) -> Result<()> {
   let registry = registry.unwrap_or_else(|| REGISTRY_NAME_OFFICIAL.to_string());
 
   if registry == *REGISTRY_NAME_OFFICIAL {
     meta.registry = REGISTRY_OFFICIAL.to_string();
-    println!("Registry now set to the official registry \"{}\"", REGISTRY_OFFICIAL);
+    println!(
+      "{}",
+      t!("Registry now set to the official registry \"{}\"", REGISTRY_OFFICIAL)
+    );
   } else if registry == *REGISTRY_NAME_CN {
     meta.registry = REGISTRY_CN.to_string();
     println!(
-      "Registry now set to the CN mirror (that provided by @justjavac) \"{}\"",
-      REGISTRY_CN
+      "{}",
+      t!(
+        "Registry now set to the CN mirror (that provided by @justjavac) \"{}\"",
+        REGISTRY_CN
+      )
     )
   } else if registry.starts_with("http://") || registry.starts_with("https://") {
     meta.registry = registry;
   } else {
     eprintln!(
-      "The {} is not valid URL, please starts with `http` or `https`",
-      registry
+      "{}",
+      t!(
+        "The {} is not valid URL, please starts with `http` or `https`",
+        registry
+      )
     );
-    eprintln!("Registry will not be changed");
+    eprintln!("{}", t!("Registry will not be changed"));
     process::exit(1)
   }
 
   meta.save();
+  warn_if_configured();
+  Ok(())
+}
+
+/// the `registry` config key hides whatever `dvm registry` sets
+fn warn_if_configured() {
+  if let Some(registry) = DvmConfig::load().registry {
+    output::warn(t!(
+      "config.toml sets the registry to {}, which takes precedence, run `dvm config unset registry` to use this one",
+      registry.0
+    ));
+  }
+}
+
+/// the time a registry takes to answer a `HEAD` of its `release-latest.txt`
+async fn probe(registry: &str) -> Option<Duration> {
+  let url = source::latest_release_url(registry);
+  let start = Instant::now();
+  match tokio::time::timeout(PROBE_TIMEOUT, http::head(&url)).await {
+    Ok(Ok(response)) if response.is_success() => Some(start.elapsed()),
+    _ => None,
+  }
+}
+
+///
+/// race the official registry, the CN mirror and the current one if it's neither,
+/// `save` sets the fastest one as the registry.
+pub fn test(meta: &mut DvmMeta, save: bool) -> Result<()> {
+  let mut registries = vec![REGISTRY_OFFICIAL.to_string(), REGISTRY_CN.to_string()];
+  if !registries.contains(&meta.registry()) {
+    registries.push(meta.registry());
+  }
+  let latencies = http::block_on(async { Ok(join_all(registries.iter().map(|it| probe(it))).await) })?;
+
+  let mut results = registries.into_iter().zip(latencies).collect::<Vec<_>>();
+  results.sort_by_key(|(_, latency)| latency.unwrap_or(Duration::MAX));
+  for (registry, latency) in &results {
+    let current = if *registry == meta.registry() { "*" } else { " " };
+    match latency {
+      Some(latency) => println!("{}{:>9} ms  {}", current, latency.as_millis(), registry),
+      None => println!("{}{:>12}  {}", current, t!("unreachable").red(), registry),
+    }
+  }
+
+  let Some((fastest, Some(_))) = results.first() else {
+    bail!(t!("No registry is reachable"));
+  };
+  if save {
+    meta.registry = fastest.clone();
+    meta.save();
+    output::success(t!("Registry now set to the fastest one \"{}\"", fastest));
+    warn_if_configured();
+  }
   Ok(())
 }
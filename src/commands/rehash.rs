@@ -0,0 +1,117 @@
+use crate::commands::compact::restore;
+use crate::commands::use_version::{link_bin, remove_bin};
+use crate::consts::{DVM_CONFIG_FILENAME, DVM_STUBS_PATH_PREFIX, DVM_VERSION_CANARY};
+use crate::meta::{DvmMeta, ToVersionReq};
+use crate::output;
+use crate::project::ProjectConfig;
+use crate::utils::{check_is_deactivated, deno_bin_path, deno_canary_path, deno_version_path, dvm_root};
+use crate::version::local_versions;
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// rebuild the dvm bin directory and validate the metadata against the installed versions
+pub fn exec(meta: &DvmMeta) -> Result<()> {
+  let mut problems = 0;
+
+  if check_is_deactivated() {
+    if deno_bin_path().symlink_metadata().is_ok() {
+      remove_bin(&deno_bin_path())?;
+      println!(
+        "{}",
+        t!("Removed {} since dvm is deactivated", deno_bin_path().display())
+      );
+    }
+  } else {
+    match active_version() {
+      Some(raw) => {
+        if let Some(version) = (raw != DVM_VERSION_CANARY)
+          .then(|| meta.resolve_local_version(&raw))
+          .flatten()
+        {
+          restore(&version)?;
+        }
+        match resolve_exe_path(meta, &raw) {
+          Some(exe_path) if exe_path.exists() => {
+            link_bin(&exe_path)?;
+            println!(
+              "{}",
+              t!("Relinked deno {} to {}", raw.bright_black(), exe_path.display())
+            );
+          }
+          _ => {
+            problems += 1;
+            output::error(t!("The active version `{}` is not installed", raw));
+          }
+        }
+      }
+      None => println!("{}", t!("No active version recorded, skip relinking")),
+    }
+  }
+
+  for mapping in &meta.versions {
+    if !mapping.is_valid_mapping() {
+      problems += 1;
+      output::error(t!(
+        "Mapping {} -> {} is not valid, run `dvm clean`",
+        mapping.required,
+        mapping.current
+      ));
+    }
+  }
+  for alias in &meta.alias {
+    if alias.try_to_version_req().is_err() {
+      problems += 1;
+      output::error(t!(
+        "Alias {} -> {} is not a valid semver range",
+        alias.name,
+        alias.required
+      ));
+    }
+  }
+
+  let installed = local_versions();
+  if let Ok(stubs) = fs::read_dir(dvm_root().join(DVM_STUBS_PATH_PREFIX)) {
+    for stub in stubs.flatten() {
+      if !installed.iter().any(|it| *it == stub.file_name().to_string_lossy()) {
+        fs::remove_file(stub.path())?;
+        println!(
+          "{}",
+          t!(
+            "Removed stale stub {}",
+            stub.path().display().to_string().bright_black()
+          )
+        );
+      }
+    }
+  }
+
+  if problems == 0 {
+    output::success(t!("Rehashed, everything looks good."));
+  } else {
+    output::warn(t!("Rehashed, {} problem(s) found.", problems));
+  }
+  Ok(())
+}
+
+/// the executable of the version recorded by `dvm use`, if it can be resolved
+pub fn active_exe_path(meta: &DvmMeta) -> Option<PathBuf> {
+  active_version().and_then(|raw| resolve_exe_path(meta, &raw))
+}
+
+/// the version recorded by `dvm use` in the home folder config
+pub fn active_version() -> Option<String> {
+  let path = dirs::home_dir()?.join(DVM_CONFIG_FILENAME);
+  let content = fs::read_to_string(path).ok()?;
+  ProjectConfig::from_str(&content).ok()?.version
+}
+
+/// resolve a version, alias or range to an installed executable without network access
+fn resolve_exe_path(meta: &DvmMeta, raw: &str) -> Option<PathBuf> {
+  if raw == DVM_VERSION_CANARY {
+    return Some(deno_canary_path());
+  }
+  meta.resolve_local_version(raw).map(|v| deno_version_path(&v))
+}
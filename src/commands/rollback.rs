@@ -0,0 +1,65 @@
+use crate::commands::{compact, use_version};
+use crate::consts::{DVM_VERSION_CANARY, DVM_VERSION_SYSTEM};
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{deno_version_path, update_stub};
+use anyhow::{bail, Result};
+use colored::Colorize;
+use semver::Version;
+
+/// put back the mappings the last upgrade moved, the versions they pointed to have to be installed still
+fn restore_mappings(meta: &mut DvmMeta) {
+  for mapping in std::mem::take(&mut meta.replaced) {
+    let Ok(version) = Version::parse(&mapping.current) else {
+      continue;
+    };
+    if !compact::is_installed(&version) {
+      output::warn(t!(
+        "{} can't be mapped back to v{}, it's not installed anymore",
+        mapping.required,
+        version
+      ));
+      continue;
+    }
+    println!(
+      "{}",
+      t!(
+        "Mapped {} back to {}",
+        mapping.required.bright_black(),
+        mapping.current.bright_green()
+      )
+    );
+    meta.set_version_mapping(mapping.required, mapping.current);
+  }
+  meta.save();
+}
+
+///
+/// revert to the version active before the current one, in one step after a bad upgrade,
+/// the aliases and ranges moved by the last `dvm upgrade` point to their older versions again.
+pub fn exec(meta: &mut DvmMeta) -> Result<()> {
+  let Some(previous) = meta.previous_activation().cloned() else {
+    bail!(t!("There is no previous version to roll back to"));
+  };
+  restore_mappings(meta);
+
+  if previous.version == DVM_VERSION_CANARY || previous.version == DVM_VERSION_SYSTEM {
+    return use_version::exec(meta, Some(previous.version), false, false);
+  }
+  let version = Version::parse(&previous.version)?;
+  if !compact::is_installed(&version) {
+    bail!(t!(
+      "deno v{} is not installed anymore, run `dvm install {}` first",
+      version,
+      version
+    ));
+  }
+  compact::restore(&version)?;
+  let exe_path = deno_version_path(&version);
+  // what was asked for goes back into .dvmrc, e.g. `latest` or an alias
+  use_version::use_this_bin_path(&exe_path, &version, previous.required.clone(), false)?;
+  update_stub(&previous.version);
+  meta.record_activation(&previous.version, &previous.required);
+  output::success(t!("Rolled back to deno {}", version));
+  Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::archive_cache::sha256_hex;
+use crate::cli::SbomFormat;
+use crate::commands::compact::restore;
+use crate::commands::install::deno_asset;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::source;
+use crate::utils::{deno_version_path, format_timestamp, now};
+use crate::version::{local_versions, DVM};
+use anyhow::{bail, Result};
+use semver::Version;
+use serde_json::{json, Value};
+use std::fs;
+
+const SUPPLIER: &str = "Deno Land Inc.";
+const SUPPLIER_URL: &str = "https://deno.com";
+const LICENSE: &str = "MIT";
+
+/// what the bill of materials says about an installed deno
+struct Entry {
+  version: Version,
+  sha256: String,
+  url: String,
+}
+
+impl Entry {
+  fn purl(&self) -> String {
+    format!("pkg:github/denoland/deno@v{}", self.version)
+  }
+}
+
+/// `2022-11-20T08:00:00Z`
+fn iso_timestamp(secs: u64) -> String {
+  format_timestamp(secs).replace(' ', "T") + "Z"
+}
+
+fn cyclonedx(entries: &[Entry], timestamp: &str) -> Value {
+  let components = entries
+    .iter()
+    .map(|it| {
+      json!({
+        "type": "application",
+        "bom-ref": it.purl(),
+        "name": "deno",
+        "version": it.version.to_string(),
+        "supplier": { "name": SUPPLIER, "url": [SUPPLIER_URL] },
+        "hashes": [{ "alg": "SHA-256", "content": it.sha256 }],
+        "licenses": [{ "license": { "id": LICENSE } }],
+        "purl": it.purl(),
+        "externalReferences": [{ "type": "distribution", "url": it.url }],
+      })
+    })
+    .collect::<Vec<_>>();
+  json!({
+    "bomFormat": "CycloneDX",
+    "specVersion": "1.5",
+    "version": 1,
+    "metadata": {
+      "timestamp": timestamp,
+      "tools": { "components": [{ "type": "application", "name": "dvm", "version": DVM }] },
+    },
+    "components": components,
+  })
+}
+
+fn spdx(entries: &[Entry], timestamp: &str) -> Value {
+  let packages = entries
+    .iter()
+    .map(|it| {
+      json!({
+        "name": "deno",
+        "SPDXID": format!("SPDXRef-deno-{}", it.version),
+        "versionInfo": it.version.to_string(),
+        "supplier": format!("Organization: {}", SUPPLIER),
+        "downloadLocation": it.url,
+        "filesAnalyzed": false,
+        "checksums": [{ "algorithm": "SHA256", "checksumValue": it.sha256 }],
+        "licenseConcluded": LICENSE,
+        "licenseDeclared": LICENSE,
+        "copyrightText": "NOASSERTION",
+        "externalRefs": [{
+          "referenceCategory": "PACKAGE-MANAGER",
+          "referenceType": "purl",
+          "referenceLocator": it.purl(),
+        }],
+      })
+    })
+    .collect::<Vec<_>>();
+  // the namespace has to be unique per document, the hashes and the time make it so
+  let hashes = entries.iter().map(|it| it.sha256.as_str()).collect::<String>();
+  json!({
+    "spdxVersion": "SPDX-2.3",
+    "dataLicense": "CC0-1.0",
+    "SPDXID": "SPDXRef-DOCUMENT",
+    "name": "deno managed by dvm",
+    "documentNamespace": format!(
+      "https://dvm.deno.dev/spdx/{}",
+      &sha256_hex(format!("{}{}", hashes, timestamp).as_bytes())[..32]
+    ),
+    "creationInfo": { "created": timestamp, "creators": [format!("Tool: dvm-{}", DVM)] },
+    "packages": packages,
+  })
+}
+
+fn entry(meta: &DvmMeta, version: Version) -> Result<Entry> {
+  restore(&version)?;
+  let sha256 = sha256_hex(&fs::read(deno_version_path(&version))?);
+  let asset = deno_asset(&version)?;
+  let url = source::release_urls(&meta.registry(), &version, &asset.target, &asset.name).remove(0);
+  Ok(Entry { version, sha256, url })
+}
+
+///
+/// print a software bill of materials of the installed deno binaries, or of one version,
+/// with where each one was downloaded from and the sha256 of the executable.
+pub fn exec(meta: &DvmMeta, version: Option<String>, format: SbomFormat) -> Result<()> {
+  let versions = match version {
+    Some(version) => match meta.resolve_local_version(&version) {
+      Some(resolved) if local_versions().contains(&resolved.to_string()) => vec![resolved],
+      _ => bail!(output::with_suggestion(
+        t!("deno `{}` is not installed", version),
+        &version,
+        &meta.version_names()
+      )),
+    },
+    None => {
+      let mut versions = local_versions()
+        .iter()
+        .filter_map(|it| Version::parse(it).ok())
+        .collect::<Vec<_>>();
+      versions.sort();
+      versions
+    }
+  };
+  let entries = versions
+    .into_iter()
+    .map(|it| entry(meta, it))
+    .collect::<Result<Vec<_>>>()?;
+
+  let timestamp = iso_timestamp((now() / 1000) as u64);
+  let document = match format {
+    SbomFormat::Cyclonedx => cyclonedx(&entries, &timestamp),
+    SbomFormat::Spdx => spdx(&entries, &timestamp),
+  };
+  println!("{}", serde_json::to_string_pretty(&document)?);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_documents() {
+    let entries = [Entry {
+      version: Version::parse("1.28.0").unwrap(),
+      sha256: "ab".repeat(32),
+      url: "https://dl.deno.land/release/v1.28.0/deno.zip".to_string(),
+    }];
+    assert_eq!(iso_timestamp(0), "1970-01-01T00:00:00Z");
+    let bom = cyclonedx(&entries, "1970-01-01T00:00:00Z");
+    assert_eq!(bom["components"][0]["purl"], "pkg:github/denoland/deno@v1.28.0");
+    assert_eq!(bom["components"][0]["hashes"][0]["content"], "ab".repeat(32));
+    let doc = spdx(&entries, "1970-01-01T00:00:00Z");
+    assert_eq!(doc["packages"][0]["SPDXID"], "SPDXRef-deno-1.28.0");
+    assert_eq!(
+      doc["packages"][0]["downloadLocation"],
+      "https://dl.deno.land/release/v1.28.0/deno.zip"
+    );
+  }
+}
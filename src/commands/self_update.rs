@@ -0,0 +1,111 @@
+use crate::archive_cache::{parse_sha256, sha256_hex};
+use crate::commands::install::{unpack_impl, TARGET};
+use crate::config::{self, DvmConfig, SelfUpdateChannel};
+use crate::consts::{DVM_SELF_RELEASES, DVM_SELF_UPDATE_PATH_PREFIX};
+use crate::utils::{dvm_cache_root, now};
+use crate::{http, output, source};
+use anyhow::{anyhow, bail, Result};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const EXE_NAME: &str = if cfg!(windows) { "dvm.exe" } else { "dvm" };
+
+/// a rustls build stays on the rustls archives
+const ARCHIVE_SUFFIX: &str = if cfg!(feature = "rustls") { "-rustls" } else { "" };
+
+/// the url of the dvm archive of this target on a channel
+fn archive_url(channel: SelfUpdateChannel) -> String {
+  let archive = format!("dvm-{}{}.zip", TARGET, ARCHIVE_SUFFIX);
+  match channel {
+    SelfUpdateChannel::Stable => format!("{}latest/download/{}", DVM_SELF_RELEASES, archive),
+    SelfUpdateChannel::Nightly => format!("{}download/nightly/{}", DVM_SELF_RELEASES, archive),
+  }
+}
+
+/// `dvm --version` of an executable, which also tells it runs here
+fn version_of(exe: &Path) -> Result<String> {
+  let output = Command::new(exe)
+    .arg("--version")
+    .output()
+    .map_err(|err| anyhow!(t!("{} can't be run: {}", exe.display(), err)))?;
+  if !output.status.success() {
+    bail!(t!("{} exited with {}", exe.display(), output.status));
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// put `new` in place of the running executable, windows only lets it be renamed out of the way
+fn replace_running(new: &Path, current: &Path) -> Result<()> {
+  // next to the current one first, a rename across file systems fails
+  let staged = current.with_extension("new");
+  fs::copy(new, &staged)?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+  }
+  if cfg!(windows) {
+    fs::rename(current, current.with_extension(format!("{}.old.exe", now())))?;
+  }
+  fs::rename(&staged, current)?;
+  Ok(())
+}
+
+///
+/// replace this dvm with the newest build of a channel, `--channel` is recorded in the config for next time.
+/// the archive has to match the checksum published next to it, and the new dvm has to run before it's put in place.
+pub fn exec(channel: Option<String>) -> Result<()> {
+  if let Some(channel) = channel {
+    config::set("self-update-channel", &channel)?;
+  }
+  let channel = DvmConfig::load().self_update_channel();
+  let url = archive_url(channel);
+  let checksum = http::block_on(source::fetch(&[format!("{}.sha256sum", url)]))?
+    .and_then(|it| parse_sha256(&String::from_utf8_lossy(&it)))
+    .ok_or_else(|| anyhow!(t!("{} publishes no checksum, refusing to update", url)))?;
+  let Some(archive) = http::block_on(source::fetch(std::slice::from_ref(&url)))? else {
+    bail!(t!("{} has not been found", url));
+  };
+  let actual = sha256_hex(&archive);
+  if actual != checksum {
+    bail!(t!("The checksum of {} is {}, {} was published", url, actual, checksum));
+  }
+
+  let dir = dvm_cache_root().join(DVM_SELF_UPDATE_PATH_PREFIX);
+  fs::remove_dir_all(&dir).ok();
+  fs::create_dir_all(&dir)?;
+  let new = dir.join(EXE_NAME);
+  unpack_impl(archive, "dvm.zip", dir.clone(), new.clone())?;
+  let current = env::current_exe()?;
+  if fs::read(&new)? == fs::read(&current)? {
+    fs::remove_dir_all(&dir).ok();
+    output::success(t!("dvm is up to date"));
+    return Ok(());
+  }
+  let version = version_of(&new)?;
+  replace_running(&new, &current)?;
+  fs::remove_dir_all(&dir).ok();
+  output::success(t!("{} has been installed to {}", version, current.display()));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_archive_url() {
+    assert_eq!(
+      archive_url(SelfUpdateChannel::Nightly),
+      format!(
+        "https://github.com/justjavac/dvm/releases/download/nightly/dvm-{}{}.zip",
+        TARGET, ARCHIVE_SUFFIX
+      )
+    );
+    let hash = "AB".repeat(32);
+    assert_eq!(parse_sha256(&format!("{}  dvm.zip\n", hash)), Some(hash.to_lowercase()));
+    assert_eq!(parse_sha256("Not Found"), None);
+  }
+}
@@ -0,0 +1,181 @@
+use crate::archive_cache;
+use crate::assets;
+use crate::commands::install::TARGET;
+use crate::consts::{REGISTRY_LATEST_CANARY_PATH, REGISTRY_LATEST_RELEASE_PATH};
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::{http, source};
+use anyhow::Result;
+use semver::Version;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread;
+
+/// the port `dvm serve` listens on unless `--port` is given
+const DEFAULT_PORT: u16 = 8473;
+
+/// the address `dvm serve` listens on unless `--host` is given, other machines need `--host 0.0.0.0`
+const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// what the server answers a request with
+#[derive(Debug, Eq, PartialEq)]
+enum Route {
+  /// `release/v<version>/<asset>`, served from the download cache
+  Release(Version, String),
+  /// `release-latest.txt`, `canary-latest.txt` and `canary/<hash>/<asset>`, passed through to the registry
+  Upstream(String),
+}
+
+/// map a request path onto the registry layout, `None` for anything else
+fn route(path: &str) -> Option<Route> {
+  let path = path.split('?').next()?.trim_start_matches('/');
+  if path == REGISTRY_LATEST_RELEASE_PATH || path == REGISTRY_LATEST_CANARY_PATH {
+    return Some(Route::Upstream(path.to_string()));
+  }
+  let parts = path.split('/').collect::<Vec<_>>();
+  // the parts end up in cache paths, nothing may point outside of it, windows separates paths with `\` too
+  if parts
+    .iter()
+    .any(|it| it.is_empty() || it.starts_with('.') || it.contains('\\'))
+  {
+    return None;
+  }
+  match parts.as_slice() {
+    ["release", version, asset] => Some(Route::Release(
+      Version::parse(version.strip_prefix('v')?).ok()?,
+      asset.to_string(),
+    )),
+    ["canary", _, _] => Some(Route::Upstream(path.to_string())),
+    _ => None,
+  }
+}
+
+/// the lock of an asset, so clients asking for the same release at once share one download
+fn asset_lock(version: &Version, asset: &str) -> Arc<Mutex<()>> {
+  static LOCKS: OnceLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> = OnceLock::new();
+  let mut locks = LOCKS
+    .get_or_init(Default::default)
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner);
+  locks.entry(format!("v{}/{}", version, asset)).or_default().clone()
+}
+
+fn fetch(registry: &str, route: &Route) -> Result<Option<Vec<u8>>> {
+  match route {
+    Route::Release(version, asset) => {
+      let lock = asset_lock(version, asset);
+      let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+      // the request only names the asset, the ones not named after a target get this host's
+      let target = assets::target_of(asset).unwrap_or(TARGET);
+      http::block_on(archive_cache::fetch(registry, version, target, asset))
+    }
+    Route::Upstream(path) => http::block_on(source::fetch(&[format!("{}{}", registry, path)])),
+  }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &[u8], head: bool) -> std::io::Result<()> {
+  write!(
+    stream,
+    "HTTP/1.1 {}\r\nContent-Length: {}\r\nContent-Type: application/octet-stream\r\nConnection: close\r\n\r\n",
+    status,
+    body.len()
+  )?;
+  if !head {
+    stream.write_all(body)?;
+  }
+  stream.flush()
+}
+
+fn handle(registry: &str, mut stream: TcpStream) -> std::io::Result<()> {
+  let mut request_line = String::new();
+  let mut reader = BufReader::new(stream.try_clone()?);
+  reader.read_line(&mut request_line)?;
+  // the headers are of no interest
+  let mut header = String::new();
+  while reader.read_line(&mut header)? > 2 {
+    header.clear();
+  }
+
+  let mut parts = request_line.split_whitespace();
+  let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+  let head = method == "HEAD";
+  let status = if method != "GET" && !head {
+    respond(&mut stream, "405 Method Not Allowed", b"", false)?;
+    "405"
+  } else {
+    match route(path).map(|it| fetch(registry, &it)) {
+      Some(Ok(Some(data))) => {
+        respond(&mut stream, "200 OK", &data, head)?;
+        "200"
+      }
+      None | Some(Ok(None)) => {
+        respond(&mut stream, "404 Not Found", b"", head)?;
+        "404"
+      }
+      Some(Err(err)) => {
+        output::warn(t!("Failed to serve {}: {}", path, err));
+        respond(&mut stream, "502 Bad Gateway", b"", head)?;
+        "502"
+      }
+    }
+  };
+  println!("{} {} {}", method, path, status);
+  Ok(())
+}
+
+///
+/// serve the download cache in the registry layout, the releases missing from it are downloaded from
+/// the configured registry and kept, so a team pointing `dvm registry` here downloads each release once.
+pub fn exec(meta: &DvmMeta, host: Option<String>, port: Option<u16>) -> Result<()> {
+  let host = host.unwrap_or_else(|| DEFAULT_HOST.to_string());
+  let port = port.unwrap_or(DEFAULT_PORT);
+  let listener = TcpListener::bind((host.as_str(), port))?;
+  output::success(t!(
+    "Serving the dvm cache on {}:{}, run `dvm registry http://<this host>:{}/` to use it",
+    host,
+    port,
+    port
+  ));
+  for stream in listener.incoming() {
+    let Ok(stream) = stream else {
+      continue;
+    };
+    let registry = meta.registry();
+    thread::spawn(move || {
+      if let Err(err) = handle(&registry, stream) {
+        output::warn(t!("Failed to answer a request: {}", err));
+      }
+    });
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_route() {
+    assert_eq!(
+      route("/release/v1.28.0/deno-x86_64-unknown-linux-gnu.zip"),
+      Some(Route::Release(
+        Version::parse("1.28.0").unwrap(),
+        "deno-x86_64-unknown-linux-gnu.zip".to_string()
+      ))
+    );
+    assert_eq!(
+      route("/release-latest.txt?t=1"),
+      Some(Route::Upstream("release-latest.txt".to_string()))
+    );
+    assert_eq!(
+      route("/canary/abc/deno.zip"),
+      Some(Route::Upstream("canary/abc/deno.zip".to_string()))
+    );
+    assert_eq!(route("/release/v1.28.0/../../config.toml"), None);
+    assert_eq!(route("/release/v1.28.0/deno\\..\\..\\config.toml"), None);
+    assert_eq!(route("/release/1.28/deno.zip"), None);
+    assert_eq!(route("/"), None);
+  }
+}
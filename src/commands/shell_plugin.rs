@@ -0,0 +1,120 @@
+use crate::cli::{HookShell, PluginShell};
+use crate::commands::env;
+use crate::commands::hook;
+use crate::output;
+use crate::version;
+use anyhow::Result;
+use clap::Command;
+use clap_complete::{generate, Shell};
+use std::fs;
+use std::path::PathBuf;
+
+/// the environment, then the cd hook
+fn conf(shell: HookShell) -> String {
+  format!(
+    "# generated by `dvm shell-plugin`\n{}\n{}",
+    env::snippet(shell.into()),
+    hook::script(shell)
+  )
+}
+
+fn completions(app: &mut Command, shell: Shell) -> Vec<u8> {
+  let mut completions = Vec::new();
+  generate(shell, app, "dvm", &mut completions);
+  completions
+}
+
+///
+/// the files of a plugin, relative to its directory:
+///   fish: `conf.d/dvm.fish` and `completions/dvm.fish` for fisher, `init.fish` for oh-my-fish,
+///   zsh: `dvm.plugin.zsh` and `_dvm`, oh-my-zsh puts the plugin directory into `fpath`,
+///   powershell: `dvm.psm1` with the argument completers and its manifest `dvm.psd1`,
+///   cmd: `dvm-activate.cmd` for the current session and `deno.cmd` which goes through `dvm exec`.
+fn files(app: &mut Command, shell: PluginShell) -> Vec<(PathBuf, Vec<u8>)> {
+  match shell {
+    PluginShell::Fish => vec![
+      (PathBuf::from("conf.d/dvm.fish"), conf(HookShell::Fish).into_bytes()),
+      (PathBuf::from("completions/dvm.fish"), completions(app, Shell::Fish)),
+      (PathBuf::from("init.fish"), b"source $path/conf.d/dvm.fish\n".to_vec()),
+    ],
+    PluginShell::Zsh => vec![
+      (PathBuf::from("dvm.plugin.zsh"), conf(HookShell::Zsh).into_bytes()),
+      (PathBuf::from("_dvm"), completions(app, Shell::Zsh)),
+    ],
+    PluginShell::Powershell => {
+      // `using` statements of the completers have to come first in a script
+      let completions = String::from_utf8_lossy(&completions(app, Shell::PowerShell)).into_owned();
+      let (using, completers): (Vec<_>, Vec<_>) = completions.lines().partition(|it| it.starts_with("using "));
+      let module = format!(
+        "{}\n{}\n{}\n",
+        using.join("\n"),
+        conf(HookShell::Powershell),
+        completers.join("\n")
+      );
+      let manifest = format!(
+        "@{{\n  RootModule = 'dvm.psm1'\n  ModuleVersion = '{}'\n  Description = 'deno version manager'\n  FunctionsToExport = @()\n}}\n",
+        version::DVM
+      );
+      vec![
+        (PathBuf::from("dvm.psm1"), module.into_bytes()),
+        (PathBuf::from("dvm.psd1"), manifest.into_bytes()),
+      ]
+    }
+    PluginShell::Cmd => {
+      let dvm = hook::dvm_exe();
+      // the printed lines are all `set`, `call` expands their `%PATH%` once more
+      let activate = format!(
+        "@echo off\r\nrem generated by `dvm shell-plugin cmd`\r\nfor /f \"delims=\" %%i in ('\"{}\" env --shell cmd') do call %%i\r\nfor /f \"delims=\" %%i in ('\"{}\" resolve-for-cwd') do set \"PATH=%%i;%PATH%\"\r\n",
+        dvm, dvm
+      );
+      let wrapper = format!("@\"{}\" exec %*\r\n", dvm);
+      vec![
+        (PathBuf::from("dvm-activate.cmd"), activate.into_bytes()),
+        (PathBuf::from("deno.cmd"), wrapper.into_bytes()),
+      ]
+    }
+  }
+}
+
+/// write a plugin laid out the way the plugin managers of `shell` install it
+pub fn exec(app: &mut Command, shell: PluginShell, dir: Option<PathBuf>) -> Result<()> {
+  let dir = dir.unwrap_or_else(|| {
+    PathBuf::from(match shell {
+      PluginShell::Fish => "dvm.fish",
+      PluginShell::Zsh | PluginShell::Powershell | PluginShell::Cmd => "dvm",
+    })
+  });
+  for (path, content) in files(app, shell) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, content)?;
+  }
+  let dir = fs::canonicalize(dir)?;
+
+  output::success(t!("The plugin has been written to {}", dir.display()));
+  match shell {
+    PluginShell::Fish => println!("{}", t!("Install it with `fisher install {}`", dir.display())),
+    PluginShell::Zsh => println!(
+      "{}",
+      t!(
+        "Move it to $ZSH_CUSTOM/plugins/dvm and add `dvm` to the plugins in ~/.zshrc, or source {} from ~/.zshrc",
+        dir.join("dvm.plugin.zsh").display()
+      )
+    ),
+    PluginShell::Powershell => println!(
+      "{}",
+      t!(
+        "Move it into a directory of $env:PSModulePath and add `Import-Module dvm` to $PROFILE, or import {} from $PROFILE",
+        dir.join("dvm.psd1").display()
+      )
+    ),
+    PluginShell::Cmd => println!(
+      "{}",
+      t!(
+        "Put {} in PATH before the dvm bin directory, then run `dvm-activate` in a cmd session",
+        dir.display()
+      )
+    ),
+  }
+  Ok(())
+}
@@ -0,0 +1,168 @@
+use crate::archive_cache;
+use crate::config;
+use crate::consts::{DVM_CANARY_PATH_PREFIX, DVM_DENO_DIR_PATH_PREFIX, DVM_UPDATE_CHECK_FILENAME};
+use crate::http_trace::redact_url;
+use crate::meta::DvmMeta;
+use crate::utils::{dvm_cache_root, dvm_root, dvm_versions, format_timestamp};
+use crate::version::local_versions;
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use toml::Value;
+
+/// bytes taken by each kind of thing dvm keeps
+#[derive(Serialize)]
+struct DiskUsage {
+  versions: u64,
+  canary: u64,
+  archives: u64,
+  deno_dirs: u64,
+}
+
+#[derive(Serialize)]
+struct Stats {
+  versions: usize,
+  oldest: Option<String>,
+  newest: Option<String>,
+  disk_usage: DiskUsage,
+  /// unix time in seconds
+  last_update_check: Option<u64>,
+  registry: String,
+  config: toml::value::Table,
+}
+
+/// the size of a file or of everything under a directory, symlinks aren't followed
+pub fn disk_usage(path: &Path) -> u64 {
+  let Ok(metadata) = fs::symlink_metadata(path) else {
+    return 0;
+  };
+  if !metadata.is_dir() {
+    return metadata.len();
+  }
+  fs::read_dir(path)
+    .map(|entries| entries.flatten().map(|it| disk_usage(&it.path())).sum())
+    .unwrap_or(0)
+}
+
+pub fn megabytes(bytes: u64) -> String {
+  format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+  let modified = fs::metadata(path).ok()?.modified().ok()?;
+  Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+impl Stats {
+  fn collect(meta: &DvmMeta) -> Result<Self> {
+    let mut versions = local_versions()
+      .iter()
+      .filter_map(|it| Version::parse(it).ok())
+      .collect::<Vec<_>>();
+    versions.sort();
+
+    // secrets are masked, and so are the passwords of urls, e.g. of `proxy` or `policy-url`
+    let mut config = config::list()?;
+    for (key, value) in config.iter_mut() {
+      if config::SECRET_KEYS.contains(&key.as_str()) {
+        *value = Value::String("***".to_string());
+      } else if let Value::String(text) = value {
+        *text = redact_url(text);
+      }
+    }
+
+    Ok(Stats {
+      versions: versions.len(),
+      oldest: versions.first().map(ToString::to_string),
+      newest: versions.last().map(ToString::to_string),
+      disk_usage: DiskUsage {
+        versions: disk_usage(&dvm_versions()),
+        canary: disk_usage(&dvm_root().join(DVM_CANARY_PATH_PREFIX)),
+        archives: disk_usage(&archive_cache::archives_root()),
+        deno_dirs: disk_usage(&dvm_cache_root().join(DVM_DENO_DIR_PATH_PREFIX)),
+      },
+      last_update_check: modified_secs(&dvm_cache_root().join(DVM_UPDATE_CHECK_FILENAME)),
+      registry: redact_url(&meta.registry()),
+      config,
+    })
+  }
+
+  fn print(&self) {
+    let version = |it: &Option<String>| {
+      it.as_ref()
+        .map(|v| format!("v{}", v))
+        .unwrap_or_else(|| "-".to_string())
+    };
+    println!(
+      "{:<20} {}",
+      "versions",
+      t!(
+        "{} installed, oldest {}, newest {}",
+        self.versions,
+        version(&self.oldest),
+        version(&self.newest)
+      )
+    );
+    let usage = &self.disk_usage;
+    for (name, bytes) in [
+      ("versions", usage.versions),
+      ("canary", usage.canary),
+      ("archives", usage.archives),
+      ("deno dirs", usage.deno_dirs),
+    ] {
+      println!("{:<20} {}", format!("disk usage {}", name), megabytes(bytes));
+    }
+    let checked = self
+      .last_update_check
+      .map(format_timestamp)
+      .unwrap_or_else(|| t!("never"));
+    println!("{:<20} {}", "last update check", checked);
+    println!("{:<20} {}", "registry", self.registry);
+    if self.config.is_empty() {
+      println!("{:<20} {}", "config", t!("defaults").bright_black());
+    }
+    for (index, (key, value)) in self.config.iter().enumerate() {
+      let value = match value {
+        Value::String(it) => it.clone(),
+        value => value.to_string(),
+      };
+      println!(
+        "{:<20} {} = {}",
+        if index == 0 { "config" } else { "" },
+        key.bright_black(),
+        value
+      );
+    }
+  }
+}
+
+/// an overview of what dvm keeps and how it's set up, for finding out disk bloat and support requests
+pub fn exec(meta: &DvmMeta, json: bool) -> Result<()> {
+  let stats = Stats::collect(meta)?;
+  if json {
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+  } else {
+    stats.print();
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_disk_usage() {
+    let root = std::env::temp_dir().join(format!("dvm-stats-{}", std::process::id()));
+    fs::create_dir_all(root.join("1.28.0")).unwrap();
+    fs::write(root.join("1.28.0").join("deno"), [0u8; 10]).unwrap();
+    fs::write(root.join(".dvmstub"), [0u8; 5]).unwrap();
+    assert_eq!(disk_usage(&root), 15);
+    assert_eq!(disk_usage(&root.join("missing")), 0);
+    fs::remove_dir_all(root).unwrap();
+  }
+}
@@ -1,42 +1,157 @@
-use crate::consts::DVM_CACHE_PATH_PREFIX;
-use crate::utils::{deno_version_path, dvm_root};
-use crate::version::current_version;
-use anyhow::Result;
+use crate::commands::compact;
+use crate::commands::list::Roles;
+use crate::consts::DVM_VERSION_SYSTEM;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{
+  deno_target_path, deno_version_dir, deno_version_path, ensure_versions_writable, external_deno, normalize_version,
+};
+use crate::version::{current_version, local_versions};
+use anyhow::{bail, Result};
 use semver::Version;
 use std::fs;
 use std::process::exit;
 
-pub fn exec(version: Option<String>) -> Result<()> {
+/// the deno dvm didn't install is only removed when asked for with `--force`
+fn uninstall_system(force: bool) -> Result<()> {
+  let Some(path) = external_deno() else {
+    bail!(t!("There is no deno outside of dvm in PATH"));
+  };
+  if !force {
+    bail!(t!(
+      "{} is not managed by dvm, pass `--force` to remove it anyway",
+      path.display()
+    ));
+  }
+  fs::remove_file(&path)?;
+  println!("{}", t!("Removed {}", path.display()));
+  Ok(())
+}
+
+/// what depends on a version, an uninstall would pull the binary out from under it
+fn dependents(roles: &Roles, in_path: Option<&str>, version: &str) -> Vec<String> {
+  let mut dependents = Vec::new();
+  if roles.active.as_deref() == Some(version) || in_path == Some(version) {
+    dependents.push(t!("it's the active version"));
+  }
+  if roles.default.as_deref() == Some(version) {
+    dependents.push(t!("it's the default version"));
+  }
+  for dir in roles.pinned.get(version).into_iter().flatten() {
+    dependents.push(t!("pinned by the project {}", dir));
+  }
+  dependents
+}
+
+/// remove the build of `target` only, the version directory goes with its last build
+fn uninstall_arch(meta: &mut DvmMeta, version: &Version, target: &str) -> Result<()> {
+  let exe_path = deno_target_path(version, target);
+  if !exe_path.exists() {
+    bail!(t!("deno v{} for {} is not installed.", version, target));
+  }
+
+  ensure_versions_writable()?;
+  fs::remove_dir_all(exe_path.parent().unwrap())?;
+  let version_dir = deno_version_dir(version);
+  if fs::read_dir(&version_dir)?.next().is_none() {
+    fs::remove_dir(&version_dir)?;
+  }
+
+  let name = version.to_string();
+  if let Some(arches) = meta.arches.get_mut(&name) {
+    arches.remove(target);
+    if arches.is_empty() {
+      meta.arches.remove(&name);
+    }
+  }
+  meta.save();
+  println!("{}", t!("deno v{} for {} removed.", version, target));
+  Ok(())
+}
+
+///
+/// remove an installed version, the active and the default versions and the ones pinned by a linked project
+/// are kept unless `force` is given, they're listed with what depends on them.
+/// a version installed only for other architectures is installed too, `target` removes just one of its builds.
+pub fn exec(meta: &mut DvmMeta, version: Option<String>, force: bool, target: Option<&str>) -> Result<()> {
+  if version.as_deref() == Some(DVM_VERSION_SYSTEM) {
+    return uninstall_system(force);
+  }
   let target_version = match version {
-    Some(target_version) => match Version::parse(&target_version) {
+    Some(target_version) => match Version::parse(normalize_version(&target_version)) {
       Ok(ver) => ver,
       Err(_) => {
-        eprintln!("Invalid semver");
+        eprintln!("{}", t!("Invalid semver"));
         exit(1)
       }
     },
     None => unimplemented!(),
   };
-  let target_exe_path = deno_version_path(&target_version);
+  if let Some(target) = target {
+    return uninstall_arch(meta, &target_version, target);
+  }
 
-  println!("{}", target_exe_path.display());
+  println!("{}", deno_version_path(&target_version).display());
 
-  if !target_exe_path.exists() {
-    eprintln!("deno v{} is not installed.", target_version);
+  let name = target_version.to_string();
+  if !compact::is_installed(&target_version) && !meta.arches.contains_key(&name) {
+    eprintln!("{}", t!("deno v{} is not installed.", target_version));
+    output::suggest(&name, &local_versions());
     exit(1)
   }
 
-  let current_version = current_version().unwrap();
-
-  if current_version == target_version.to_string() {
-    println!("Failed: deno v{} is in use.", target_version);
-    exit(1);
+  // what the linked projects pin now, not when they were linked
+  meta.resolve_project_pins();
+  let dependents = dependents(&Roles::of(meta), current_version().as_deref(), &name);
+  if !dependents.is_empty() {
+    let list = dependents
+      .iter()
+      .map(|it| format!("  - {}", it))
+      .collect::<Vec<_>>()
+      .join("\n");
+    if !force {
+      bail!(t!(
+        "deno v{} is still needed, pass `--force` to remove it anyway:\n{}",
+        target_version,
+        list
+      ));
+    }
+    output::warn(t!(
+      "Removing deno v{} though it's still needed:\n{}",
+      target_version,
+      list
+    ));
   }
 
-  let version_dir = dvm_root().join(format!("{}/{}", DVM_CACHE_PATH_PREFIX, target_version));
+  ensure_versions_writable()?;
+  let version_dir = deno_version_dir(&target_version);
 
   fs::remove_dir_all(&version_dir).unwrap();
-  println!("deno v{} removed.", target_version);
+  meta.origins.remove(&name);
+  meta.arches.remove(&name);
+  meta.save();
+  println!("{}", t!("deno v{} removed.", target_version));
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_dependents() {
+    let roles = Roles {
+      active: Some("1.28.0".to_string()),
+      default: Some("1.28.0".to_string()),
+      pinned: [("1.41.0".to_string(), vec!["/work/app".to_string()])].into(),
+    };
+    assert_eq!(dependents(&roles, None, "1.28.0").len(), 2);
+    assert_eq!(
+      dependents(&roles, None, "1.41.0"),
+      vec!["pinned by the project /work/app".to_string()]
+    );
+    assert_eq!(dependents(&roles, Some("1.40.0"), "1.40.0").len(), 1);
+    assert!(dependents(&roles, None, "1.39.0").is_empty());
+  }
+}
@@ -0,0 +1,94 @@
+use crate::consts::{DVM_CONFIG_FILENAME, DVM_VERSION_CANARY};
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::project::{self, Channel, ProjectConfig};
+use crate::version::{get_latest_version, remote_versions};
+use anyhow::{anyhow, bail, Result};
+use semver::Version;
+use std::path::Path;
+
+/// the newest release of a channel listed in the versions
+fn newest(versions: &[String], channel: &Channel) -> Option<Version> {
+  versions
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .filter(|it| match channel {
+      Channel::Rc => it.pre.is_empty() || it.pre.as_str().starts_with("rc"),
+      Channel::Range(range) => range.matches(it),
+      Channel::Stable | Channel::Canary => it.pre.is_empty(),
+    })
+    .max()
+}
+
+/// the build a channel points at right now, canary never has more than one
+pub fn resolve(registry: &str, channel: &Channel) -> Result<String> {
+  match channel {
+    Channel::Canary => Ok(DVM_VERSION_CANARY.to_string()),
+    Channel::Stable => Ok(get_latest_version(registry)?.to_string()),
+    channel => newest(&remote_versions()?, channel)
+      .map(|it| it.to_string())
+      .ok_or_else(|| anyhow!(t!("No release follows the channel `{}`", channel))),
+  }
+}
+
+///
+/// pin the channel of the `.dvmrc` in the current directory to the build it points at,
+/// only if it has no version line yet, the pinned version is returned then.
+pub fn pin_unresolved(registry: &str) -> Result<Option<String>> {
+  let Some((
+    _,
+    ProjectConfig {
+      version: None,
+      channel: Some(channel),
+      ..
+    },
+  )) = ProjectConfig::load()
+  else {
+    return Ok(None);
+  };
+  let version = resolve(registry, &channel)?;
+  project::save_version(Path::new(DVM_CONFIG_FILENAME), &version)?;
+  output::success(t!("The channel `{}` has been pinned to {}", channel, version));
+  Ok(Some(version))
+}
+
+/// advance the version line of the `.dvmrc` in the current directory to the newest build of its channel
+pub fn exec(meta: &DvmMeta) -> Result<()> {
+  let Some((_, config)) = ProjectConfig::load() else {
+    bail!(t!("There is no {} in the current directory", DVM_CONFIG_FILENAME));
+  };
+  let Some(channel) = config.channel else {
+    bail!(t!(
+      "{} follows no channel, add a line like `channel = 1.x` to it",
+      DVM_CONFIG_FILENAME
+    ));
+  };
+  let version = resolve(&meta.registry(), &channel)?;
+  if config.version.as_deref() == Some(version.as_str()) {
+    output::success(t!("{} is the newest of the channel `{}` already", version, channel));
+    return Ok(());
+  }
+  project::save_version(Path::new(DVM_CONFIG_FILENAME), &version)?;
+  output::success(t!(
+    "The pin has moved from {} to {}, run `dvm use` to switch to it",
+    config.version.unwrap_or_else(|| "-".to_string()),
+    version
+  ));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::str::FromStr;
+
+  #[test]
+  fn test_newest() {
+    let versions = ["1.46.3", "2.0.0-rc.2", "1.45.0", "2.0.0-beta.1"].map(String::from);
+    let newest = |channel: &str| newest(&versions, &Channel::from_str(channel).unwrap()).map(|it| it.to_string());
+    assert_eq!(newest("rc").as_deref(), Some("2.0.0-rc.2"));
+    assert_eq!(newest("1.x stable").as_deref(), Some("1.46.3"));
+    assert_eq!(newest("~1.45").as_deref(), Some("1.45.0"));
+    assert_eq!(newest("3.x"), None);
+  }
+}
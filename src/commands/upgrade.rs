@@ -1,32 +1,180 @@
+use crate::commands::use_version::check_exe;
+use crate::meta::{Alias, VersionMapping};
+use crate::plan::{self, MappingChange, Plan};
+use crate::utils::{deno_version_dir, deno_version_path};
 use crate::{
   commands::install,
-  consts::{DVM_VERSION_CANARY, DVM_VERSION_INVALID},
+  consts::{DVM_VERSION_CANARY, DVM_VERSION_INVALID, DVM_VERSION_LTS},
+  output,
   utils::best_version,
   version::{remote_versions, VersionArg},
   DvmMeta,
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use colored::Colorize;
+use semver::{Version, VersionReq};
+use std::fs;
 use std::str::FromStr;
 
-pub fn exec(meta: &mut DvmMeta, alias: Option<String>) -> Result<()> {
+/// `--lts` upgrades the predefined `lts` alias
+pub fn alias_of(lts: bool, alias: Option<String>) -> Option<String> {
+  match lts {
+    true => Some(DVM_VERSION_LTS.to_string()),
+    false => alias,
+  }
+}
+
+/// the aliases upgraded when none is given, `lts` only once it's been used
+fn upgraded_aliases(meta: &DvmMeta) -> Vec<Alias> {
+  meta
+    .list_alias()
+    .into_iter()
+    .filter(|it| it.name != DVM_VERSION_LTS || meta.get_version_mapping(DVM_VERSION_LTS).is_some())
+    .collect()
+}
+
+/// whether moving an alias from `current` to `latest` goes back, anything that isn't a version never does
+fn is_downgrade(current: &str, latest: &str) -> bool {
+  match (Version::parse(current), Version::parse(latest)) {
+    (Ok(current), Ok(latest)) => latest < current,
+    _ => false,
+  }
+}
+
+///
+/// whether an alias may be moved from `current` to `latest`, a downgrade only with `--allow-downgrade`,
+/// it's warned about either way.
+fn may_move(alias: &str, current: &str, latest: &str, allow_downgrade: bool) -> bool {
+  if !is_downgrade(current, latest) {
+    return true;
+  }
+  let versions = format!("v{} → v{}", current, latest);
+  if allow_downgrade {
+    output::warn(t!("Downgrading {}: {}", alias, versions.bright_red()));
+  } else {
+    output::warn(t!(
+      "{} would be downgraded: {}, pass --allow-downgrade to do it",
+      alias,
+      versions.bright_red()
+    ));
+  }
+  allow_downgrade
+}
+
+/// the newest remote version in the range of an alias
+fn newest(versions: &[String], alias: &str, range: VersionReq) -> Result<Version> {
+  let range_text = range.to_string();
+  best_version(versions.iter().map(AsRef::as_ref), range)
+    .ok_or_else(|| anyhow!(t!("No release of deno satisfies `{}` of {}", range_text, alias)))
+}
+
+/// what `exec` would do, resolved against the remote versions without downloading anything
+pub fn plan(meta: &DvmMeta, alias: Option<String>, allow_downgrade: bool) -> Result<Plan> {
+  let versions = remote_versions()?;
+  let mut result = Plan::default();
+  if alias.as_deref() == Some(DVM_VERSION_CANARY) {
+    result.installs.push(plan::install(meta, Some(DVM_VERSION_CANARY))?);
+    return Ok(result);
+  }
+  if let Some(alias) = &alias {
+    if !meta.has_alias(alias) {
+      bail!(t!(
+        "{} is not a valid semver version or tag and will not be upgraded",
+        alias
+      ));
+    }
+  }
+
+  let aliases = match &alias {
+    Some(alias) => vec![alias.clone()],
+    None => upgraded_aliases(meta).into_iter().map(|it| it.name).collect(),
+  };
+  for name in aliases {
+    let current = meta.get_version_mapping(&name);
+    let (latest, remapped) = match meta.resolve_version_req(&name) {
+      // a single exact alias is installed but not remapped, like `exec` does
+      VersionArg::Exact(v) => (v.to_string(), alias.is_none()),
+      VersionArg::Range(r) => (newest(&versions, &name, r)?.to_string(), true),
+    };
+    if current.as_deref() == Some(latest.as_str()) {
+      continue;
+    }
+    // an exact version isn't remapped, it still mustn't go back silently
+    if !may_move(&name, current.as_deref().unwrap_or_default(), &latest, allow_downgrade) {
+      continue;
+    }
+    if !result.installs.iter().any(|it| it.version == latest) {
+      result.installs.push(plan::install(meta, Some(&latest))?);
+    }
+    if remapped {
+      result.mappings.push(MappingChange {
+        alias: name,
+        from: current,
+        to: latest,
+      });
+    }
+  }
+  // upgrading all the aliases brings canary along
+  if alias.is_none() && !result.mappings.is_empty() {
+    result.installs.push(plan::install(meta, Some(DVM_VERSION_CANARY))?);
+  }
+  Ok(result)
+}
+
+///
+/// install a version and make sure it runs before an alias is moved to it,
+/// a broken version that wasn't there before is removed again.
+fn install_verified(meta: &DvmMeta, version: &str) -> Result<()> {
+  let parsed = Version::parse(version)?;
+  let exe_path = deno_version_path(&parsed);
+  let installed = exe_path.exists();
+  install::exec(meta, true, Some(version.to_string()))?;
+  if let Err(err) = check_exe(&exe_path, Some(&parsed)) {
+    if !installed {
+      fs::remove_dir_all(deno_version_dir(&parsed)).ok();
+    }
+    bail!(t!(
+      "deno v{} doesn't work, its alias is left unchanged: {}",
+      version,
+      err
+    ));
+  }
+  Ok(())
+}
+
+/// the mappings before `changes`, those which pointed nowhere can't be restored
+fn replaced(changes: &[(String, String, String)]) -> Vec<VersionMapping> {
+  changes
+    .iter()
+    .filter(|(_, from, _)| from != DVM_VERSION_INVALID)
+    .map(|(alias, from, _)| VersionMapping {
+      required: alias.clone(),
+      current: from.clone(),
+    })
+    .collect()
+}
+
+pub fn exec(meta: &mut DvmMeta, alias: Option<String>, allow_downgrade: bool) -> Result<()> {
   let versions = remote_versions().expect("Fetching version list failed.");
   if let Some(alias) = alias {
     if alias == DVM_VERSION_CANARY {
-      println!("Upgrading {}", alias.bright_black());
+      println!("{}", t!("Upgrading {}", alias.bright_black()));
       install::exec(meta, true, Some(alias)).unwrap();
-      println!("All aliases have been upgraded");
+      println!("{}", t!("All aliases have been upgraded"));
       return Ok(());
     }
 
     if !meta.has_alias(&alias) {
       eprintln!(
-        "{} is not a valid semver version or tag and will not be upgraded",
-        alias.bright_black()
+        "{}",
+        t!(
+          "{} is not a valid semver version or tag and will not be upgraded",
+          alias.bright_black()
+        )
       );
       std::process::exit(1);
     }
-    println!("Upgrading alias {}", alias.bright_black());
+    println!("{}", t!("Upgrading alias {}", alias.bright_black()));
     let current = meta
       .get_version_mapping(alias.as_str())
       .unwrap_or_else(|| DVM_VERSION_INVALID.to_string());
@@ -34,48 +182,88 @@ pub fn exec(meta: &mut DvmMeta, alias: Option<String>) -> Result<()> {
     match version_req {
       VersionArg::Exact(v) => {
         if current == v.to_string() {
-          println!("{} is already the latest version", alias);
+          println!("{}", t!("{} is already the latest version", alias));
           std::process::exit(0);
         } else {
-          install::exec(meta, true, Some(v.to_string())).expect("Install failed");
+          if !may_move(&alias, &current, &v.to_string(), allow_downgrade) {
+            bail!(t!("{} has not been downgraded", alias));
+          }
+          install_verified(meta, &v.to_string())?;
         }
       }
       VersionArg::Range(r) => {
-        let version = best_version(versions.iter().map(AsRef::as_ref), r).unwrap();
-        install::exec(meta, true, Some(version.to_string())).expect("Install failed");
-        meta.set_version_mapping(alias, version.to_string());
+        let version = newest(&versions, &alias, r)?;
+        if !may_move(&alias, &current, &version.to_string(), allow_downgrade) {
+          bail!(t!("{} has not been downgraded", alias));
+        }
+        install_verified(meta, &version.to_string())?;
+        output::upgrade_summary(&[(alias.clone(), current.clone(), version.to_string())]);
+        meta.set_version_mapping(alias.clone(), version.to_string());
+        meta.set_replaced_mappings(replaced(&[(alias, current, version.to_string())]));
       }
     }
   } else {
-    for alias in meta.list_alias() {
+    let mut changes = Vec::new();
+    for alias in upgraded_aliases(meta) {
       let current = meta
         .get_version_mapping(alias.name.as_str())
         .unwrap_or_else(|| DVM_VERSION_INVALID.to_string());
 
       let latest = match VersionArg::from_str(alias.required.clone().as_str()).unwrap() {
         VersionArg::Exact(v) => v.to_string(),
-        VersionArg::Range(v) => best_version(versions.iter().map(AsRef::as_ref), v).unwrap().to_string(),
+        VersionArg::Range(v) => match newest(&versions, &alias.name, v) {
+          Ok(version) => version.to_string(),
+          Err(err) => {
+            output::warn(err);
+            continue;
+          }
+        },
       };
 
-      if current == latest {
+      if current == latest || !may_move(&alias.name, &current, &latest, allow_downgrade) {
         continue;
       }
 
       println!(
-        "Upgrading {} from {} to {}",
-        alias.name.bright_black(),
-        current.bright_red(),
-        latest.clone().bright_green()
+        "{}",
+        t!(
+          "Upgrading {} from {} to {}",
+          alias.name.bright_black(),
+          current.bright_red(),
+          latest.clone().bright_green()
+        )
       );
-      install::exec(meta, true, Some(latest.clone()))?;
+      if let Err(err) = install_verified(meta, &latest) {
+        output::warn(err);
+        continue;
+      }
+      changes.push((alias.name.clone(), current, latest.clone()));
       meta.set_version_mapping(alias.name, latest);
 
-      println!("Upgrading {}", DVM_VERSION_CANARY.bright_black());
+      println!("{}", t!("Upgrading {}", DVM_VERSION_CANARY.bright_black()));
       install::exec(meta, true, Some(DVM_VERSION_CANARY.to_string())).unwrap();
     }
 
-    println!("All aliases have been upgraded");
+    output::upgrade_summary(&changes);
+    if !changes.is_empty() {
+      meta.set_replaced_mappings(replaced(&changes));
+    }
+    println!("{}", t!("All aliases have been upgraded"));
   }
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_downgrade() {
+    assert!(is_downgrade("1.28.0", "1.27.3"));
+    assert!(is_downgrade("1.28.0", "1.28.0-rc.1"));
+    assert!(!is_downgrade("1.28.0", "1.28.1"));
+    assert!(!is_downgrade("1.28.0", "1.28.0"));
+    assert!(!is_downgrade(DVM_VERSION_INVALID, "1.28.0"));
+  }
+}
@@ -1,25 +1,55 @@
-use crate::commands::install;
+use crate::commands::{compact, install, update_pin};
+use crate::config::{Activation, DvmConfig};
 use crate::consts::{DVM_CONFIG_FILENAME, DVM_VERSION_CANARY, DVM_VERSION_LATEST, DVM_VERSION_SYSTEM};
 use crate::deno_bin_path;
+use crate::interrupt;
 use crate::meta::DvmMeta;
-use crate::utils::{best_version, deno_canary_path, deno_version_path, prompt_request, update_stub};
-use crate::utils::{is_exact_version, load_dvmrc};
+use crate::output;
+use crate::policy;
+use crate::project;
+use crate::timings::{self, Phase};
+use crate::utils::{
+  best_version, deno_canary_path, deno_target_path, deno_version_path, external_deno, now, prompt_request,
+  shim_target_path, update_stub,
+};
+use crate::utils::{is_exact_version, load_dvmrc, normalize_version};
 use crate::version::remote_versions;
 use crate::version::{get_latest_version, VersionArg};
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use cfg_if::cfg_if;
 use semver::Version;
 use std::fs;
-use std::path::Path;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// using a tag or a specific version
-pub fn exec(meta: &mut DvmMeta, version: Option<String>, local: bool) -> Result<()> {
+///
+/// using a tag or a specific version,
+/// `save` writes it to the `.dvmrc` of the current directory and records the project in the meta.
+pub fn exec(meta: &mut DvmMeta, version: Option<String>, local: bool, save: bool) -> Result<()> {
+  exec_for(meta, version, local, save, None)
+}
+
+/// `exec` activating the build of `target`, another architecture installed with `dvm install --arch`
+pub fn exec_for(
+  meta: &mut DvmMeta,
+  version: Option<String>,
+  local: bool,
+  save: bool,
+  target: Option<&str>,
+) -> Result<()> {
+  let local = local || save;
+  let version = version.map(|it| normalize_version(&it).to_string());
   let version_req: VersionArg;
   if let Some(ref version) = version {
     if version == &DVM_VERSION_CANARY.to_string() {
+      if target.is_some() {
+        bail!(t!("The canary has no builds for other architectures"));
+      }
+      policy::check_canary()?;
       let canary_path = deno_canary_path();
       if !canary_path.exists() {
-        if prompt_request("deno canary is not installed. do you want to install it?") {
+        if prompt_request(&t!("deno canary is not installed. do you want to install it?")) {
           install::exec(meta, true, Some(DVM_VERSION_CANARY.to_string())).unwrap();
           use_canary_bin_path(local).unwrap();
         } else {
@@ -28,10 +58,15 @@ pub fn exec(meta: &mut DvmMeta, version: Option<String>, local: bool) -> Result<
       }
 
       use_canary_bin_path(local).unwrap();
+      meta.record_activation(DVM_VERSION_CANARY, DVM_VERSION_CANARY);
       return Ok(());
     } else if version == &DVM_VERSION_SYSTEM.to_string() {
-      std::fs::remove_file(deno_bin_path()).unwrap();
-      println!("Deno that was previously installed on your system will be activated now.");
+      remove_bin(&deno_bin_path()).unwrap();
+      meta.record_activation(DVM_VERSION_SYSTEM, DVM_VERSION_SYSTEM);
+      println!(
+        "{}",
+        t!("Deno that was previously installed on your system will be activated now.")
+      );
       return Ok(());
     }
 
@@ -42,37 +77,59 @@ pub fn exec(meta: &mut DvmMeta, version: Option<String>, local: bool) -> Result<
     } else {
       // dvm will reject for using semver range directly now.
       eprintln!(
-        "`{}` is not a valid semver version or tag and will not be used\ntype `dvm help` for more info",
-        version
+        "{}",
+        t!(
+          "`{}` is not a valid semver version or tag and will not be used\ntype `dvm help` for more info",
+          version
+        )
       );
+      output::suggest(version, &meta.version_names());
       std::process::exit(1);
     }
   } else {
-    println!("No version input detect, try to use version in .dvmrc file");
+    output::info(t!("No version input detect, try to use version in .dvmrc file"));
+    // a channel without a version line yet is resolved once, later `dvm update-pin` advances it
+    if update_pin::pin_unresolved(&meta.registry())?.as_deref() == Some(DVM_VERSION_CANARY) {
+      return exec_for(meta, Some(DVM_VERSION_CANARY.to_string()), true, false, target);
+    }
     version_req = load_dvmrc();
-    println!("Using semver range: {}", version_req);
+    output::info(t!("Using semver range: {}", version_req));
   }
 
   let used_version = if version_req.to_string() == "*" {
-    println!("Checking for latest version");
-    let version = get_latest_version(&meta.registry).expect("Get latest version failed");
-    println!("The latest version is v{}", version);
+    output::info(t!("Checking for latest version"));
+    let version = get_latest_version(&meta.registry()).expect("Get latest version failed");
+    output::info(t!("The latest version is v{}", version));
     version
   } else {
     match version_req {
       VersionArg::Exact(ref v) => v.clone(),
       VersionArg::Range(ref r) => {
-        println!("Fetching version list");
+        output::info(t!("Fetching version list"));
         let versions = remote_versions().expect("Fetching version list failed.");
         best_version(versions.iter().map(AsRef::as_ref), r.clone()).unwrap()
       }
     }
   };
 
-  let new_exe_path = deno_version_path(&used_version);
+  policy::check(&used_version)?;
+  compact::restore(&used_version)?;
+  let new_exe_path = match target {
+    Some(target) => deno_target_path(&used_version, target),
+    None => deno_version_path(&used_version),
+  };
 
   if !new_exe_path.exists() {
-    if prompt_request(format!("deno v{} is not installed. do you want to install it?", used_version).as_str()) {
+    if let Some(target) = target {
+      if !prompt_request(&t!(
+        "deno v{} for {} is not installed. do you want to install it?",
+        used_version,
+        target
+      )) {
+        std::process::exit(1);
+      }
+      install::install_arch(meta, true, Some(used_version.to_string()), target)?;
+    } else if prompt_request(t!("deno v{} is not installed. do you want to install it?", used_version).as_str()) {
       install::exec(meta, true, Some(used_version.to_string())).unwrap();
       let temp = version_req.to_string();
       let version = version.as_ref().unwrap_or(&temp);
@@ -84,75 +141,307 @@ pub fn exec(meta: &mut DvmMeta, version: Option<String>, local: bool) -> Result<
     }
   }
 
-  use_this_bin_path(
-    &new_exe_path,
-    &used_version,
-    version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string()),
-    local,
-  )?;
+  // a corrupt or mismatched binary is offered to be reinstalled instead of activated
+  if let Err(err) = check_exe(&new_exe_path, Some(&used_version)) {
+    output::warn(t!("deno v{} is broken: {}", used_version, err));
+    if !prompt_request(&t!("do you want to reinstall deno v{}?", used_version)) {
+      bail!(t!("deno v{} has not been activated", used_version));
+    }
+    fs::remove_file(&new_exe_path)?;
+    match target {
+      Some(target) => install::install_arch(meta, true, Some(used_version.to_string()), target).map(|_| ())?,
+      None => install::exec(meta, true, Some(used_version.to_string()))?,
+    }
+    check_exe(&new_exe_path, Some(&used_version))?;
+  }
+
+  let raw_version = version.unwrap_or_else(|| DVM_VERSION_LATEST.to_string());
+  use_this_bin_path(&new_exe_path, &used_version, raw_version.clone(), local)?;
   update_stub(used_version.to_string().as_str());
+  meta.record_activation(&used_version.to_string(), &raw_version);
+  if save {
+    let dir = std::env::current_dir()?;
+    meta.set_project_pin(dir.to_string_lossy().to_string(), used_version.to_string());
+    output::info(t!("Saved deno {} as the version of {}", used_version, dir.display()));
+  }
   Ok(())
 }
 
 pub fn use_canary_bin_path(local: bool) -> Result<()> {
+  let _timer = timings::start(Phase::Activate);
   let canary_dir = deno_canary_path();
 
   if !canary_dir.exists() {
-    eprintln!("Canary dir not found, will not be used");
+    eprintln!("{}", t!("Canary dir not found, will not be used"));
     std::process::exit(1);
   }
 
-  let bin_path = deno_bin_path();
-  if !bin_path.parent().unwrap().exists() {
-    fs::create_dir_all(bin_path.parent().unwrap()).unwrap();
-  }
-  if bin_path.exists() {
-    fs::remove_file(&bin_path)?;
-  }
-  fs::hard_link(&canary_dir, &bin_path)?;
+  activate_bin(&canary_dir, None)?;
 
   if local {
-    println!("Writing to current folder config");
-    fs::write(std::path::Path::new(DVM_CONFIG_FILENAME), DVM_VERSION_CANARY)?;
+    output::info(t!("Writing to current folder config"));
+    project::save_version(Path::new(DVM_CONFIG_FILENAME), DVM_VERSION_CANARY)?;
   } else {
-    println!("Writing to home folder config");
-    fs::write(dirs::home_dir().unwrap().join(DVM_CONFIG_FILENAME), DVM_VERSION_CANARY)?;
+    output::info(t!("Writing to home folder config"));
+    project::save_version(&dirs::home_dir().unwrap().join(DVM_CONFIG_FILENAME), DVM_VERSION_CANARY)?;
   }
 
-  println!("Now using deno canary");
+  output::info(t!("Now using deno canary"));
+  warn_external_deno();
   Ok(())
 }
 
 pub fn use_this_bin_path(exe_path: &Path, version: &Version, raw_version: String, local: bool) -> Result<()> {
-  check_exe(exe_path, version)?;
+  let _timer = timings::start(Phase::Activate);
+  activate_bin(exe_path, Some(version))?;
 
+  if local {
+    output::info(t!("Writing to current folder config"));
+    project::save_version(Path::new(DVM_CONFIG_FILENAME), &raw_version)?;
+  } else {
+    output::info(t!("Writing to home folder config"));
+    project::save_version(&dirs::home_dir().unwrap().join(DVM_CONFIG_FILENAME), &raw_version)?;
+  }
+  output::info(t!("Now using deno {}", version));
+  warn_external_deno();
+  Ok(())
+}
+
+/// another deno first in PATH makes `dvm use` look like it had no effect
+fn warn_external_deno() {
+  if let Some(external) = external_deno() {
+    output::warn(t!(
+      "{} is not managed by dvm and comes first in PATH, run `dvm doctor --force-external` to put dvm's deno before it",
+      external.display()
+    ));
+  }
+}
+
+///
+/// a running `deno.exe` can't be removed on windows, but it can be renamed out of the way,
+/// the `.old.exe` left behind is removed by `remove_stale_bins` once it's unlocked.
+pub fn remove_bin(bin_path: &Path) -> Result<()> {
+  match fs::remove_file(bin_path) {
+    Err(err) if cfg!(windows) && err.kind() == ErrorKind::PermissionDenied => {
+      fs::rename(bin_path, bin_path.with_extension(format!("{}.old.exe", now())))?;
+      Ok(())
+    }
+    result => Ok(result?),
+  }
+}
+
+/// remove the executables renamed by `remove_bin`, the ones still running are kept for later
+pub fn remove_stale_bins() {
   let bin_path = deno_bin_path();
+  let Ok(dir) = bin_path.parent().unwrap().read_dir() else {
+    return;
+  };
+  for entry in dir.flatten() {
+    if entry.file_name().to_string_lossy().ends_with(".old.exe") {
+      fs::remove_file(entry.path()).ok();
+    }
+  }
+}
+
+/// an executable moved aside while its replacement is verified
+pub struct Backup {
+  path: PathBuf,
+  backup: Option<PathBuf>,
+}
+
+impl Backup {
+  /// move `path` out of the way, nothing is kept if there's nothing there
+  pub fn take(path: &Path) -> Result<Self> {
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    // `exists` is false for a dangling symlink
+    if path.symlink_metadata().is_err() {
+      return Ok(Backup {
+        path: path.to_path_buf(),
+        backup: None,
+      });
+    }
+    if backup.symlink_metadata().is_ok() {
+      fs::remove_file(&backup)?;
+    }
+    fs::rename(path, &backup)?;
+    Ok(Backup {
+      path: path.to_path_buf(),
+      backup: Some(backup),
+    })
+  }
+
+  /// put the previous executable back in place of its replacement
+  pub fn restore(self) -> Result<()> {
+    if self.path.symlink_metadata().is_ok() {
+      remove_bin(&self.path)?;
+    }
+    if let Some(backup) = &self.backup {
+      fs::rename(backup, &self.path)?;
+    }
+    Ok(())
+  }
+
+  /// the replacement works, the previous executable is not needed anymore
+  pub fn discard(self) {
+    if let Some(backup) = &self.backup {
+      remove_bin(backup).ok();
+    }
+  }
+}
+
+///
+/// replace `path` through `replace` and check the result runs with `check_exe`,
+/// the previous executable is restored if either fails.
+pub fn replace_verified(path: &Path, version: Option<&Version>, replace: impl FnOnce() -> Result<()>) -> Result<()> {
+  interrupt::critical(|| {
+    let backup = Backup::take(path)?;
+    match replace().and_then(|()| check_exe(path, version)) {
+      Ok(()) => {
+        backup.discard();
+        Ok(())
+      }
+      Err(err) => {
+        let restored = backup.backup.is_some();
+        backup.restore()?;
+        if restored {
+          bail!(t!("{}, the previous deno has been restored", err));
+        }
+        Err(err)
+      }
+    }
+  })
+}
+
+/// link the executable into the bin directory, the previous one stays if the new one doesn't run
+fn activate_bin(exe_path: &Path, version: Option<&Version>) -> Result<()> {
+  replace_verified(&deno_bin_path(), version, || link_bin(exe_path))
+}
+
+/// put the executable into the dvm bin directory with the configured activation
+pub fn link_bin(exe_path: &Path) -> Result<()> {
+  let bin_path = &deno_bin_path();
   if !bin_path.parent().unwrap().exists() {
     fs::create_dir_all(bin_path.parent().unwrap()).unwrap();
   }
-  if bin_path.exists() {
-    fs::remove_file(&bin_path)?;
+  // `exists` is false for a dangling symlink
+  if bin_path.symlink_metadata().is_ok() {
+    remove_bin(bin_path)?;
   }
-  fs::hard_link(exe_path, &bin_path)?;
 
-  if local {
-    println!("Writing to current folder config");
-    fs::write(std::path::Path::new(DVM_CONFIG_FILENAME), raw_version)?;
-  } else {
-    println!("Writing to home folder config");
-    fs::write(dirs::home_dir().unwrap().join(DVM_CONFIG_FILENAME), raw_version)?;
+  match DvmConfig::load().activation() {
+    Activation::Hardlink => {
+      // hard links can't cross file systems, nor point to files of other users
+      // when the versions store is shared, fallback to a copy then.
+      if fs::hard_link(exe_path, bin_path).is_err() {
+        fs::copy(exe_path, bin_path)?;
+      }
+    }
+    Activation::Symlink => {
+      cfg_if! {
+        if #[cfg(windows)] {
+          std::os::windows::fs::symlink_file(exe_path, bin_path)?
+        } else {
+          std::os::unix::fs::symlink(exe_path, bin_path)?
+        }
+      }
+    }
+    Activation::Copy => {
+      fs::copy(exe_path, bin_path)?;
+    }
+    Activation::Shim => {
+      // the target is known before the shim runs, `check_exe` runs it right away
+      fs::write(shim_target_path(), exe_path.to_string_lossy().as_bytes())?;
+      let dvm = std::env::current_exe()?;
+      if fs::hard_link(&dvm, bin_path).is_err() {
+        fs::copy(&dvm, bin_path)?;
+      }
+      return Ok(());
+    }
   }
-  println!("Now using deno {}", version);
+  fs::remove_file(shim_target_path()).ok();
   Ok(())
 }
 
-fn check_exe(exe_path: &Path, expected_version: &Version) -> Result<()> {
+/// whether `deno -V` output is of the expected version, builds of forks tagged like `1.43.5-custom.1`
+/// report the upstream version they were forked from, deno's own prereleases are `rc`, `alpha` or `beta`.
+fn reports_version(stdout: &str, expected: &Version) -> bool {
+  if stdout == format!("deno {}", expected) {
+    return true;
+  }
+  let upstream_pre = ["rc", "alpha", "beta"]
+    .iter()
+    .any(|it| expected.pre.as_str().starts_with(it));
+  !expected.pre.is_empty()
+    && !upstream_pre
+    && stdout == format!("deno {}.{}.{}", expected.major, expected.minor, expected.patch)
+}
+
+/// run `deno -V`, the version it reports has to be `expected_version` if there's one
+pub fn check_exe(exe_path: &Path, expected_version: Option<&Version>) -> Result<()> {
   let output = Command::new(exe_path)
     .arg("-V")
     .stderr(std::process::Stdio::inherit())
-    .output()?;
-  let stdout = String::from_utf8(output.stdout)?;
-  assert!(output.status.success());
-  assert_eq!(stdout.trim(), format!("deno {}", expected_version));
+    .output()
+    .map_err(|err| anyhow!(t!("{} can't be run: {}", exe_path.display(), err)))?;
+  if !output.status.success() {
+    bail!(t!("{} exited with {}", exe_path.display(), output.status));
+  }
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  if let Some(expected) = expected_version {
+    if !reports_version(stdout.trim(), expected) {
+      bail!(t!(
+        "{} reports `{}` instead of deno {}",
+        exe_path.display(),
+        stdout.trim(),
+        expected
+      ));
+    }
+  }
   Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use std::os::unix::fs::PermissionsExt;
+
+  fn script(path: &Path, body: &str) -> Result<()> {
+    fs::write(path, format!("#!/bin/sh\n{}\n", body))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_reports_version() {
+    let version = |it: &str| Version::parse(it).unwrap();
+    assert!(reports_version("deno 1.43.5", &version("1.43.5")));
+    assert!(reports_version("deno 1.43.5", &version("1.43.5-custom.1")));
+    assert!(!reports_version("deno 2.0.0", &version("2.0.0-rc.1")));
+    assert!(!reports_version("deno 1.43.4", &version("1.43.5-custom.1")));
+  }
+
+  #[test]
+  fn test_replace_verified() {
+    let dir = std::env::temp_dir().join(format!("dvm-replace-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let bin = dir.join("deno");
+    let version = Version::parse("1.28.0").unwrap();
+    script(&bin, "echo deno 1.28.0").unwrap();
+
+    let broken = replace_verified(&bin, Some(&version), || script(&bin, "exit 3"));
+    assert!(broken.is_err());
+    assert!(check_exe(&bin, Some(&version)).is_ok());
+
+    let other = replace_verified(&bin, Some(&version), || script(&bin, "echo deno 1.29.0"));
+    assert!(other.is_err());
+    assert!(check_exe(&bin, Some(&version)).is_ok());
+
+    let upgraded = Version::parse("1.29.0").unwrap();
+    replace_verified(&bin, Some(&upgraded), || script(&bin, "echo deno 1.29.0")).unwrap();
+    assert!(check_exe(&bin, Some(&upgraded)).is_ok());
+    assert!(!dir.join("deno.bak").exists());
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
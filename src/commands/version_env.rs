@@ -0,0 +1,26 @@
+use crate::cli::VersionEnvCommands;
+use crate::version::version_req_parse;
+use crate::DvmMeta;
+
+use anyhow::Result;
+use colored::Colorize;
+
+pub fn exec(meta: &mut DvmMeta, command: VersionEnvCommands) -> Result<()> {
+  match command {
+    VersionEnvCommands::Set { required, name, value } => {
+      version_req_parse(required.as_str());
+      meta.set_version_env(required, name, value);
+      Ok(())
+    }
+    VersionEnvCommands::Unset { required, name } => {
+      meta.delete_version_env(&required, &name);
+      Ok(())
+    }
+    VersionEnvCommands::List => {
+      for env in &meta.env {
+        println!("{} {}={}", env.required.bright_black(), env.name, env.value);
+      }
+      Ok(())
+    }
+  }
+}
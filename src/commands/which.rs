@@ -0,0 +1,48 @@
+use crate::commands::compact::restore;
+use crate::consts::DVM_VERSION_CANARY;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{deno_canary_path, deno_version_path, denort_version_path};
+use anyhow::{bail, Result};
+
+/// print the path of an installed deno, or of its denort runtime
+pub fn exec(meta: &DvmMeta, denort: bool, version: String) -> Result<()> {
+  let path = if version == DVM_VERSION_CANARY {
+    if denort {
+      bail!(t!("denort is not available for canary"));
+    }
+    deno_canary_path()
+  } else {
+    let Some(resolved) = meta.resolve_local_version(&version) else {
+      bail!(output::with_suggestion(
+        t!("no installed version matches `{}`", version),
+        &version,
+        &meta.version_names()
+      ));
+    };
+    restore(&resolved)?;
+    if denort {
+      denort_version_path(&resolved)
+    } else {
+      deno_version_path(&resolved)
+    }
+  };
+
+  if !path.exists() {
+    if denort {
+      bail!(t!(
+        "denort of `{}` is not installed, run `dvm install --denort {}`",
+        version,
+        version
+      ));
+    }
+    bail!(output::with_suggestion(
+      t!("deno `{}` is not installed", version),
+      &version,
+      &meta.version_names()
+    ));
+  }
+
+  println!("{}", path.display());
+  Ok(())
+}
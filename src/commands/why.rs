@@ -0,0 +1,76 @@
+use crate::commands::current;
+use crate::commands::rehash::active_version;
+use crate::meta::DvmMeta;
+use crate::output;
+use crate::utils::{deno_version_dir, format_timestamp};
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+///
+/// everything on this machine that needs a version: the aliases and mappings resolving to it,
+/// the linked projects pinning it, and whether it's the default or the one active here.
+fn reasons(meta: &DvmMeta, version: &str) -> Vec<String> {
+  let resolves_to = |required: &str| {
+    meta
+      .resolve_local_version(required)
+      .map(|it| it.to_string() == version)
+      .unwrap_or(false)
+  };
+  let mut reasons = Vec::new();
+  // the predefined aliases follow whatever is installed, they don't need any version
+  for alias in &meta.alias {
+    if resolves_to(&alias.name) {
+      reasons.push(t!("alias {} ({}) points at it", alias.name, alias.required));
+    }
+  }
+  for mapping in meta.versions.iter().filter(|it| it.current == version) {
+    reasons.push(t!("mapped from {}", mapping.required));
+  }
+  for project in meta.projects.iter().filter(|it| it.version == version && it.exists()) {
+    reasons.push(t!("pinned by the project {}", project.dir));
+  }
+  if active_version().map(|it| resolves_to(&it)).unwrap_or(false) {
+    reasons.push(t!("it's the default version"));
+  }
+  if let Some((_, source)) = current::resolve(meta).filter(|(it, _)| it == version) {
+    reasons.push(t!("it's active here, from {}", source));
+  }
+  reasons
+}
+
+/// explain why an installed version is on disk, so it's known whether uninstalling it is safe
+pub fn exec(meta: &mut DvmMeta, version: String) -> Result<()> {
+  let Some(resolved) = meta.resolve_local_version(&version) else {
+    bail!(output::with_suggestion(
+      t!("no installed version matches `{}`", version),
+      &version,
+      &meta.version_names()
+    ));
+  };
+  if !deno_version_dir(&resolved).exists() {
+    bail!(output::with_suggestion(
+      t!("deno `{}` is not installed", version),
+      &version,
+      &meta.version_names()
+    ));
+  }
+
+  // what the linked projects pin now, not when they were linked
+  meta.resolve_project_pins();
+  let name = resolved.to_string();
+  let reasons = reasons(meta, &name);
+  let last_used = meta
+    .last_used_time(&name)
+    .map(format_timestamp)
+    .unwrap_or_else(|| t!("never"));
+  println!("deno v{} ({})", name, t!("last used {}", last_used).bright_black());
+  if reasons.is_empty() {
+    output::success(t!("Nothing needs deno v{}, it's safe to uninstall", name));
+    return Ok(());
+  }
+  for reason in reasons {
+    println!("  - {}", reason);
+  }
+  output::warn(t!("Uninstalling deno v{} breaks the above", name));
+  Ok(())
+}
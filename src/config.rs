@@ -0,0 +1,584 @@
+use crate::consts::{
+  DVM_CACHE_RETENTION_DAYS, DVM_CONFIG_TOML_FILENAME, REGISTRY_CN, REGISTRY_NAME_CN, REGISTRY_NAME_OFFICIAL,
+  REGISTRY_OFFICIAL,
+};
+use crate::http::parse_rate;
+use crate::utils::dvm_config_root;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use toml::value::{Table, Value};
+
+/// the config as loaded by this process
+static LOADED: Mutex<Option<DvmConfig>> = Mutex::new(None);
+
+/// all keys that can be set in `config.toml`
+pub const CONFIG_KEYS: &[&str] = &[
+  "activation",
+  "auto-clean",
+  "auto-clean-unused-days",
+  "delta-source",
+  "deno-version-precedence",
+  "denort",
+  "download-url-template",
+  "isolate-deno-dir",
+  "limit-rate",
+  "lts-line",
+  "policy-url",
+  "prefetch-latest",
+  "proxy",
+  "registry",
+  "registry-basic-auth",
+  "registry-token",
+  "require-signature",
+  "retention-days",
+  "self-update-channel",
+  "signature-public-key",
+  "system-dir",
+  "telemetry",
+  "telemetry-url",
+];
+
+/// how the selected deno executable is put into the dvm bin directory
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+  #[default]
+  Hardlink,
+  Symlink,
+  Copy,
+  /// a copy of dvm that runs the selected executable through `dvm exec`, which stops `deno upgrade`
+  /// from overwriting it in the versions store, `dvm rehash` refreshes it after a `dvm self-update`
+  Shim,
+}
+
+/// which dvm builds `dvm self-update` follows
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelfUpdateChannel {
+  #[default]
+  Stable,
+  /// built from the main branch every night
+  Nightly,
+}
+
+/// what happens to the versions found unused by the daily auto clean
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoClean {
+  /// ask before removing them, only in an interactive terminal
+  #[default]
+  Prompt,
+  /// remove them without asking
+  Remove,
+}
+
+/// whether `DVM_DENO_VERSION` or the project `.dvmrc` wins when both pick a version
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionPrecedence {
+  #[default]
+  Project,
+  Env,
+}
+
+///
+/// where release assets are downloaded from instead of the registry, e.g. `https://mirror.corp/deno/{version}/{archive}`,
+/// `{version}`, `{target}` and `{archive}` are filled in.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UrlTemplate(pub String);
+
+impl TryFrom<String> for UrlTemplate {
+  type Error = String;
+
+  fn try_from(raw: String) -> Result<Self, Self::Error> {
+    if !raw.starts_with("http://") && !raw.starts_with("https://") {
+      return Err(format!("`{}` should start with `http://` or `https://`", raw));
+    }
+    if !raw.contains("{archive}") {
+      return Err(format!("`{}` has no `{{archive}}` placeholder", raw));
+    }
+    Ok(UrlTemplate(raw))
+  }
+}
+
+impl From<UrlTemplate> for String {
+  fn from(template: UrlTemplate) -> Self {
+    template.0
+  }
+}
+
+/// the minor line deno maintains as LTS, a version range
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LtsLine(pub String);
+
+impl TryFrom<String> for LtsLine {
+  type Error = String;
+
+  fn try_from(raw: String) -> Result<Self, Self::Error> {
+    semver::VersionReq::parse(&raw).map_err(|_| format!("`{}` is no version range", raw))?;
+    Ok(LtsLine(raw))
+  }
+}
+
+impl From<LtsLine> for String {
+  fn from(line: LtsLine) -> Self {
+    line.0
+  }
+}
+
+/// the registry releases are downloaded from, `official`, `cn` or its url
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Registry(pub String);
+
+impl TryFrom<String> for Registry {
+  type Error = String;
+
+  fn try_from(raw: String) -> Result<Self, Self::Error> {
+    match raw.as_str() {
+      REGISTRY_NAME_OFFICIAL => Ok(Registry(REGISTRY_OFFICIAL.to_string())),
+      REGISTRY_NAME_CN => Ok(Registry(REGISTRY_CN.to_string())),
+      it if it.starts_with("http://") || it.starts_with("https://") => Ok(Registry(raw)),
+      _ => Err(format!(
+        "`{}` should be `official`, `cn` or start with `http://` or `https://`",
+        raw
+      )),
+    }
+  }
+}
+
+impl From<Registry> for String {
+  fn from(registry: Registry) -> Self {
+    registry.0
+  }
+}
+
+/// the proxy all requests go through, e.g. `http://proxy.corp:3128`
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProxyUrl(pub String);
+
+impl TryFrom<String> for ProxyUrl {
+  type Error = String;
+
+  fn try_from(raw: String) -> Result<Self, Self::Error> {
+    if !raw.starts_with("http://") && !raw.starts_with("https://") {
+      return Err(format!("`{}` should start with `http://` or `https://`", raw));
+    }
+    reqwest::Url::parse(&raw).map_err(|err| format!("`{}` is not a valid url: {}", raw, err))?;
+    Ok(ProxyUrl(raw))
+  }
+}
+
+impl From<ProxyUrl> for String {
+  fn from(proxy: ProxyUrl) -> Self {
+    proxy.0
+  }
+}
+
+impl UrlTemplate {
+  pub fn render(&self, version: &str, target: &str, archive: &str) -> String {
+    self
+      .0
+      .replace("{version}", version)
+      .replace("{target}", target)
+      .replace("{archive}", archive)
+  }
+}
+
+///
+/// where binary deltas between versions are downloaded from, e.g. `https://deltas.corp/deno`,
+/// the delta from an installed `<from>` to `<version>` is at `<source>/v<version>/deno-<target>.from-v<from>.zst`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DeltaSource(pub String);
+
+impl TryFrom<String> for DeltaSource {
+  type Error = String;
+
+  fn try_from(raw: String) -> Result<Self, Self::Error> {
+    if !raw.starts_with("http://") && !raw.starts_with("https://") {
+      return Err(format!("`{}` should start with `http://` or `https://`", raw));
+    }
+    Ok(DeltaSource(raw))
+  }
+}
+
+impl From<DeltaSource> for String {
+  fn from(source: DeltaSource) -> Self {
+    source.0
+  }
+}
+
+impl DeltaSource {
+  /// the `sha256sum` of the executable a delta rebuilds, published next to the deltas
+  pub fn digest_url(&self, version: &str, target: &str) -> String {
+    format!(
+      "{}/v{}/deno-{}.sha256sum",
+      self.0.trim_end_matches('/'),
+      version,
+      target
+    )
+  }
+
+  pub fn url(&self, from: &str, version: &str, target: &str) -> String {
+    format!(
+      "{}/v{}/deno-{}.from-v{}.zst",
+      self.0.trim_end_matches('/'),
+      version,
+      target,
+      from
+    )
+  }
+}
+
+/// a download rate in bytes per second, given as bytes or with a suffix like `2M`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "RawRate")]
+pub struct Rate(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRate {
+  Bytes(u64),
+  Human(String),
+}
+
+impl TryFrom<RawRate> for Rate {
+  type Error = String;
+
+  fn try_from(raw: RawRate) -> Result<Self, Self::Error> {
+    match raw {
+      RawRate::Bytes(rate) => parse_rate(&rate.to_string()).map(Rate),
+      RawRate::Human(rate) => parse_rate(&rate).map(Rate),
+    }
+  }
+}
+
+/// config keys whose values are secrets, they're never printed
+pub const SECRET_KEYS: &[&str] = &["registry-basic-auth", "registry-token"];
+
+///
+/// user config stored in `config.toml` of the dvm config root,
+/// every key is optional and falls back to dvm's default behavior.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DvmConfig {
+  /// how `dvm use` activates a version
+  pub activation: Option<Activation>,
+  /// whether the auto clean asks before removing the unused versions
+  pub auto_clean: Option<AutoClean>,
+  /// days a version may go unused before the auto clean removes it, off unless set
+  pub auto_clean_unused_days: Option<u64>,
+  /// patch releases are patched from an installed version with deltas from here, when there are
+  pub delta_source: Option<DeltaSource>,
+  /// `env` lets `DVM_DENO_VERSION` override the project `.dvmrc`
+  pub deno_version_precedence: Option<VersionPrecedence>,
+  /// also download the `denort` runtime when installing a version
+  pub denort: Option<bool>,
+  /// release assets are downloaded from this template instead of the registry
+  pub download_url_template: Option<UrlTemplate>,
+  /// give each version its own `DENO_DIR` when deno is launched through dvm
+  pub isolate_deno_dir: Option<bool>,
+  /// throttle downloads to this many bytes per second
+  pub limit_rate: Option<Rate>,
+  /// the range `lts` resolves to, e.g. `~2.4`, when deno has announced a line newer than dvm knows of
+  pub lts_line: Option<LtsLine>,
+  /// the org policy restricting which versions can be installed and used
+  pub policy_url: Option<String>,
+  /// download a newer stable in the background once a day, without installing it
+  pub prefetch_latest: Option<bool>,
+  /// requests go through this proxy unless `HTTPS_PROXY`, `HTTP_PROXY` or `ALL_PROXY` is set, `NO_PROXY` is honored
+  pub proxy: Option<ProxyUrl>,
+  /// the registry releases are downloaded from, it takes precedence over the one set by `dvm registry`
+  pub registry: Option<Registry>,
+  /// `user:password` sent to the registry, `DVM_REGISTRY_BASIC_AUTH` takes precedence
+  pub registry_basic_auth: Option<String>,
+  /// a bearer token sent to the registry, `DVM_REGISTRY_TOKEN` takes precedence
+  pub registry_token: Option<String>,
+  /// refuse archives which can't be verified with `signature-public-key`
+  pub require_signature: Option<bool>,
+  /// days an unused version is kept before `dvm clean` removes it
+  pub retention_days: Option<u64>,
+  /// `stable` or `nightly`, set by `dvm self-update --channel`
+  pub self_update_channel: Option<SelfUpdateChannel>,
+  /// the minisign public key release archives are verified with
+  pub signature_public_key: Option<String>,
+  /// the versions store shared by all users
+  pub system_dir: Option<PathBuf>,
+  /// report anonymous usage, off unless set
+  pub telemetry: Option<bool>,
+  /// where the usage reports are sent, nothing is sent without it
+  pub telemetry_url: Option<String>,
+}
+
+impl DvmConfig {
+  pub fn path() -> PathBuf {
+    dvm_config_root().join(DVM_CONFIG_TOML_FILENAME)
+  }
+
+  ///
+  /// load from disk, the default config is used if it's absent or invalid,
+  /// it's read once a process as the path helpers ask for it all the time, `set` and `unset` read it again.
+  pub fn load() -> Self {
+    let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+    loaded
+      .get_or_insert_with(|| match read_table().and_then(from_table) {
+        Ok(config) => config,
+        Err(err) => {
+          eprintln!("{}", t!("Ignoring invalid {}: {}", DvmConfig::path().display(), err));
+          DvmConfig::default()
+        }
+      })
+      .clone()
+  }
+
+  pub fn activation(&self) -> Activation {
+    self.activation.unwrap_or_default()
+  }
+
+  pub fn auto_clean(&self) -> AutoClean {
+    self.auto_clean.unwrap_or_default()
+  }
+
+  pub fn deno_version_precedence(&self) -> VersionPrecedence {
+    self.deno_version_precedence.unwrap_or_default()
+  }
+
+  pub fn denort(&self) -> bool {
+    self.denort.unwrap_or(false)
+  }
+
+  pub fn limit_rate(&self) -> Option<u64> {
+    self.limit_rate.map(|it| it.0)
+  }
+
+  pub fn prefetch_latest(&self) -> bool {
+    self.prefetch_latest.unwrap_or(false)
+  }
+
+  pub fn require_signature(&self) -> bool {
+    self.require_signature.unwrap_or(false)
+  }
+
+  pub fn self_update_channel(&self) -> SelfUpdateChannel {
+    self.self_update_channel.unwrap_or_default()
+  }
+
+  pub fn telemetry(&self) -> bool {
+    self.telemetry.unwrap_or(false)
+  }
+
+  pub fn isolate_deno_dir(&self) -> bool {
+    self.isolate_deno_dir.unwrap_or(false)
+  }
+
+  /// retention in milliseconds, to compare with `utils::now()`
+  pub fn retention_millis(&self) -> u128 {
+    self.retention_days.unwrap_or(DVM_CACHE_RETENTION_DAYS) as u128 * 24 * 60 * 60 * 1000
+  }
+}
+
+fn read_table() -> Result<Table> {
+  let path = DvmConfig::path();
+  if !path.exists() {
+    return Ok(Table::new());
+  }
+  Ok(toml::from_str(&read_to_string(path)?)?)
+}
+
+fn write_table(table: &Table) -> Result<()> {
+  let path = DvmConfig::path();
+  create_dir_all(path.parent().unwrap())?;
+  write(path, toml::to_string(table)?)?;
+  *LOADED.lock().unwrap_or_else(PoisonError::into_inner) = None;
+  Ok(())
+}
+
+fn from_table(table: Table) -> Result<DvmConfig> {
+  Ok(Value::Table(table).try_into()?)
+}
+
+fn check_key(key: &str) -> Result<()> {
+  if !CONFIG_KEYS.contains(&key) {
+    bail!(
+      "unknown config key `{}`, available keys: {}",
+      key,
+      CONFIG_KEYS.join(", ")
+    );
+  }
+  Ok(())
+}
+
+/// parse a value from the command line, falls back to a string
+fn parse_value(raw: &str) -> Value {
+  raw
+    .parse::<bool>()
+    .map(Value::Boolean)
+    .or_else(|_| raw.parse::<i64>().map(Value::Integer))
+    .unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+pub fn get(key: &str) -> Result<Option<Value>> {
+  check_key(key)?;
+  Ok(read_table()?.remove(key))
+}
+
+/// set a key, the value is validated before written to disk
+pub fn set(key: &str, raw: &str) -> Result<()> {
+  check_key(key)?;
+  let mut table = read_table()?;
+  table.insert(key.to_string(), parse_value(raw));
+  from_table(table.clone()).map_err(|err| anyhow!("invalid value `{}` for `{}`: {}", raw, key, err))?;
+  write_table(&table)
+}
+
+pub fn unset(key: &str) -> Result<()> {
+  check_key(key)?;
+  let mut table = read_table()?;
+  table.remove(key);
+  write_table(&table)
+}
+
+pub fn list() -> Result<Table> {
+  read_table()
+}
+
+/// the config as toml to distribute to other machines, secrets are left out unless asked for
+pub fn export(include_secrets: bool) -> Result<String> {
+  let mut table = read_table()?;
+  if !include_secrets {
+    for key in SECRET_KEYS {
+      table.remove(*key);
+    }
+  }
+  Ok(toml::to_string(&table)?)
+}
+
+/// a key an import changes, with its value before, `None` if it wasn't set
+#[derive(Debug, PartialEq)]
+pub struct Change {
+  pub key: String,
+  pub old: Option<Value>,
+  pub new: Value,
+}
+
+/// the keys of `imported` whose values differ from `current`
+fn diff(current: &Table, imported: &Table) -> Vec<Change> {
+  imported
+    .iter()
+    .filter(|(key, value)| current.get(*key) != Some(*value))
+    .map(|(key, value)| Change {
+      key: key.clone(),
+      old: current.get(key).cloned(),
+      new: value.clone(),
+    })
+    .collect()
+}
+
+///
+/// the changes importing a config would make, it's validated like `config set` validates a key,
+/// the keys it doesn't set are kept. nothing is written unless `apply`.
+pub fn import(text: &str, apply: bool) -> Result<Vec<Change>> {
+  let imported: Table = toml::from_str(text)?;
+  from_table(imported.clone()).map_err(|err| anyhow!("invalid config: {}", err))?;
+  let mut table = read_table()?;
+  let changes = diff(&table, &imported);
+  if apply && !changes.is_empty() {
+    table.extend(imported);
+    from_table(table.clone())?;
+    write_table(&table)?;
+  }
+  Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_diff() {
+    let current: Table = toml::from_str("retention-days = 7\nregistry-token = \"a\"").unwrap();
+    let imported: Table = toml::from_str("retention-days = 30\nregistry-token = \"a\"\ndenort = true").unwrap();
+    assert_eq!(
+      diff(&current, &imported),
+      vec![
+        Change {
+          key: "denort".to_string(),
+          old: None,
+          new: Value::Boolean(true),
+        },
+        Change {
+          key: "retention-days".to_string(),
+          old: Some(Value::Integer(7)),
+          new: Value::Integer(30),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_config() {
+    let config: DvmConfig = toml::from_str("activation = \"symlink\"\nretention-days = 30\n").unwrap();
+    assert_eq!(config.activation(), Activation::Symlink);
+    assert!(!config.isolate_deno_dir());
+    assert_eq!(config.retention_millis(), 30 * 24 * 60 * 60 * 1000);
+    let config: DvmConfig = toml::from_str("registry = \"cn\"\nproxy = \"http://proxy:3128\"").unwrap();
+    assert_eq!(config.registry, Some(Registry(REGISTRY_CN.to_string())));
+    assert!(toml::from_str::<DvmConfig>("registry = \"dl.deno.land\"").is_err());
+    assert!(toml::from_str::<DvmConfig>("proxy = \"proxy:3128\"").is_err());
+    let config: DvmConfig = toml::from_str("activation = \"shim\"").unwrap();
+    assert_eq!(config.activation(), Activation::Shim);
+    assert!(toml::from_str::<DvmConfig>("activation = \"junction\"").is_err());
+    assert!(toml::from_str::<DvmConfig>("unknown = 1").is_err());
+    let config: DvmConfig = toml::from_str("limit-rate = \"2M\"").unwrap();
+    assert_eq!(config.limit_rate(), Some(2 * 1024 * 1024));
+    assert!(toml::from_str::<DvmConfig>("limit-rate = \"fast\"").is_err());
+    let config: DvmConfig = toml::from_str("lts-line = \"~2.4\"").unwrap();
+    assert_eq!(config.lts_line, Some(LtsLine("~2.4".to_string())));
+    assert!(toml::from_str::<DvmConfig>("lts-line = \"two\"").is_err());
+    assert_eq!(config.deno_version_precedence(), VersionPrecedence::Project);
+    let config: DvmConfig =
+      toml::from_str("download-url-template = \"https://mirror.corp/deno/{version}/{archive}\"").unwrap();
+    assert_eq!(
+      config.download_url_template.unwrap().render("1.28.0", "x", "deno.zip"),
+      "https://mirror.corp/deno/1.28.0/deno.zip"
+    );
+    assert!(toml::from_str::<DvmConfig>("download-url-template = \"https://mirror.corp/deno\"").is_err());
+    let config: DvmConfig = toml::from_str("auto-clean = \"remove\"\nauto-clean-unused-days = 90").unwrap();
+    assert_eq!(config.auto_clean(), AutoClean::Remove);
+    assert_eq!(config.auto_clean_unused_days, Some(90));
+    let config: DvmConfig = toml::from_str("delta-source = \"https://deltas.corp/deno/\"").unwrap();
+    assert_eq!(
+      config.delta_source.unwrap().url("1.28.0", "1.28.1", "x"),
+      "https://deltas.corp/deno/v1.28.1/deno-x.from-v1.28.0.zst"
+    );
+    let config: DvmConfig = toml::from_str("delta-source = \"https://deltas.corp/deno/\"").unwrap();
+    assert_eq!(
+      config.delta_source.unwrap().digest_url("1.28.1", "x"),
+      "https://deltas.corp/deno/v1.28.1/deno-x.sha256sum"
+    );
+    assert!(toml::from_str::<DvmConfig>("delta-source = \"deltas.corp\"").is_err());
+    let config: DvmConfig = toml::from_str("deno-version-precedence = \"env\"").unwrap();
+    assert_eq!(config.deno_version_precedence(), VersionPrecedence::Env);
+  }
+
+  #[test]
+  fn test_default_config() {
+    let config = DvmConfig::default();
+    assert_eq!(config.activation(), Activation::Hardlink);
+    assert_eq!(config.retention_millis(), 7 * 24 * 60 * 60 * 1000);
+  }
+
+  #[test]
+  fn test_parse_value() {
+    assert_eq!(parse_value("true"), Value::Boolean(true));
+    assert_eq!(parse_value("90"), Value::Integer(90));
+    assert_eq!(parse_value("copy"), Value::String("copy".to_string()));
+  }
+}
@@ -1,6 +1,7 @@
 pub const REGISTRY_OFFICIAL: &str = "https://dl.deno.land/";
 #[allow(unused)]
 pub const REGISTRY_CN: &str = "https://dl.deno.js.cn/";
+pub const REGISTRY_GITHUB_RELEASE: &str = "https://github.com/denoland/deno/releases/download/";
 pub const REGISTRY_LATEST_RELEASE_PATH: &str = "release-latest.txt";
 pub const REGISTRY_LATEST_CANARY_PATH: &str = "canary-latest.txt";
 pub const REGISTRY_NAME_CN: &str = "cn";
@@ -8,20 +9,66 @@ pub const REGISTRY_NAME_OFFICIAL: &str = "official";
 
 pub const DVM_CACHE_PATH_PREFIX: &str = "versions";
 pub const DVM_CANARY_PATH_PREFIX: &str = "canary";
-pub const DVM_CACHE_INVALID_TIMEOUT: u128 = 60 * 60 * 24 * 7;
+pub const DVM_DENO_DIR_PATH_PREFIX: &str = "cache";
+pub const DVM_STUBS_PATH_PREFIX: &str = "stubs";
+pub const DVM_ARCHIVES_PATH_PREFIX: &str = "archives";
+pub const DVM_HTTP_CACHE_PATH_PREFIX: &str = "http";
+pub const DVM_SELF_UPDATE_PATH_PREFIX: &str = "self-update";
+pub const DVM_BUILD_PATH_PREFIX: &str = "build";
+pub const DVM_HOOKS_PATH_PREFIX: &str = "hooks";
+/// next to the shim in the dvm bin directory, the path of the executable it runs
+pub const DVM_SHIM_TARGET_FILENAME: &str = "deno.shim";
+/// the downloaded org policies, one file a `policy-url`
+pub const DVM_POLICY_FILENAME_PREFIX: &str = "policy-";
+pub const DVM_UPDATE_CHECK_FILENAME: &str = "last-update-check";
+pub const DVM_UPDATE_CHECK_INTERVAL_HOURS: u64 = 24;
+/// installs a deno even if the system glibc looks too old for it
+pub const DVM_SKIP_GLIBC_CHECK_ENV: &str = "DVM_SKIP_GLIBC_CHECK";
+/// lets `deno upgrade` run through `dvm exec` and replace the managed executable
+pub const DVM_ALLOW_DENO_UPGRADE_ENV: &str = "DVM_ALLOW_DENO_UPGRADE";
+/// an install lock untouched for longer is taken over, its dvm must have been killed
+pub const DVM_INSTALL_LOCK_STALE_MINUTES: u64 = 30;
+/// how long a downloaded org policy is enforced before it's downloaded again
+pub const DVM_POLICY_REFRESH_MINUTES: u64 = 60;
+pub const DVM_AUTO_CLEAN_CHECK_FILENAME: &str = "last-auto-clean";
+pub const DVM_RELEASES_FILENAME: &str = "releases.json";
+pub const DVM_RESOLVE_CACHE_FILENAME: &str = "resolve-for-cwd.json";
+pub const DVM_TELEMETRY_NOTICE_FILENAME: &str = "telemetry-notice";
+/// what the last command did, `dvm report` attaches it
+pub const DVM_LAST_COMMAND_LOG_FILENAME: &str = "last-command.log";
+pub const DVM_RELEASES_URL: &str = "https://api.github.com/repos/denoland/deno/releases";
+/// where dvm itself is released, `nightly` is a prerelease replaced every night
+pub const DVM_SELF_RELEASES: &str = "https://github.com/justjavac/dvm/releases/";
+pub const DVM_CACHE_RETENTION_DAYS: u64 = 7;
+pub const DVM_COMPACT_DAYS: u64 = 30;
+/// activations kept by `dvm history`, the oldest ones are dropped first
+pub const DVM_HISTORY_LIMIT: usize = 100;
+/// 128 + SIGINT, what shells report for a command stopped by ctrl-c
+pub const DVM_INTERRUPTED_EXIT_CODE: i32 = 130;
+/// compaction runs rarely, so it's worth the slow high levels
+pub const DVM_COMPACT_LEVEL: i32 = 19;
 
 pub const DVM_CONFIG_FILENAME: &str = ".dvmrc";
+/// pins a version for the current shell or CI step without touching any file
+pub const DVM_DENO_VERSION_ENV: &str = "DVM_DENO_VERSION";
+/// runs `dvm exec` with the build of another architecture, `x64` or `arm64`
+pub const DVM_ARCH_ENV: &str = "DVM_ARCH";
+pub const DVM_CONFIG_TOML_FILENAME: &str = "config.toml";
+pub const DVM_LOCK_FILENAME: &str = "dvm.lock";
 
 pub const DVM_VERSION_CANARY: &str = "canary";
 pub const DVM_VERSION_LATEST: &str = "latest";
+pub const DVM_VERSION_LTS: &str = "lts";
 pub const DVM_VERSION_SYSTEM: &str = "system";
 pub const DVM_VERSION_INVALID: &str = "N/A";
 
 cfg_if::cfg_if! {
   if #[cfg(windows)] {
     pub const DENO_EXE: &str = "deno.exe";
+    pub const DENORT_EXE: &str = "denort.exe";
   } else {
     pub const DENO_EXE: &str = "deno";
+    pub const DENORT_EXE: &str = "denort";
   }
 }
 
@@ -35,7 +82,20 @@ pub const AFTER_HELP: &str = "\x1b[33mEXAMPLE:\x1b[39m
 \x1b[33mNOTE:\x1b[39m
   To remove, delete, or uninstall dvm - just remove the \x1b[36m`$DVM_DIR`\x1b[39m folder (usually \x1b[36m`~/.dvm`\x1b[39m)";
 
+pub const CURRENT_HELP: &str = "\x1b[33mPROMPT SEGMENTS:\x1b[39m
+  `--prompt` prints at most one line and never touches the network, e.g. for starship:
+  \x1b[35m
+  [custom.deno]
+  command = \"dvm current --prompt\"
+  when = true\x1b[39m
+
+  or for oh-my-posh, a `command` segment running `dvm current --prompt`.";
+
 pub const COMPLETIONS_HELP: &str = "Output shell completion script to standard output.
   \x1b[35m
   dvm completions bash > /usr/local/etc/bash_completion.d/dvm.bash
-  source /usr/local/etc/bash_completion.d/dvm.bash\x1b[39m";
+  source /usr/local/etc/bash_completion.d/dvm.bash\x1b[39m
+
+Or let dvm put it where the shell loads it from, again to refresh it.
+  \x1b[35m
+  dvm completions zsh --install\x1b[39m";
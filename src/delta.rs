@@ -0,0 +1,142 @@
+use crate::archive_cache::{parse_sha256, sha256_hex};
+use crate::commands::install::TARGET;
+use crate::commands::use_version::check_exe;
+use crate::config::DvmConfig;
+use crate::timings::{self, Phase};
+use crate::utils::{deno_version_dir, deno_version_path};
+use crate::version::local_versions;
+use crate::{interrupt, output, signature, source};
+use anyhow::{bail, Result};
+use semver::Version;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// `zstd --patch-from` raises the window to cover the whole base binary, the decoder has to allow it
+const WINDOW_LOG_MAX: u32 = if cfg!(target_pointer_width = "64") { 31 } else { 30 };
+
+/// the installed version a delta to `version` starts from, the newest older patch release of its minor
+fn base_version(installed: &[String], version: &Version) -> Option<Version> {
+  installed
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .filter(|it| it.major == version.major && it.minor == version.minor && it < version)
+    .max()
+}
+
+/// rebuild a binary from the one it was diffed against, as `zstd -d --patch-from=<base>` does
+fn apply(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+  let mut decoder = zstd::stream::read::Decoder::with_ref_prefix(delta, base)?;
+  decoder.window_log_max(WINDOW_LOG_MAX)?;
+  let mut binary = Vec::new();
+  decoder.read_to_end(&mut binary)?;
+  Ok(binary)
+}
+
+///
+/// write the patched executable of `version`, it has to match the published digest before it's written,
+/// and it's removed again unless it runs as that version.
+fn write_patched(base_path: &Path, delta: &[u8], version: &Version, digest: &str) -> Result<PathBuf> {
+  let binary = apply(&fs::read(base_path)?, delta)?;
+  let actual = sha256_hex(&binary);
+  if actual != digest {
+    bail!(t!(
+      "The patched deno v{} is {}, {} was published",
+      version,
+      actual,
+      digest
+    ));
+  }
+  let version_dir = deno_version_dir(version);
+  let exe_path = deno_version_path(version);
+  let _staged = interrupt::stage(if version_dir.exists() { &exe_path } else { &version_dir });
+  fs::create_dir_all(&version_dir)?;
+  fs::write(&exe_path, binary)?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&exe_path, fs::Permissions::from_mode(0o755))?;
+    if crate::utils::dvm_system_root().is_some() {
+      fs::set_permissions(&version_dir, fs::Permissions::from_mode(0o755))?;
+    }
+  }
+  if let Err(err) = check_exe(&exe_path, Some(version)) {
+    fs::remove_file(&exe_path).ok();
+    return Err(err);
+  }
+  Ok(exe_path)
+}
+
+///
+/// install `version` by patching the newest installed patch release of its minor with a delta from `delta-source`,
+/// `false` if there's nothing to patch from or no delta for it, the full release is downloaded then.
+/// when signatures are required or checked with a `signature-public-key` the full release is always downloaded,
+/// a patched binary can't be checked against them.
+pub async fn install(version: &Version) -> Result<bool> {
+  let config = DvmConfig::load();
+  let signed = signature::is_required() || config.signature_public_key.is_some();
+  let Some(source) = config.delta_source.filter(|_| !signed) else {
+    return Ok(false);
+  };
+  let Some(base) = base_version(&local_versions(), version) else {
+    return Ok(false);
+  };
+  // a compacted base would have to be restored first, which costs more than the download saves
+  let base_path = deno_version_path(&base);
+  if !base_path.exists() {
+    return Ok(false);
+  }
+  let url = source.url(&base.to_string(), &version.to_string(), TARGET);
+  let Some(delta) = source::fetch(&[url]).await? else {
+    return Ok(false);
+  };
+  // nothing unverified is ever run, without a digest the full release is downloaded
+  let digest_url = source.digest_url(&version.to_string(), TARGET);
+  let Some(digest) = source::fetch(&[digest_url])
+    .await?
+    .and_then(|it| parse_sha256(&String::from_utf8_lossy(&it)))
+  else {
+    output::warn(t!(
+      "The delta to deno v{} has no published digest, downloading the full release",
+      version
+    ));
+    return Ok(false);
+  };
+
+  output::info(t!("Patching deno v{} from v{}", version, base));
+  let _timer = timings::start(Phase::Extract);
+  let version = version.clone();
+  tokio::task::spawn_blocking(move || write_patched(&base_path, &delta, &version, &digest)).await??;
+  Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_base_version() {
+    let installed = ["1.28.0", "1.28.2", "1.29.0", "1.27.9"].map(String::from);
+    let base = |it: &str| base_version(&installed, &Version::parse(it).unwrap());
+    assert_eq!(base("1.28.3"), Version::parse("1.28.2").ok());
+    assert_eq!(base("1.28.1"), Version::parse("1.28.0").ok());
+    assert_eq!(base("1.29.0"), None);
+    assert_eq!(base("1.30.1"), None);
+  }
+
+  #[test]
+  fn test_apply() {
+    let base = b"deno 1.28.0 ".repeat(1000);
+    let mut binary = base.clone();
+    binary[6000..6012].copy_from_slice(b"deno 1.28.1 ");
+    let mut delta = Vec::new();
+    {
+      let mut encoder = zstd::stream::write::Encoder::with_ref_prefix(&mut delta, 19, &base).unwrap();
+      std::io::Write::write_all(&mut encoder, &binary).unwrap();
+      encoder.finish().unwrap();
+    }
+    assert!(delta.len() < 100);
+    assert_eq!(apply(&base, &delta).unwrap(), binary);
+    assert_ne!(apply(b"another base", &delta).ok(), Some(binary));
+  }
+}
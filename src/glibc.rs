@@ -0,0 +1,99 @@
+use crate::consts::DVM_SKIP_GLIBC_CHECK_ENV;
+use crate::version::remote_versions;
+use anyhow::{bail, Result};
+use semver::{Version, VersionReq};
+use std::process::Command;
+
+/// a glibc version, `(major, minor)`
+type Glibc = (u32, u32);
+
+///
+/// the oldest glibc the linux builds of a deno release run on, the first matching range wins,
+/// newer releases are built against newer glibc, add a range when that moves again.
+const MINIMUMS: &[(&str, Glibc)] = &[(">=1.18.0", (2, 27)), ("*", (2, 18))];
+
+/// `glibc 2.35` as printed by `getconf GNU_LIBC_VERSION`
+fn parse(text: &str) -> Option<Glibc> {
+  let version = text.split_whitespace().last()?;
+  let mut parts = version.split('.');
+  Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// the glibc of this system, `None` on musl or wherever it can't be told
+fn system_glibc() -> Option<Glibc> {
+  let output = Command::new("getconf").arg("GNU_LIBC_VERSION").output().ok()?;
+  output
+    .status
+    .success()
+    .then(|| parse(&String::from_utf8_lossy(&output.stdout)))?
+}
+
+fn required_glibc(version: &Version) -> Option<Glibc> {
+  MINIMUMS
+    .iter()
+    .find(|(range, _)| VersionReq::parse(range).map(|it| it.matches(version)).unwrap_or(false))
+    .map(|(_, glibc)| *glibc)
+}
+
+/// the newest release that runs on `glibc`
+fn newest_supported(versions: &[String], glibc: Glibc) -> Option<Version> {
+  versions
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .filter(|it| it.pre.is_empty() && required_glibc(it).map(|required| required <= glibc).unwrap_or(true))
+    .max()
+}
+
+///
+/// fail before anything is downloaded if the system glibc is too old for a version,
+/// a deno linked against a newer glibc installs fine and only dies once it's run.
+/// `DVM_SKIP_GLIBC_CHECK` skips it, e.g. for systems with a compatible glibc elsewhere.
+pub fn check(version: &Version) -> Result<()> {
+  if !cfg!(target_os = "linux") || std::env::var_os(DVM_SKIP_GLIBC_CHECK_ENV).is_some() {
+    return Ok(());
+  }
+  let (Some(system), Some(required)) = (system_glibc(), required_glibc(version)) else {
+    return Ok(());
+  };
+  if system >= required {
+    return Ok(());
+  }
+
+  let message = t!(
+    "deno v{} needs glibc {}.{} or newer, this system has glibc {}.{}",
+    version,
+    required.0,
+    required.1,
+    system.0,
+    system.1
+  );
+  match remote_versions().ok().and_then(|it| newest_supported(&it, system)) {
+    Some(older) => bail!(t!(
+      "{}\nInstall deno v{} instead, the newest one that runs here, or set {} to install anyway",
+      message,
+      older,
+      DVM_SKIP_GLIBC_CHECK_ENV
+    )),
+    None => bail!(t!("{}\nSet {} to install anyway", message, DVM_SKIP_GLIBC_CHECK_ENV)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(parse("glibc 2.35\n"), Some((2, 35)));
+    assert_eq!(parse("glibc 2.17"), Some((2, 17)));
+    assert_eq!(parse(""), None);
+  }
+
+  #[test]
+  fn test_newest_supported() {
+    let versions = ["1.17.3", "1.18.0", "1.28.0", "1.17.0"].map(String::from);
+    assert_eq!(required_glibc(&Version::parse("1.28.0").unwrap()), Some((2, 27)));
+    assert_eq!(newest_supported(&versions, (2, 26)), Version::parse("1.17.3").ok());
+    assert_eq!(newest_supported(&versions, (2, 35)), Version::parse("1.28.0").ok());
+  }
+}
@@ -0,0 +1,88 @@
+use crate::consts::DVM_HOOKS_PATH_PREFIX;
+use crate::output;
+use crate::utils::{dvm_config_root, dvm_root};
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// the scripts dvm runs around an install, they live in `<config dir>/hooks/<name>`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Hook {
+  /// before anything is downloaded, failing it aborts the install
+  PreInstall,
+  /// once the version is unpacked, failing it only warns
+  PostInstall,
+}
+
+impl Hook {
+  pub fn name(self) -> &'static str {
+    match self {
+      Hook::PreInstall => "pre-install",
+      Hook::PostInstall => "post-install",
+    }
+  }
+}
+
+/// the script of a hook, windows also finds `<name>.cmd`, `<name>.bat` and `<name>.ps1`
+fn script(hook: Hook) -> Option<PathBuf> {
+  let dir = dvm_config_root().join(DVM_HOOKS_PATH_PREFIX);
+  let extensions: &[&str] = if cfg!(windows) {
+    &["", "cmd", "bat", "ps1"]
+  } else {
+    &[""]
+  };
+  extensions
+    .iter()
+    .map(|ext| dir.join(hook.name()).with_extension(ext))
+    .find(|it| it.is_file())
+}
+
+fn command(script: &Path) -> Command {
+  match script.extension().and_then(|it| it.to_str()) {
+    Some("ps1") => {
+      let mut cmd = Command::new("powershell");
+      cmd
+        .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"])
+        .arg(script);
+      cmd
+    }
+    Some("cmd" | "bat") => {
+      let mut cmd = Command::new("cmd");
+      cmd.arg("/C").arg(script);
+      cmd
+    }
+    _ => Command::new(script),
+  }
+}
+
+///
+/// run a hook if it's there, it's given `DVM_HOOK`, `DVM_VERSION`, `DVM_DENO_PATH` and `DVM_DIR`,
+/// its output goes to stderr when stdout carries json progress events.
+pub fn run(hook: Hook, version: &str, deno_path: &Path) -> Result<()> {
+  let Some(script) = script(hook) else {
+    return Ok(());
+  };
+  output::info(t!("Running the {} hook {}", hook.name(), script.display()));
+
+  let status = command(&script)
+    .env("DVM_HOOK", hook.name())
+    .env("DVM_VERSION", version)
+    .env("DVM_DENO_PATH", deno_path)
+    .env("DVM_DIR", dvm_root())
+    .stdin(Stdio::null())
+    .stdout(output::passthrough())
+    .status();
+
+  let failure = match status {
+    Ok(status) if status.success() => return Ok(()),
+    Ok(status) => status.to_string(),
+    Err(err) => err.to_string(),
+  };
+  match hook {
+    Hook::PreInstall => bail!(t!("The {} hook failed: {}", hook.name(), failure)),
+    Hook::PostInstall => {
+      output::warn(t!("The {} hook failed: {}", hook.name(), failure));
+      Ok(())
+    }
+  }
+}
@@ -0,0 +1,506 @@
+use crate::config::DvmConfig;
+use crate::{http_trace, interrupt};
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use cfg_if::cfg_if;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+static LIMIT_RATE: OnceLock<Option<u64>> = OnceLock::new();
+static TRANSPORT: OnceLock<Box<dyn Transport>> = OnceLock::new();
+static REGISTRY_AUTH: OnceLock<Option<RegistryAuth>> = OnceLock::new();
+
+/// credentials of a private registry, only sent to urls under it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryAuth {
+  prefixes: Vec<String>,
+  authorization: String,
+}
+
+impl RegistryAuth {
+  pub fn bearer(registry: &str, token: &str) -> Self {
+    RegistryAuth {
+      prefixes: vec![registry.to_string()],
+      authorization: format!("Bearer {}", token),
+    }
+  }
+
+  pub fn basic(registry: &str, credentials: &str) -> Self {
+    RegistryAuth {
+      prefixes: vec![registry.to_string()],
+      authorization: format!("Basic {}", STANDARD.encode(credentials)),
+    }
+  }
+
+  /// send the credentials to the urls under `prefix` as well
+  pub fn with_prefix(mut self, prefix: String) -> Self {
+    self.prefixes.push(prefix);
+    self
+  }
+
+  /// the `Authorization` header of a request, GitHub and the other hosts never get it
+  pub fn header_for(&self, url: &str) -> Option<&str> {
+    self
+      .prefixes
+      .iter()
+      .any(|it| url.starts_with(it))
+      .then_some(self.authorization.as_str())
+  }
+}
+
+#[cfg(test)]
+thread_local! {
+  static SCOPED_TRANSPORT: std::cell::RefCell<Option<std::sync::Arc<dyn Transport>>> = std::cell::RefCell::new(None);
+}
+
+/// what identifies the version of a response, `ETag` and `Last-Modified`, sent back to ask whether it changed
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Validators {
+  pub etag: Option<String>,
+  pub last_modified: Option<String>,
+}
+
+impl Validators {
+  pub fn is_empty(&self) -> bool {
+    self.etag.is_none() && self.last_modified.is_none()
+  }
+}
+
+/// a response whose body hasn't been read yet, the body comes in chunks
+pub struct Response {
+  status: u16,
+  content_length: Option<u64>,
+  validators: Validators,
+  body: BoxStream<'static, std::io::Result<Vec<u8>>>,
+}
+
+impl Response {
+  pub fn new(status: u16, body: impl Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static) -> Self {
+    Response {
+      status,
+      content_length: None,
+      validators: Validators::default(),
+      body: body.boxed(),
+    }
+  }
+
+  pub fn with_validators(mut self, validators: Validators) -> Self {
+    self.validators = validators;
+    self
+  }
+
+  pub fn validators(&self) -> &Validators {
+    &self.validators
+  }
+
+  pub fn with_content_length(mut self, content_length: Option<u64>) -> Self {
+    self.content_length = content_length;
+    self
+  }
+
+  /// a response with the whole body at hand
+  pub fn from_bytes(status: u16, body: Vec<u8>) -> Self {
+    Response::new(status, stream::once(async { Ok(body) }))
+  }
+
+  pub fn status(&self) -> u16 {
+    self.status
+  }
+
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+
+  pub fn content_length(&self) -> Option<u64> {
+    self.content_length
+  }
+
+  /// the whole body, throttled to the limit rate if there's one
+  pub async fn bytes(self) -> std::io::Result<Vec<u8>> {
+    self.bytes_with_progress(|_| {}).await
+  }
+
+  /// like `bytes`, `on_progress` is given the number of bytes read so far after each chunk
+  pub async fn bytes_with_progress(mut self, mut on_progress: impl FnMut(u64)) -> std::io::Result<Vec<u8>> {
+    let rate = limit_rate();
+    let start = Instant::now();
+    let mut body = Vec::new();
+    while let Some(chunk) = self.body.next().await {
+      body.extend_from_slice(&chunk?);
+      on_progress(body.len() as u64);
+      if let Some(rate) = rate {
+        let expected = Duration::from_secs_f64(body.len() as f64 / rate as f64);
+        if let Some(ahead) = expected.checked_sub(start.elapsed()) {
+          tokio::time::sleep(ahead).await;
+        }
+      }
+    }
+    Ok(body)
+  }
+
+  pub async fn text(self) -> Result<String> {
+    Ok(String::from_utf8(self.bytes().await?)?)
+  }
+}
+
+///
+/// how dvm talks to the network, every request goes through it,
+/// so tests can answer from memory with `with_transport`.
+pub trait Transport: Send + Sync {
+  /// a `GET` request, statuses other than 2xx are responses too, not errors
+  fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>>;
+  /// a `GET` answered with `304 Not Modified` if what `validators` identify is still current,
+  /// transports without conditional requests get the whole body every time
+  fn get_if_changed<'a>(&'a self, url: &'a str, validators: &'a Validators) -> BoxFuture<'a, Result<Response>> {
+    let _ = validators;
+    self.get(url)
+  }
+  /// a `HEAD` request, the response has no body
+  fn head<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>>;
+  /// a `POST` request of a json body
+  fn post_json<'a>(&'a self, url: &'a str, body: String) -> BoxFuture<'a, Result<Response>>;
+}
+
+/// the default transport
+pub struct ReqwestTransport;
+
+cfg_if! {
+  if #[cfg(feature = "rustls")] {
+    pub const TLS_BACKEND: &str = "rustls (webpki roots)";
+  } else if #[cfg(all(feature = "native-tls", windows))] {
+    pub const TLS_BACKEND: &str = "native-tls (schannel)";
+  } else if #[cfg(all(feature = "native-tls", target_os = "macos"))] {
+    pub const TLS_BACKEND: &str = "native-tls (security framework)";
+  } else if #[cfg(feature = "native-tls")] {
+    pub const TLS_BACKEND: &str = "native-tls (OpenSSL, statically linked)";
+  } else {
+    /// built with `--no-default-features`, only plain http registries work
+    pub const TLS_BACKEND: &str = "none";
+  }
+}
+
+/// add the registry credentials to a request for a url under the registry
+fn authorize(request: reqwest::RequestBuilder, url: &str) -> reqwest::RequestBuilder {
+  match REGISTRY_AUTH.get().and_then(|it| it.as_ref()?.header_for(url)) {
+    Some(authorization) => request.header(reqwest::header::AUTHORIZATION, authorization),
+    None => request,
+  }
+}
+
+/// a reqwest response as a `Response`, with its validators
+fn to_response(response: reqwest::Response) -> Response {
+  let status = response.status().as_u16();
+  let content_length = response.content_length();
+  let header = |name| {
+    response
+      .headers()
+      .get(name)
+      .and_then(|it: &reqwest::header::HeaderValue| it.to_str().ok())
+      .map(str::to_string)
+  };
+  let validators = Validators {
+    etag: header(reqwest::header::ETAG),
+    last_modified: header(reqwest::header::LAST_MODIFIED),
+  };
+  let body = response
+    .bytes_stream()
+    .map(|chunk| chunk.map(|it| it.to_vec()).map_err(std::io::Error::other));
+  Response::new(status, body)
+    .with_content_length(content_length)
+    .with_validators(validators)
+}
+
+impl Transport for ReqwestTransport {
+  fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+    Box::pin(async move { Ok(to_response(http_trace::send(authorize(client().get(url), url)).await?)) })
+  }
+
+  fn get_if_changed<'a>(&'a self, url: &'a str, validators: &'a Validators) -> BoxFuture<'a, Result<Response>> {
+    Box::pin(async move {
+      let mut request = authorize(client().get(url), url);
+      if let Some(etag) = &validators.etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+      }
+      if let Some(last_modified) = &validators.last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+      }
+      Ok(to_response(http_trace::send(request).await?))
+    })
+  }
+
+  fn head<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+    Box::pin(async move {
+      let response = http_trace::send(authorize(client().head(url), url)).await?;
+      Ok(Response::from_bytes(response.status().as_u16(), Vec::new()))
+    })
+  }
+
+  fn post_json<'a>(&'a self, url: &'a str, body: String) -> BoxFuture<'a, Result<Response>> {
+    Box::pin(async move {
+      let request = client()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+      let response = http_trace::send(request).await?;
+      Ok(Response::from_bytes(response.status().as_u16(), Vec::new()))
+    })
+  }
+}
+
+///
+/// the client shared by the whole command, so the connections are pooled
+/// and a flow of several requests pays the TLS handshake only once per host.
+pub fn client() -> &'static Client {
+  static CLIENT: OnceLock<Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    // no overall timeout, archives can take a while on slow networks
+    let builder = Client::builder()
+      // GitHub API requires a user agent, http://developer.github.com/v3/#user-agent-required
+      .user_agent(concat!("dvm/", env!("CARGO_PKG_VERSION")))
+      .connect_timeout(Duration::from_secs(30));
+    #[cfg(feature = "rustls")]
+    let builder = builder.use_rustls_tls();
+    let builder = match configured_proxy() {
+      Some(proxy) => builder.proxy(proxy),
+      None => builder,
+    };
+    builder.build().expect("Failed to build the http client")
+  })
+}
+
+/// the `proxy` config key, the proxy variables of the environment win like they do for any other tool
+fn configured_proxy() -> Option<reqwest::Proxy> {
+  let vars = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+  ];
+  if vars
+    .iter()
+    .any(|it| std::env::var_os(it).is_some_and(|it| !it.is_empty()))
+  {
+    return None;
+  }
+  let proxy = DvmConfig::load().proxy?;
+  let proxy = reqwest::Proxy::all(&proxy.0).expect("the proxy url is checked when the config is loaded");
+  Some(proxy.no_proxy(reqwest::NoProxy::from_env()))
+}
+
+pub fn runtime() -> &'static Runtime {
+  static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+  RUNTIME.get_or_init(|| {
+    tokio::runtime::Builder::new_multi_thread()
+      .enable_all()
+      .build()
+      .expect("Failed to start the async runtime")
+  })
+}
+
+///
+/// drive a network flow from the synchronous commands,
+/// ctrl-c cancels whatever is in flight, a partial download is never renamed into place.
+pub fn block_on<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+  runtime().block_on(async {
+    tokio::select! {
+      result = future => result,
+      _ = tokio::signal::ctrl_c() => interrupt::exit(),
+    }
+  })
+}
+
+/// run `f` with requests of the current thread going through `transport`
+#[cfg(test)]
+pub fn with_transport<R>(transport: impl Transport + 'static, f: impl FnOnce() -> R) -> R {
+  let previous = SCOPED_TRANSPORT.with(|it| it.replace(Some(std::sync::Arc::new(transport))));
+  let result = f();
+  SCOPED_TRANSPORT.with(|it| it.replace(previous));
+  result
+}
+
+/// a `GET` through the configured transport
+pub async fn get(url: &str) -> Result<Response> {
+  #[cfg(test)]
+  if let Some(transport) = SCOPED_TRANSPORT.with(|it| it.borrow().clone()) {
+    return transport.get(url).await;
+  }
+  TRANSPORT.get_or_init(|| Box::new(ReqwestTransport)).get(url).await
+}
+
+/// a conditional `GET` through the configured transport
+pub async fn get_if_changed(url: &str, validators: &Validators) -> Result<Response> {
+  #[cfg(test)]
+  if let Some(transport) = SCOPED_TRANSPORT.with(|it| it.borrow().clone()) {
+    return transport.get_if_changed(url, validators).await;
+  }
+  TRANSPORT
+    .get_or_init(|| Box::new(ReqwestTransport))
+    .get_if_changed(url, validators)
+    .await
+}
+
+/// a `HEAD` through the configured transport
+pub async fn head(url: &str) -> Result<Response> {
+  #[cfg(test)]
+  if let Some(transport) = SCOPED_TRANSPORT.with(|it| it.borrow().clone()) {
+    return transport.head(url).await;
+  }
+  TRANSPORT.get_or_init(|| Box::new(ReqwestTransport)).head(url).await
+}
+
+/// a `POST` of a json body through the configured transport
+pub async fn post_json(url: &str, body: String) -> Result<Response> {
+  #[cfg(test)]
+  if let Some(transport) = SCOPED_TRANSPORT.with(|it| it.borrow().clone()) {
+    return transport.post_json(url, body).await;
+  }
+  TRANSPORT
+    .get_or_init(|| Box::new(ReqwestTransport))
+    .post_json(url, body)
+    .await
+}
+
+///
+/// parse a rate such as `2M`, `500k` or `1048576` into bytes per second,
+/// the suffixes are binary multiples like curl's `--limit-rate`.
+pub fn parse_rate(raw: &str) -> Result<u64, String> {
+  let raw = raw.trim();
+  let (digits, multiple) = match raw.chars().last().map(|c| c.to_ascii_lowercase()) {
+    Some('k') => (&raw[..raw.len() - 1], 1024),
+    Some('m') => (&raw[..raw.len() - 1], 1024 * 1024),
+    Some('g') => (&raw[..raw.len() - 1], 1024 * 1024 * 1024),
+    _ => (raw, 1),
+  };
+  let invalid = || format!("invalid rate `{}`, expected e.g. 500K or 2M", raw);
+  match digits.parse::<u64>() {
+    Ok(rate) if rate > 0 => rate.checked_mul(multiple).ok_or_else(invalid),
+    _ => Err(invalid()),
+  }
+}
+
+///
+/// pick up the credentials of `registry` from the environment or the config, a token wins over basic auth,
+/// the host of `download-url-template` gets them too,
+/// cross-host redirects drop them, so a mirror redirecting to a CDN doesn't leak them.
+pub fn set_registry_auth(registry: &str) {
+  let config = DvmConfig::load();
+  let token = std::env::var("DVM_REGISTRY_TOKEN").ok().or(config.registry_token);
+  let basic = std::env::var("DVM_REGISTRY_BASIC_AUTH")
+    .ok()
+    .or(config.registry_basic_auth);
+  let auth = match (token, basic) {
+    (Some(token), _) if !token.is_empty() => Some(RegistryAuth::bearer(registry, &token)),
+    (_, Some(basic)) if !basic.is_empty() => Some(RegistryAuth::basic(registry, &basic)),
+    _ => None,
+  };
+  let template_origin = config
+    .download_url_template
+    .and_then(|it| reqwest::Url::parse(&it.0).ok())
+    .map(|it| format!("{}/", it.origin().ascii_serialization()));
+  let auth = match (auth, template_origin) {
+    (Some(auth), Some(origin)) => Some(auth.with_prefix(origin)),
+    (auth, _) => auth,
+  };
+  REGISTRY_AUTH.set(auth).ok();
+}
+
+/// the `--limit-rate` option takes precedence over the `limit-rate` config key
+pub fn set_limit_rate(rate: Option<u64>) {
+  LIMIT_RATE.set(rate.or_else(|| DvmConfig::load().limit_rate())).ok();
+}
+
+fn limit_rate() -> Option<u64> {
+  *LIMIT_RATE.get_or_init(|| DvmConfig::load().limit_rate())
+}
+
+/// a transport answering from memory, unknown urls are 404s
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockTransport {
+  responses: std::collections::HashMap<String, (u16, Vec<u8>)>,
+}
+
+#[cfg(test)]
+impl MockTransport {
+  pub fn with(mut self, url: &str, status: u16, body: impl Into<Vec<u8>>) -> Self {
+    self.responses.insert(url.to_string(), (status, body.into()));
+    self
+  }
+}
+
+#[cfg(test)]
+impl Transport for MockTransport {
+  fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+    let (status, body) = self.responses.get(url).cloned().unwrap_or((404, Vec::new()));
+    Box::pin(async move { Ok(Response::from_bytes(status, body)) })
+  }
+
+  fn head<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+    self.get(url)
+  }
+
+  fn post_json<'a>(&'a self, url: &'a str, _body: String) -> BoxFuture<'a, Result<Response>> {
+    self.get(url)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_with_transport() {
+    let mock = MockTransport::default().with("https://dl.deno.land/release-latest.txt", 200, "v1.28.0\n");
+    with_transport(mock, || {
+      block_on(async {
+        let response = get("https://dl.deno.land/release-latest.txt").await?;
+        assert!(response.is_success());
+        assert_eq!(response.text().await?, "v1.28.0\n");
+        assert_eq!(get("https://dl.deno.land/missing").await?.status(), 404);
+        Ok(())
+      })
+      .unwrap()
+    });
+  }
+
+  #[test]
+  fn test_registry_auth() {
+    let auth = RegistryAuth::basic("https://deno.corp/", "ci:secret");
+    assert_eq!(
+      auth.header_for("https://deno.corp/release/v1.28.0/deno.zip"),
+      Some("Basic Y2k6c2VjcmV0")
+    );
+    assert_eq!(
+      auth.header_for("https://github.com/denoland/deno/releases/download/"),
+      None
+    );
+    let auth = RegistryAuth::bearer("https://deno.corp/", "token").with_prefix("https://mirror.corp/".to_string());
+    assert_eq!(
+      auth.header_for("https://mirror.corp/deno/1.28.0/deno.zip"),
+      Some("Bearer token")
+    );
+    assert_eq!(
+      auth.header_for("https://deno.corp/release-latest.txt"),
+      Some("Bearer token")
+    );
+  }
+
+  #[test]
+  fn test_parse_rate() {
+    assert_eq!(parse_rate("2M"), Ok(2 * 1024 * 1024));
+    assert_eq!(parse_rate("500k"), Ok(500 * 1024));
+    assert_eq!(parse_rate("4096"), Ok(4096));
+    assert!(parse_rate("0").is_err());
+    assert!(parse_rate("18446744073709551615G").is_err());
+    assert!(parse_rate("fast").is_err());
+  }
+}
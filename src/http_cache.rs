@@ -0,0 +1,112 @@
+use crate::archive_cache::sha256_hex;
+use crate::consts::DVM_HTTP_CACHE_PATH_PREFIX;
+use crate::http::{self, Response, Validators};
+use crate::utils::dvm_cache_root;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// a cached response body, with what it's revalidated with
+#[derive(Deserialize, Serialize)]
+struct Entry {
+  validators: Validators,
+  body: String,
+}
+
+fn entry_path(dir: &Path, url: &str) -> PathBuf {
+  dir.join(format!("{}.json", &sha256_hex(url.as_bytes())[..16]))
+}
+
+async fn get_in(dir: &Path, url: &str) -> Result<Response> {
+  let path = entry_path(dir, url);
+  let cached = fs::read(&path)
+    .ok()
+    .and_then(|it| serde_json::from_slice::<Entry>(&it).ok());
+  let validators = cached.as_ref().map(|it| it.validators.clone()).unwrap_or_default();
+  let response = http::get_if_changed(url, &validators).await?;
+  if let (304, Some(cached)) = (response.status(), cached) {
+    return Ok(Response::from_bytes(200, cached.body.into_bytes()).with_validators(cached.validators));
+  }
+  if !response.is_success() || response.validators().is_empty() {
+    return Ok(response);
+  }
+
+  let (status, validators) = (response.status(), response.validators().clone());
+  let body = response.bytes().await?;
+  if let Ok(text) = String::from_utf8(body.clone()) {
+    // the cache only saves transfers, failing to write it is fine
+    let entry = Entry {
+      validators: validators.clone(),
+      body: text,
+    };
+    if fs::create_dir_all(dir).is_ok() {
+      fs::write(&path, serde_json::to_vec(&entry)?).ok();
+    }
+  }
+  Ok(Response::from_bytes(status, body).with_validators(validators))
+}
+
+///
+/// a `GET` of remote metadata, e.g. the releases list or a checksum, revalidated with the `ETag` or
+/// `Last-Modified` it was cached with, so a refresh of something unchanged is a `304` without a body.
+/// GitHub doesn't count those against the API rate limit.
+pub async fn get(url: &str) -> Result<Response> {
+  get_in(&dvm_cache_root().join(DVM_HTTP_CACHE_PATH_PREFIX), url).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::{block_on, with_transport, Transport};
+  use futures_util::future::BoxFuture;
+
+  /// a server whose only resource has the etag `"1"`
+  struct Revalidating;
+
+  impl Transport for Revalidating {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+      static NONE: Validators = Validators {
+        etag: None,
+        last_modified: None,
+      };
+      self.get_if_changed(url, &NONE)
+    }
+
+    fn get_if_changed<'a>(&'a self, _url: &'a str, validators: &'a Validators) -> BoxFuture<'a, Result<Response>> {
+      let etag = Validators {
+        etag: Some("\"1\"".to_string()),
+        last_modified: None,
+      };
+      let response = if validators.etag == etag.etag {
+        Response::from_bytes(304, Vec::new())
+      } else {
+        Response::from_bytes(200, b"v1.28.0".to_vec()).with_validators(etag)
+      };
+      Box::pin(async move { Ok(response) })
+    }
+
+    fn head<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response>> {
+      self.get(url)
+    }
+
+    fn post_json<'a>(&'a self, url: &'a str, _body: String) -> BoxFuture<'a, Result<Response>> {
+      self.get(url)
+    }
+  }
+
+  #[test]
+  fn test_get_revalidates() {
+    let dir = std::env::temp_dir().join(format!("dvm-http-cache-{}", std::process::id()));
+    let url = "https://dl.deno.land/release-latest.txt";
+    with_transport(Revalidating, || {
+      for _ in 0..2 {
+        let response = block_on(get_in(&dir, url)).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(block_on(async { response.text().await }).unwrap(), "v1.28.0");
+      }
+    });
+    assert!(entry_path(&dir, url).exists());
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
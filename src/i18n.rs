@@ -0,0 +1,421 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Locale {
+  En,
+  ZhCn,
+}
+
+///
+/// translate a message into the user's locale,
+/// the English message is the key, and `{}` in it are filled with `args` in order:
+///
+/// ```ignore
+/// println!("{}", t!("Now using deno {}", version));
+/// ```
+macro_rules! t {
+  ($msg:literal) => {
+    $crate::i18n::translate($msg, &[])
+  };
+  ($msg:literal, $($arg:expr),+ $(,)?) => {
+    $crate::i18n::translate($msg, &[$(&$arg as &dyn std::fmt::Display),+])
+  };
+}
+
+/// Simplified Chinese messages keyed by their English version
+static ZH_CN: phf::Map<&'static str, &'static str> = phf::phf_map! {
+  // common
+  "error" => "错误",
+  "downloading {}" => "正在下载 {}",
+  "download '{}' failed: {}" => "下载 '{}' 失败：{}",
+  "deno v{} is not installed. do you want to install it?" => "deno v{} 尚未安装，是否安装？",
+  "Invalid semver {}" => "无效的 semver 版本 {}",
+  "Checking for latest version" => "正在检查最新版本",
+  "The latest version is v{}" => "最新版本为 v{}",
+  "Fetching version list" => "正在获取版本列表",
+  "A version should be followed after {}" => "{} 后面需要跟一个版本号",
+  "Ignoring invalid {}: {}" => "忽略无效的 {}：{}",
+  "Could not find the home directory, please set `DVM_DIR` or pass `--dvm-dir`" =>
+    "找不到用户主目录，请设置 `DVM_DIR` 或传入 `--dvm-dir`",
+  "The versions store {} is read-only, please ask its administrator to manage versions" =>
+    "版本仓库 {} 是只读的，请联系管理员管理版本",
+  "Did you mean {}?" => "你是不是想要 {}？",
+  "Interrupted, cleaning up" => "已中断，正在清理",
+  "Removed {}" => "已移除 {}",
+  // install
+  "Version v{} is already installed" => "v{} 版本已经安装",
+  "Version has not been found, aborting" => "未找到该版本，已中止",
+  "Version has been found" => "已找到该版本",
+  "Deno v{} has been downloaded" => "Deno v{} 已下载",
+  "denort v{} has been downloaded" => "denort v{} 已下载",
+  "denort is not available for canary, skipped" => "canary 版本没有 denort，已跳过",
+  "denort is not available for deno v{}: {}" => "deno v{} 没有可用的 denort：{}",
+  "{} has not been found" => "未找到 {}",
+  "Using the cached {}" => "使用缓存的 {}",
+  "Dropping the corrupted cache of {}" => "丢弃已损坏的 {} 缓存",
+  "Failed to cache {}: {}" => "缓存 {} 失败：{}",
+  "A signature is required but no public key is configured, set `signature-public-key` with `dvm config set`" =>
+    "要求签名但未配置公钥，请用 `dvm config set` 设置 `signature-public-key`",
+  "{} has no published signature" => "{} 没有发布签名",
+  "{} has no published signature, it's not verified" => "{} 没有发布签名，未经验证",
+  "The signature of {} is invalid: {}" => "{} 的签名无效：{}",
+  "Verified the signature of {} with key {}" => "已验证 {} 的签名，密钥为 {}",
+  "Canary builds aren't signed" => "canary 版本没有签名",
+  "deno has no binaries for this platform, pass `--build-from-source` to build it" =>
+    "deno 没有此平台的二进制文件，传入 `--build-from-source` 以从源码构建",
+  "cargo is required to build deno from source" => "从源码构建 deno 需要 cargo",
+  "Building deno v{} with cargo, it takes a while" => "正在用 cargo 构建 deno v{}，需要一段时间",
+  "Building deno v{} failed: {}, the sources are kept in {}" => "构建 deno v{} 失败：{}，源码保留在 {}",
+  "Deno v{} has been built" => "Deno v{} 已构建完成",
+  "Running the {} hook {}" => "正在运行 {} 钩子 {}",
+  "The {} hook failed: {}" => "{} 钩子运行失败：{}",
+  // use
+  "deno canary is not installed. do you want to install it?" => "deno canary 尚未安装，是否安装？",
+  "Deno that was previously installed on your system will be activated now." => "现在将启用系统中原先安装的 Deno。",
+  "`{}` is not a valid semver version or tag and will not be used\ntype `dvm help` for more info" =>
+    "`{}` 不是有效的 semver 版本或标签，不会被使用\n输入 `dvm help` 查看更多信息",
+  "No version input detect, try to use version in .dvmrc file" => "未指定版本，尝试使用 .dvmrc 文件中的版本",
+  "Using semver range: {}" => "使用 semver 范围：{}",
+  "Canary dir not found, will not be used" => "未找到 canary 目录，不会被使用",
+  "Writing to current folder config" => "写入当前目录的配置",
+  "Writing to home folder config" => "写入用户主目录的配置",
+  "Now using deno canary" => "正在使用 deno canary",
+  "Now using deno {}" => "正在使用 deno {}",
+  "{} is a Windows deno, it's ignored inside WSL in favor of dvm's Linux deno" => "{} 是 Windows 版的 deno，在 WSL 中会被忽略，改用 dvm 的 Linux 版 deno",
+  "Add this line to {} to switch deno per project:" => "将这一行添加到 {} 以按项目切换 deno：",
+  "The plugin has been written to {}" => "插件已写入 {}",
+  "Put {} in PATH before the dvm bin directory, then run `dvm-activate` in a cmd session" => "将 {} 放在 PATH 中 dvm bin 目录之前，然后在 cmd 会话中运行 `dvm-activate`",
+  "Move it into a directory of $env:PSModulePath and add `Import-Module dvm` to $PROFILE, or import {} from $PROFILE" => "将其移动到 $env:PSModulePath 中的目录并在 $PROFILE 中添加 `Import-Module dvm`，或在 $PROFILE 中导入 {}",
+  "Move it to $ZSH_CUSTOM/plugins/dvm and add `dvm` to the plugins in ~/.zshrc, or source {} from ~/.zshrc" => "将其移动到 $ZSH_CUSTOM/plugins/dvm 并在 ~/.zshrc 的 plugins 中添加 `dvm`，或在 ~/.zshrc 中 source {}",
+  "Install it with `fisher install {}`" => "使用 `fisher install {}` 安装",
+  "There is no previous version to roll back to" => "没有可以回滚到的上一个版本",
+  "{} can't be mapped back to v{}, it's not installed anymore" => "{} 无法映射回 v{}，该版本已不再安装",
+  "Mapped {} back to {}" => "已将 {} 映射回 {}",
+  "deno v{} is not installed anymore, run `dvm install {}` first" => "deno v{} 已不再安装，请先运行 `dvm install {}`",
+  "Rolled back to deno {}" => "已回滚到 deno {}",
+  "{} can't be run: {}" => "无法运行 {}：{}",
+  "{} exited with {}" => "{} 退出，{}",
+  "{} reports `{}` instead of deno {}" => "{} 报告的版本是 `{}` 而不是 deno {}",
+  "{}, the previous deno has been restored" => "{}，已恢复之前的 deno",
+  "deno v{} doesn't work, its alias is left unchanged: {}" => "deno v{} 无法正常运行，别名保持不变：{}",
+  "Saved deno {} as the version of {}" => "已将 deno {} 保存为 {} 的版本",
+  "{} is not managed by dvm and comes first in PATH, run `dvm doctor --force-external` to put dvm's deno before it" =>
+    "{} 不是由 dvm 管理的且在 PATH 中排在前面，运行 `dvm doctor --force-external` 以将 dvm 的 deno 放在它前面",
+  // exec
+  "No such alias or version found." => "未找到该别名或版本。",
+  "No such version found." => "未找到该版本。",
+  // uninstall
+  "Invalid semver" => "无效的 semver 版本",
+  "deno v{} is not installed." => "deno v{} 尚未安装。",
+  "deno v{} removed." => "deno v{} 已移除。",
+  // clean
+  "Cleaned successfully" => "清理完成",
+  "Containers run linux, {} is no linux target" => "容器运行的是 linux，{} 不是 linux 目标平台",
+  "{} is not a sha256" => "{} 不是 sha256",
+  "deno v{} publishes no sha256 of {}, pass it with --sha256" => "deno v{} 没有发布 {} 的 sha256，请通过 --sha256 传入",
+  "deno v{} publishes no sha256 of {}, it's left out" => "deno v{} 没有发布 {} 的 sha256，已跳过",
+  "deno v{} publishes no sha256 of its linux builds" => "deno v{} 没有发布其 linux 版本的 sha256",
+  "The devcontainer feature installing deno v{} has been written to {}" => "安装 deno v{} 的 devcontainer feature 已写入 {}",
+  "Add it to the features of {}:" => "请将其加入 {} 的 features：",
+  "{} has been created" => "{} 已创建",
+  "last used {}" => "上次使用于 {}",
+  "deno {} haven't been used for {} days, remove them?" => "deno {} 已经 {} 天没有使用了，是否删除？",
+  "Failed to remove deno v{}: {}" => "删除 deno v{} 失败：{}",
+  "{} installed, oldest {}, newest {}" => "已安装 {} 个，最旧 {}，最新 {}",
+  "never" => "从未",
+  "defaults" => "默认",
+  "Taking over the stale lock {}" => "接管过期的锁 {}",
+  "Waiting for another dvm to finish installing deno {}" => "正在等待另一个 dvm 完成 deno {} 的安装",
+  "deno v{} needs glibc {}.{} or newer, this system has glibc {}.{}" => "deno v{} 需要 glibc {}.{} 或更新的版本，当前系统的 glibc 为 {}.{}",
+  "{}\nInstall deno v{} instead, the newest one that runs here, or set {} to install anyway" => "{}\n请改为安装可在此运行的最新版本 deno v{}，或设置 {} 以强制安装",
+  "{}\nSet {} to install anyway" => "{}\n设置 {} 以强制安装",
+  "deno v{} has no build for {}" => "deno v{} 没有 {} 的版本",
+  "No released version matches {}" => "没有匹配 {} 的已发布版本",
+  "{}, not managed by dvm" => "{}，不由 dvm 管理",
+  "There is no deno outside of dvm in PATH" => "PATH 中没有 dvm 之外的 deno",
+  "{} is not managed by dvm, pass `--force` to remove it anyway" => "{} 不由 dvm 管理，传入 `--force` 以强制删除",
+  "alias {} ({}) points at it" => "别名 {} ({}) 指向它",
+  "pinned by the project {}" => "被项目 {} 固定",
+  "it's the default version" => "它是默认版本",
+  "it's active here, from {}" => "它在这里被激活，来自 {}",
+  "Nothing needs deno v{}, it's safe to uninstall" => "没有任何地方需要 deno v{}，可以放心卸载",
+  "Uninstalling deno v{} breaks the above" => "卸载 deno v{} 会影响以上各项",
+  "Patching deno v{} from v{}" => "正在修补 deno v{}，基于 v{}",
+  "The delta to deno v{} can't be used, downloading it in full: {}" => "无法使用 deno v{} 的增量更新，改为完整下载：{}",
+  "Failed to serve {}: {}" => "无法提供 {}：{}",
+  "Failed to answer a request: {}" => "无法响应请求：{}",
+  "Serving the dvm cache on {}:{}, run `dvm registry http://<this host>:{}/` to use it" =>
+    "正在 {}:{} 上提供 dvm 缓存，运行 `dvm registry http://<本机地址>:{}/` 来使用它",
+  "the {} channel isn't allowed by the policy, only {} is" => "策略不允许 {} 渠道，只允许 {}",
+  "deno v{} is banned by the policy" => "deno v{} 已被策略禁用",
+  "deno v{} is outside of `{}` allowed by the policy" => "deno v{} 不在策略允许的 `{}` 范围内",
+  "Failed to refresh the policy {}: {}" => "无法更新策略 {}：{}",
+  "Ignoring the invalid policy {}: {}" => "忽略无效的策略 {}：{}",
+  "The provenance of {} is invalid: {}" => "{} 的来源证明无效：{}",
+  "{} has an unsigned provenance naming {} as its builder" => "{} 有一份未签名的来源证明，其中的构建者为 {}",
+  "The mise plugin has been written to {}" => "mise 插件已写入 {}",
+  "Run `mise plugins link --force deno {}`, then `mise use deno@<version>` installs through dvm" =>
+    "运行 `mise plugins link --force deno {}`，之后 `mise use deno@<版本>` 会通过 dvm 安装",
+  "The proto plugin has been written to {}" => "proto 插件已写入 {}",
+  "Add `deno = \"file://{}\"` to the [plugins] of .prototools" => "将 `deno = \"file://{}\"` 添加到 .prototools 的 [plugins] 中",
+  "No script given to run" => "没有给出要运行的脚本",
+  "dvm can't upgrade to the rc channel, pass the rc version instead" => "dvm 无法升级到 rc 渠道，请直接传入 rc 版本",
+  "`deno upgrade` would overwrite a version managed by dvm, running `dvm install` instead, set {} to let deno do it" =>
+    "`deno upgrade` 会覆盖 dvm 管理的版本，改为运行 `dvm install`，设置 {} 以让 deno 自行升级",
+  "The config already matches {}" => "配置已与 {} 一致",
+  "{} config keys have been imported" => "已导入 {} 个配置项",
+  "No release follows the channel `{}`" => "没有版本属于频道 `{}`",
+  "The channel `{}` has been pinned to {}" => "频道 `{}` 已固定到 {}",
+  "There is no {} in the current directory" => "当前目录中没有 {}",
+  "{} follows no channel, add a line like `channel = 1.x` to it" => "{} 未指定频道，请在其中添加一行如 `channel = 1.x`",
+  "{} is the newest of the channel `{}` already" => "{} 已是频道 `{}` 的最新版本",
+  "The pin has moved from {} to {}, run `dvm use` to switch to it" => "固定版本已从 {} 更新到 {}，运行 `dvm use` 切换过去",
+  "No release of the LTS line {} has been found" => "未找到 LTS 版本线 {} 的任何版本",
+  "{} publishes no checksum, refusing to update" => "{} 未发布校验和，拒绝更新",
+  "The checksum of {} is {}, {} was published" => "{} 的校验和为 {}，发布的是 {}",
+  "dvm is up to date" => "dvm 已是最新",
+  "{} has been installed to {}" => "{} 已安装到 {}",
+  "`{}` is neither `owner/name` nor the url of a repository" => "`{}` 既不是 `owner/name` 也不是仓库地址",
+  "A fork has no latest version, pass the version to install" => "分支仓库没有最新版本，请指定要安装的版本",
+  "Builds from {} can't be verified" => "无法验证来自 {} 的构建",
+  "from {}" => "来自 {}",
+  "{} builds can't run on {}" => "{} 的构建无法在 {} 上运行",
+  "The canary has no builds for other architectures" => "canary 没有其他架构的构建",
+  "deno v{} for {} is already installed" => "deno v{}（{}）已经安装",
+  "deno v{} for {} has been installed" => "deno v{}（{}）已安装",
+  "deno v{} for {} is not installed. do you want to install it?" => "deno v{}（{}）未安装，是否安装？",
+  "deno v{} for {} is not installed, run `dvm install --arch {} {}`" => "deno v{}（{}）未安装，请运行 `dvm install --arch {} {}`",
+  "Unable to find the home directory" => "找不到主目录",
+  "{} has no known completion directory, redirect `dvm completions {}` to a file it loads" => "{} 没有已知的补全目录，请将 `dvm completions {}` 重定向到它会加载的文件",
+  "The completions in {} are up to date" => "{} 中的补全已是最新",
+  "The completions have been written to {}" => "补全已写入 {}",
+  "{} loads them now" => "{} 现在会加载它们",
+  "Open a new shell to use them" => "打开新的 shell 以使用它们",
+  "dvm: {} changed, now using deno {}" => "dvm: {} 已更改，现在使用 deno {}",
+  "dvm: {} changed, deno {} is not installed yet, run `dvm install` in {}" => "dvm: {} 已更改，deno {} 尚未安装，请在 {} 中运行 `dvm install`",
+  "active" => "使用中",
+  "default" => "默认",
+  "pinned by {}" => "被 {} 固定",
+  "pinned by {} projects" => "被 {} 个项目固定",
+  "it's the active version" => "它是当前使用的版本",
+  "deno v{} is still needed, pass `--force` to remove it anyway:\n{}" => "deno v{} 仍被需要，传入 `--force` 以强制删除：\n{}",
+  "Removing deno v{} though it's still needed:\n{}" => "正在删除仍被需要的 deno v{}：\n{}",
+  "deno v{} is broken: {}" => "deno v{} 已损坏：{}",
+  "do you want to reinstall deno v{}?" => "是否重新安装 deno v{}？",
+  "deno v{} has not been activated" => "deno v{} 未被启用",
+  "Canary builds publish no checksums" => "canary 构建不发布校验和",
+  "deno v{} publishes no sha256 of {}, it can't be verified" => "deno v{} 没有发布 {} 的 sha256，无法校验",
+  "The sha256 of {} is {}, deno v{} published {}" => "{} 的 sha256 为 {}，而 deno v{} 发布的是 {}",
+  "{} of deno v{} is intact, sha256 {}" => "{}（deno v{}）完好，sha256 {}",
+  "{} reclaimed" => "已回收 {}",
+  "Failed to remove {}: {}" => "删除 {} 失败：{}",
+  "Removed {} versions, {} reclaimed" => "已删除 {} 个版本，回收 {}",
+  "Downgrading {}: {}" => "正在降级 {}：{}",
+  "{} would be downgraded: {}, pass --allow-downgrade to do it" => "{} 将被降级：{}，使用 --allow-downgrade 以执行",
+  "{} has not been downgraded" => "{} 未被降级",
+  "{} is not a dvm shim: {}" => "{} 不是 dvm 的 shim：{}",
+  "config.toml sets the registry to {}, which takes precedence, run `dvm config unset registry` to use this one" => "config.toml 将 registry 设置为 {}，它的优先级更高，运行 `dvm config unset registry` 以使用此 registry",
+  "deno v{} for {} is not installed." => "deno v{}（{}）未安装。",
+  "deno v{} for {} removed." => "deno v{}（{}）已移除。",
+  "{} only" => "仅 {}",
+  "The patched deno v{} is {}, {} was published" => "修补后的 deno v{} 为 {}，发布的是 {}",
+  "The delta to deno v{} has no published digest, downloading the full release" => "deno v{} 的增量没有发布摘要，将下载完整版本",
+  "the policy allows `{}`, which is no version range" => "策略允许的 `{}` 不是版本范围",
+  "No release of deno satisfies `{}` of {}" => "没有 deno 版本满足 `{}`（{}）",
+  "deno v{} is already installed from {}, run `dvm uninstall {}` first" => "deno v{} 已从 {} 安装，请先运行 `dvm uninstall {}`",
+  "{} is not https, a config is only imported over https" => "{} 不是 https，配置只能通过 https 导入",
+  "Apply these changes?" => "是否应用这些更改？",
+  "The config has been left unchanged" => "配置未更改",
+  "The deno built from v{} doesn't report itself as such: {}" => "从 v{} 构建的 deno 未报告为该版本：{}",
+  "Cleaning version {}" => "正在清理版本 {}",
+  "Keeping version {}, {}" => "保留版本 {}，{}",
+  "mapped from {}" => "由 {} 映射",
+  "linked by {}" => "被 {} 关联",
+  "used recently" => "最近使用过",
+  "The versions store is read-only, no version will be cleaned" => "版本仓库是只读的，不会清理任何版本",
+  // activate & deactivate
+  "Dvm has already been deactivated, exiting." => "Dvm 已经停用，退出。",
+  "Dvm is now deacvated." => "Dvm 已停用。",
+  "Removed the deno shim {}" => "已移除 deno 链接 {}",
+  "There is no deno shim at {}" => "{} 处没有 deno 链接",
+  "Forgot the global default version {} in {}" => "已移除全局默认版本 {}，位于 {}",
+  "No global default version is set" => "未设置全局默认版本",
+  "deno now resolves to {}" => "deno 现在指向 {}",
+  "No other deno is found in PATH" => "PATH 中没有找到其他 deno",
+  "{} stays in PATH with no deno in it, run `dvm activate` to bring dvm back" =>
+    "{} 仍在 PATH 中但其中已没有 deno，运行 `dvm activate` 以重新启用 dvm",
+  // doctor
+  "DVM deno bin is already set correctly." => "DVM 的 deno bin 已正确设置。",
+  "Please restart your shell of choice to take effects." => "请重启你的 shell 使其生效。",
+  "All fixes applied, DVM is ready to use." => "所有修复已应用，DVM 可以使用了。",
+  "{} is not managed by dvm, pass `--force-external` to put dvm's deno before it in PATH" =>
+    "{} 不是由 dvm 管理的，传入 `--force-external` 以在 PATH 中将 dvm 的 deno 放在它前面",
+  "Found old dvm cache of version `{}`, migrating to new dvm cache location..." =>
+    "发现旧的 dvm 版本缓存 `{}`，正在迁移到新的缓存位置...",
+  // registry
+  "Registry now set to the official registry \"{}\"" => "镜像源已设置为官方源 \"{}\"",
+  "Registry now set to the CN mirror (that provided by @justjavac) \"{}\"" =>
+    "镜像源已设置为 @justjavac 提供的中国镜像 \"{}\"",
+  "The {} is not valid URL, please starts with `http` or `https`" => "{} 不是有效的 URL，请以 `http` 或 `https` 开头",
+  "Registry will not be changed" => "镜像源不会被修改",
+  "unreachable" => "无法访问",
+  "No registry is reachable" => "所有镜像源都无法访问",
+  "Registry now set to the fastest one \"{}\"" => "镜像源已设置为最快的 \"{}\"",
+  // upgrade
+  "Upgrading {}" => "正在升级 {}",
+  "Upgrading alias {}" => "正在升级别名 {}",
+  "All aliases have been upgraded" => "所有别名已升级",
+  "{} is already the latest version" => "{} 已经是最新版本",
+  "{} is not a valid semver version or tag and will not be upgraded" => "{} 不是有效的 semver 版本或标签，不会被升级",
+  "Upgrading {} from {} to {}" => "正在将 {} 从 {} 升级到 {}",
+  // dry run
+  "Would install deno v{}" => "将安装 deno v{}",
+  "Would map {} from {} to {}" => "将把 {} 从 {} 映射到 {}",
+  "Nothing to do" => "无需任何操作",
+  "unknown" => "未知",
+  // list
+  "Failed to fetch the release dates: {}" => "获取发布日期失败：{}",
+  // current
+  "No version is active" => "当前没有启用的版本",
+  // projects
+  "No version given and there is no .dvmrc in {}" => "未指定版本，且 {} 中没有 .dvmrc",
+  "Linked {} to deno {}" => "已将 {} 关联到 deno {}",
+  "No project is linked, run `dvm link` in a project" => "没有关联的项目，请在项目中运行 `dvm link`",
+  "directory missing" => "目录不存在",
+  "not installed" => "未安装",
+  "installed" => "已安装",
+  // history
+  "No version has been activated yet" => "还没有启用过任何版本",
+  // lock
+  "the lock is for {}, this machine is {}" => "该锁文件适用于 {}，本机为 {}",
+  "deno v{} is not installed" => "deno v{} 尚未安装",
+  "the installed deno v{} has the sha256 {} instead of {}" => "已安装的 deno v{} 的 sha256 为 {}，而不是 {}",
+  "deno v{} is not the active version" => "deno v{} 不是当前使用的版本",
+  "Locked deno v{} in {}" => "已锁定 deno v{}，写入 {}",
+  "{} doesn't match the installed deno" => "{} 与已安装的 deno 不符",
+  "deno v{} matches {}" => "deno v{} 与 {} 相符",
+  // telemetry
+  "Telemetry is on, dvm reports the command name, its version, the OS and arch, and whether the command succeeded to {}, run `dvm telemetry off` to stop it" =>
+    "遥测已开启，dvm 会向 {} 报告命令名称、dvm 版本、操作系统和架构以及命令是否成功，运行 `dvm telemetry off` 以关闭",
+  "Telemetry is on, reports are sent to {}" => "遥测已开启，报告发送至 {}",
+  "Each report has the command name, the dvm version, the OS and arch, and whether it succeeded" =>
+    "每份报告包含命令名称、dvm 版本、操作系统和架构以及是否成功",
+  "Telemetry is off, set `telemetry` and `telemetry-url` with `dvm config set` to send usage reports" =>
+    "遥测已关闭，使用 `dvm config set` 设置 `telemetry` 和 `telemetry-url` 以发送使用报告",
+  "Telemetry is on but no `telemetry-url` is set, nothing is sent" => "遥测已开启，但未设置 `telemetry-url`，不会发送任何内容",
+  "Telemetry is off" => "遥测已关闭",
+  // which
+  "no installed version matches `{}`" => "没有已安装的版本匹配 `{}`",
+  "denort is not available for canary" => "canary 版本没有 denort",
+  "denort of `{}` is not installed, run `dvm install --denort {}`" =>
+    "`{}` 的 denort 尚未安装，请运行 `dvm install --denort {}`",
+  "deno `{}` is not installed" => "deno `{}` 尚未安装",
+  // rehash
+  "Removed {} since dvm is deactivated" => "dvm 已停用，已移除 {}",
+  "Relinked deno {} to {}" => "已将 deno {} 重新链接到 {}",
+  "The active version `{}` is not installed" => "当前使用的版本 `{}` 尚未安装",
+  "No active version recorded, skip relinking" => "没有记录当前使用的版本，跳过重新链接",
+  "Mapping {} -> {} is not valid, run `dvm clean`" => "映射 {} -> {} 无效，请运行 `dvm clean`",
+  "Alias {} -> {} is not a valid semver range" => "别名 {} -> {} 不是有效的 semver 范围",
+  "Removed stale stub {}" => "已移除过期的记录 {}",
+  "Rehashed, everything looks good." => "重建完成，一切正常。",
+  "Rehashed, {} problem(s) found." => "重建完成，发现 {} 个问题。",
+  // compact
+  "Compressing {} v{}" => "正在压缩 {} v{}",
+  "Decompressing {} v{}" => "正在解压 {} v{}",
+  "Reclaimed {} MB" => "已回收 {} MB",
+  // prefetch
+  "deno v{} is ready already" => "deno v{} 已准备就绪",
+  "deno v{} has been prefetched" => "deno v{} 已预先下载",
+  "{} is cached already" => "{} 已经缓存",
+  "Prefetched into {}" => "已预先下载到 {}",
+  "deno v{} has no release for {}" => "deno v{} 没有 {} 的发布包",
+};
+
+/// the locale of a `LANG` like value, e.g. `zh_CN.UTF-8`
+pub fn parse_locale(value: &str) -> Option<Locale> {
+  let value = value.trim().replace('-', "_").to_lowercase();
+  if value.is_empty() {
+    None
+  } else if value.starts_with("zh_cn") || value.starts_with("zh_hans") || value == "zh" {
+    Some(Locale::ZhCn)
+  } else {
+    Some(Locale::En)
+  }
+}
+
+/// `DVM_LANG` takes precedence over the usual locale variables
+pub fn locale() -> Locale {
+  static LOCALE: OnceLock<Locale> = OnceLock::new();
+  *LOCALE.get_or_init(|| {
+    ["DVM_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+      .iter()
+      .find_map(|name| std::env::var(name).ok().and_then(|it| parse_locale(&it)))
+      .unwrap_or_else(|| {
+        if cfg!(windows) && crate::utils::is_china_mainland() {
+          Locale::ZhCn
+        } else {
+          Locale::En
+        }
+      })
+  })
+}
+
+fn format(template: &str, args: &[&dyn Display]) -> String {
+  let mut args = args.iter();
+  let mut parts = template.split("{}");
+  let mut result = parts.next().unwrap_or_default().to_string();
+  for part in parts {
+    if let Some(arg) = args.next() {
+      result.push_str(&arg.to_string());
+    }
+    result.push_str(part);
+  }
+  result
+}
+
+pub fn translate_in(locale: Locale, msg: &'static str, args: &[&dyn Display]) -> String {
+  let template = match locale {
+    Locale::En => msg,
+    Locale::ZhCn => ZH_CN.get(msg).copied().unwrap_or(msg),
+  };
+  format(template, args)
+}
+
+pub fn translate(msg: &'static str, args: &[&dyn Display]) -> String {
+  translate_in(locale(), msg, args)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_locale() {
+    assert_eq!(parse_locale("zh_CN.UTF-8"), Some(Locale::ZhCn));
+    assert_eq!(parse_locale("zh-Hans"), Some(Locale::ZhCn));
+    assert_eq!(parse_locale("en_US.UTF-8"), Some(Locale::En));
+    assert_eq!(parse_locale(""), None);
+  }
+
+  #[test]
+  fn test_translate() {
+    assert_eq!(
+      translate_in(Locale::En, "Now using deno {}", &[&"1.28.0"]),
+      "Now using deno 1.28.0"
+    );
+    assert_eq!(
+      translate_in(Locale::ZhCn, "Now using deno {}", &[&"1.28.0"]),
+      "正在使用 deno 1.28.0"
+    );
+    assert_eq!(translate_in(Locale::ZhCn, "untranslated {}", &[&1]), "untranslated 1");
+  }
+
+  #[test]
+  fn test_placeholders_match() {
+    for (en, zh) in ZH_CN.entries() {
+      assert_eq!(en.matches("{}").count(), zh.matches("{}").count(), "{}", en);
+    }
+  }
+}
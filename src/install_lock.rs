@@ -0,0 +1,85 @@
+use crate::consts::DVM_INSTALL_LOCK_STALE_MINUTES;
+use crate::interrupt::{self, Staged};
+use crate::output;
+use crate::utils::dvm_versions;
+use anyhow::Result;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// how often a waiting install looks at the lock again
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+///
+/// held while a version is installed, so two processes sharing the versions store
+/// never unpack into the same directory, the lock file is removed when it's dropped.
+pub struct InstallLock {
+  path: PathBuf,
+  _staged: Staged,
+}
+
+fn lock_path(name: &str) -> PathBuf {
+  dvm_versions().join(format!(".install-{}.lock", name))
+}
+
+/// a lock left over by a dvm that was killed, nothing installs for that long
+fn is_stale(path: &Path) -> bool {
+  let stale = Duration::from_secs(DVM_INSTALL_LOCK_STALE_MINUTES * 60);
+  fs::metadata(path)
+    .and_then(|it| it.modified())
+    .ok()
+    .and_then(|it| SystemTime::now().duration_since(it).ok())
+    .map(|elapsed| elapsed > stale)
+    .unwrap_or(false)
+}
+
+/// take the install lock of a version, waiting for the process holding it
+pub fn acquire(name: &str) -> Result<InstallLock> {
+  let path = lock_path(name);
+  fs::create_dir_all(path.parent().unwrap())?;
+  let mut waiting = false;
+  loop {
+    match OpenOptions::new().write(true).create_new(true).open(&path) {
+      Ok(mut file) => {
+        let _staged = interrupt::stage(&path);
+        writeln!(file, "{}", std::process::id())?;
+        return Ok(InstallLock { path, _staged });
+      }
+      Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+        if is_stale(&path) {
+          output::warn(t!("Taking over the stale lock {}", path.display()));
+          fs::remove_file(&path).ok();
+          continue;
+        }
+        if !waiting {
+          output::info(t!("Waiting for another dvm to finish installing deno {}", name));
+          waiting = true;
+        }
+        thread::sleep(POLL_INTERVAL);
+      }
+      Err(err) => return Err(err.into()),
+    }
+  }
+}
+
+impl Drop for InstallLock {
+  fn drop(&mut self) {
+    fs::remove_file(&self.path).ok();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_stale() {
+    let path = std::env::temp_dir().join(format!("dvm-install-lock-{}", std::process::id()));
+    assert!(!is_stale(&path));
+    fs::write(&path, "").unwrap();
+    assert!(!is_stale(&path));
+    fs::remove_file(path).unwrap();
+  }
+}
@@ -0,0 +1,81 @@
+use crate::consts::DVM_INTERRUPTED_EXIT_CODE;
+use crate::http;
+use crate::output;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// what the running command would leave unfinished if it's interrupted now
+static STAGED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+/// held while the meta is written, ctrl-c waits for it
+static CRITICAL: Mutex<()> = Mutex::new(());
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// a path removed on ctrl-c until the guard is dropped, i.e. until the work on it is over
+pub struct Staged(PathBuf);
+
+pub fn stage(path: impl AsRef<Path>) -> Staged {
+  let path = path.as_ref().to_path_buf();
+  lock(&STAGED).push(path.clone());
+  Staged(path)
+}
+
+impl Drop for Staged {
+  fn drop(&mut self) {
+    let mut staged = lock(&STAGED);
+    if let Some(index) = staged.iter().rposition(|it| *it == self.0) {
+      staged.remove(index);
+    }
+  }
+}
+
+/// run `f` without being interrupted halfway
+pub fn critical<T>(f: impl FnOnce() -> T) -> T {
+  let _guard = lock(&CRITICAL);
+  f()
+}
+
+/// remove what's staged and exit with `DVM_INTERRUPTED_EXIT_CODE`
+pub fn exit() -> ! {
+  let _guard = lock(&CRITICAL);
+  output::warn(t!("Interrupted, cleaning up"));
+  for path in lock(&STAGED).iter().rev() {
+    let removed = if path.is_dir() {
+      fs::remove_dir_all(path)
+    } else {
+      fs::remove_file(path)
+    };
+    if removed.is_ok() {
+      output::info(t!("Removed {}", path.display()));
+    }
+  }
+  std::process::exit(DVM_INTERRUPTED_EXIT_CODE)
+}
+
+///
+/// handle ctrl-c from now on, the downloads in flight are dropped with the process,
+/// child processes get the signal from the terminal themselves.
+pub fn install() {
+  http::runtime().spawn(async {
+    if tokio::signal::ctrl_c().await.is_ok() {
+      exit();
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stage() {
+    let path = std::env::temp_dir().join(format!("dvm-stage-{}", std::process::id()));
+    let staged = stage(&path);
+    assert!(lock(&STAGED).contains(&path));
+    drop(staged);
+    assert!(!lock(&STAGED).contains(&path));
+  }
+}
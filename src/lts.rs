@@ -0,0 +1,65 @@
+use crate::config::DvmConfig;
+use crate::version::remote_versions;
+use anyhow::{anyhow, Result};
+use semver::{Version, VersionReq};
+
+///
+/// the minor lines deno maintains as LTS, oldest first, the last one is what `lts` resolves to unless `lts-line` is set.
+/// deno announces the next line with its release, a line stays LTS for the versions installed from it.
+const LINES: &[&str] = &["~2.1"];
+
+/// the range `lts` stands for, `lts-line` or the newest of the known lines
+pub fn current() -> String {
+  DvmConfig::load()
+    .lts_line
+    .map(|it| it.0)
+    .unwrap_or_else(|| LINES[LINES.len() - 1].to_string())
+}
+
+/// the channel a version was released on, `lts`, `rc` or `stable`
+pub fn channel(version: &Version) -> &'static str {
+  if !version.pre.is_empty() {
+    return "rc";
+  }
+  let current = current();
+  let is_lts = LINES
+    .iter()
+    .copied()
+    .chain([current.as_str()])
+    .any(|line| VersionReq::parse(line).map(|it| it.matches(version)).unwrap_or(false));
+  if is_lts {
+    "lts"
+  } else {
+    "stable"
+  }
+}
+
+/// the newest release of the current LTS line
+pub fn newest(versions: &[String]) -> Option<Version> {
+  let current = VersionReq::parse(&current()).ok()?;
+  versions
+    .iter()
+    .filter_map(|it| Version::parse(it).ok())
+    .filter(|it| current.matches(it))
+    .max()
+}
+
+/// the version `dvm install lts` installs
+pub fn latest() -> Result<Version> {
+  newest(&remote_versions()?).ok_or_else(|| anyhow!(t!("No release of the LTS line {} has been found", current())))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_channel() {
+    let channel = |it: &str| channel(&Version::parse(it).unwrap());
+    assert_eq!(channel("2.1.4"), "lts");
+    assert_eq!(channel("2.2.0"), "stable");
+    assert_eq!(channel("2.1.0-rc.1"), "rc");
+    let versions = ["2.1.4", "2.1.10", "2.2.0", "2.1.11-rc.1"].map(String::from);
+    assert_eq!(newest(&versions), Version::parse("2.1.10").ok());
+  }
+}
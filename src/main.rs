@@ -1,9 +1,34 @@
 extern crate core;
 
+#[macro_use]
+mod i18n;
+
+mod archive;
+mod archive_cache;
+mod assets;
 mod cli;
 mod commands;
+mod config;
 mod consts;
+mod delta;
+mod glibc;
+mod hooks;
+mod http;
+mod http_cache;
+mod http_trace;
+mod install_lock;
+mod interrupt;
+mod lts;
 mod meta;
+mod output;
+mod plan;
+mod policy;
+mod project;
+mod provenance;
+mod signature;
+mod source;
+mod telemetry;
+mod timings;
 mod utils;
 pub mod version;
 
@@ -11,6 +36,7 @@ use cfg_if::cfg_if;
 use clap::CommandFactory;
 
 use cli::{Cli, Commands};
+use commands::prefetch_targets::resolve_arch;
 use meta::DvmMeta;
 use utils::dvm_root;
 
@@ -28,25 +54,167 @@ cfg_if! {
 }
 
 pub fn main() {
+  output::init_color(cli::ColorChoice::Auto);
+  // the deno of the dvm bin directory with `activation = "shim"`, the arguments are deno's
+  if commands::exec::is_shim() {
+    let mut meta = DvmMeta::new();
+    http::set_registry_auth(&meta.registry());
+    if let Err(err) = commands::exec::shim(&mut meta, std::env::args().skip(1).collect()) {
+      output::report(err);
+      std::process::exit(1);
+    }
+    return;
+  }
+  cli::init_dvm_dir();
+  if let Err(err) = utils::check_dvm_root() {
+    output::report(err);
+    std::process::exit(1);
+  }
+
+  if cfg!(windows) {
+    commands::use_version::remove_stale_bins();
+  }
+
+  // run on every prompt by the shell hook, it skips the cli parsing and loads the meta only if needed
+  if std::env::args().nth(1).as_deref() == Some("resolve-for-cwd") {
+    commands::hook::resolve_for_cwd().ok();
+    return;
+  }
+
   let mut meta = DvmMeta::new();
+  // `dvm exec` is handled while parsing, the installs it starts need the credentials as well
+  http::set_registry_auth(&meta.registry());
 
   let Ok(cli) = cli::cli_parse(&mut meta) else {
     return;
   };
 
+  output::init_color(cli.color);
+  output::init_progress(cli.progress);
+  if cli.timings {
+    timings::enable();
+  }
+  if cli.trace_http {
+    http_trace::enable();
+  }
+  http::set_limit_rate(cli.limit_rate);
+  // nothing is spawned from prompt renders and path lookups
+  let is_foreground = !matches!(
+    cli.command,
+    Commands::PrefetchLatest
+      | Commands::Current { .. }
+      | Commands::Root { .. }
+      | Commands::CacheDir
+      | Commands::Hook { .. }
+      | Commands::Env { .. }
+      | Commands::ResolveForCwd
+      | Commands::LspHelper
+  );
+  // the report is of the command before it
+  let records = is_foreground && !matches!(cli.command, Commands::Report { .. });
+  if is_foreground {
+    interrupt::install();
+    commands::prefetch_latest::spawn_if_due();
+  }
+
   let result = match cli.command {
-    Commands::Completions { shell } => commands::completions::exec(&mut Cli::command(), shell),
+    Commands::Completions { shell, install } => commands::completions::exec(&mut Cli::command(), shell, install),
     Commands::Info => commands::info::exec(),
-    Commands::Install { no_use, version } => commands::install::exec(&meta, no_use, version),
-    Commands::List => commands::list::exec(),
-    Commands::ListRemote => commands::list::exec_remote(),
-    Commands::Uninstall { version } => commands::uninstall::exec(version),
-    Commands::Use { version, local } => commands::use_version::exec(&mut meta, version, local),
+    Commands::Root { path, json } => commands::paths::exec(path, json),
+    Commands::Hook { shell, eval } => commands::hook::exec(shell, eval),
+    Commands::Env { shell } => commands::env::exec(shell),
+    Commands::ShellPlugin { shell, dir } => commands::shell_plugin::exec(&mut Cli::command(), shell, dir),
+    Commands::ResolveForCwd => commands::hook::resolve_for_cwd(),
+    Commands::CacheDir => commands::paths::exec(Some(cli::DvmPath::Cache), false),
+    Commands::Current { prompt } => commands::current::exec(&meta, prompt),
+    Commands::Install {
+      dry_run: true,
+      json,
+      version,
+      ..
+    } => commands::install::dry_run(&meta, version, json),
+    Commands::Install {
+      verify_only: true,
+      require_signature,
+      target,
+      version,
+      ..
+    } => {
+      signature::set_required(require_signature);
+      commands::install::verify_only(&meta, version, target)
+    }
+    Commands::Install {
+      no_use,
+      github_output,
+      denort,
+      require_signature,
+      build_from_source,
+      repo,
+      arch,
+      version,
+      ..
+    } => {
+      signature::set_required(require_signature);
+      let installed = if let Some(arch) = arch {
+        resolve_arch(&arch).and_then(|target| commands::install::install_arch(&mut meta, no_use, version, target))
+      } else if let Some(repo) = repo {
+        commands::install::install_from_repo(&mut meta, &repo, no_use, version)
+      } else if build_from_source {
+        commands::build_from_source::exec(&meta, no_use, version)
+      } else {
+        commands::install::install(&meta, no_use, denort, version)
+      };
+      installed.and_then(|(version, exe_path)| {
+        meta.tag_channel(&version);
+        meta.save();
+        if github_output {
+          commands::ci::github_output(&version, &exe_path)
+        } else {
+          Ok(())
+        }
+      })
+    }
+    Commands::Which { denort, version } => commands::which::exec(&meta, denort, version),
+    Commands::Why { version } => commands::why::exec(&mut meta, version),
+    Commands::History { limit } => commands::history::exec(&meta, limit),
+    Commands::Rollback => commands::rollback::exec(&mut meta),
+    Commands::List => commands::list::exec(&mut meta),
+    Commands::ListRemote { lts } => commands::list::exec_remote(lts),
+    Commands::Search { pattern } => commands::list::exec_search(pattern),
+    Commands::Uninstall { version, force, arch } => arch.map(|it| resolve_arch(&it)).transpose().and_then(|target| {
+      let target = target.filter(|it| *it != commands::install::TARGET);
+      commands::uninstall::exec(&mut meta, version, force, target)
+    }),
+    Commands::Use {
+      version,
+      local,
+      save,
+      arch,
+    } => arch.map(|it| resolve_arch(&it)).transpose().and_then(|target| {
+      let target = target.filter(|it| *it != commands::install::TARGET);
+      commands::use_version::exec_for(&mut meta, version, local, save, target)
+    }),
+    Commands::Link { version } => commands::projects::link(&mut meta, version),
+    Commands::Projects => commands::projects::list(&meta),
     Commands::Alias { command } => commands::alias::exec(&mut meta, command),
+    Commands::VersionEnv { command } => commands::version_env::exec(&mut meta, command),
     Commands::Activate => commands::activate::exec(&mut meta),
-    Commands::Deactivate => commands::deactivate::exec(),
-    Commands::Doctor => commands::doctor::exec(&mut meta),
-    Commands::Upgrade { alias } => commands::upgrade::exec(&mut meta, alias),
+    Commands::Deactivate { global } => commands::deactivate::exec(global),
+    Commands::Doctor { force_external } => commands::doctor::exec(&mut meta, force_external),
+    Commands::Upgrade {
+      dry_run: true,
+      json,
+      lts,
+      allow_downgrade,
+      alias,
+    } => commands::upgrade::plan(&meta, commands::upgrade::alias_of(lts, alias), allow_downgrade)
+      .map(|plan| plan.print(json)),
+    Commands::Upgrade {
+      lts,
+      allow_downgrade,
+      alias,
+      ..
+    } => commands::upgrade::exec(&mut meta, commands::upgrade::alias_of(lts, alias), allow_downgrade),
     Commands::Exec {
       command: _,
       deno_version: _,
@@ -54,12 +222,54 @@ pub fn main() {
       /* unused */
       Ok(())
     }
+    Commands::MigrateLayout => commands::migrate_layout::exec(),
     Commands::Clean => commands::clean::exec(&mut meta),
-    Commands::Registry { registry } => commands::registry::exec(&mut meta, registry),
+    Commands::Compact { days } => commands::compact::exec(&meta, days),
+    Commands::Rehash => commands::rehash::exec(&meta),
+    Commands::PrefetchLatest => commands::prefetch_latest::exec(&meta),
+    Commands::PrefetchTargets { version, targets } => commands::prefetch_targets::exec(&meta, version, targets),
+    Commands::Ci { command } => commands::ci::exec(&meta, command),
+    Commands::Vscode { version } => commands::editor::exec(&meta, cli::Editor::Vscode, version),
+    Commands::Editor { editor, version } => commands::editor::exec(&meta, editor, version),
+    Commands::Dockerfile {
+      version,
+      target,
+      sha256,
+      snippet,
+    } => commands::dockerfile::exec(&meta, version, target, sha256, snippet),
+    Commands::Devcontainer { version, dir } => commands::devcontainer::exec(&meta, version, dir),
+    Commands::Stats { json } => commands::stats::exec(&meta, json),
+    Commands::Lock { command } => commands::lock::exec(&meta, command),
+    Commands::Config { command } => commands::config::exec(command),
+    Commands::Telemetry { command } => telemetry::exec(command),
+    Commands::LspHelper => commands::lsp_helper::exec(),
+    Commands::Mise { command } => commands::mise::exec(&mut meta, command),
+    Commands::ProtoPlugin { output } => commands::proto_plugin::exec(&meta, output),
+    Commands::Sbom { version, format } => commands::sbom::exec(&meta, version, format),
+    Commands::Serve { host, port } => commands::serve::exec(&meta, host, port),
+    Commands::SelfUpdate { channel } => commands::self_update::exec(channel),
+    Commands::UpdatePin => commands::update_pin::exec(&meta),
+    Commands::Report { json } => commands::report::exec(&meta, json),
+    Commands::Registry {
+      command: Some(cli::RegistryCommands::Test { save }),
+      ..
+    } => commands::registry::test(&mut meta, save),
+    Commands::Registry { registry, .. } => commands::registry::exec(&mut meta, registry),
   };
 
+  if is_foreground && result.is_ok() && !output::is_json() {
+    commands::clean::auto_clean_if_due(&mut meta);
+  }
+
+  if records {
+    commands::report::record(&result);
+  }
+  timings::print();
+  if is_foreground {
+    telemetry::report(result.is_ok());
+  }
   if let Err(err) = result {
-    eprintln!("\x1b[31merror:\x1b[39m: {}", err);
+    output::report(err);
     std::process::exit(1);
   }
 }
@@ -1,18 +1,32 @@
-use crate::consts::{DVM_CACHE_INVALID_TIMEOUT, REGISTRY_OFFICIAL};
-use crate::utils::{deno_version_path, dvm_root, dvm_versions, now};
-use crate::version::VersionArg;
+use crate::commands::compact::is_installed;
+use crate::config::DvmConfig;
+use crate::consts::{DVM_CONFIG_FILENAME, DVM_HISTORY_LIMIT, REGISTRY_OFFICIAL};
+use crate::project::ProjectConfig;
+use crate::utils::{best_version, dvm_root, dvm_versions, is_versions_writable, now, stub_path};
+use crate::version::{local_versions, VersionArg};
+use crate::{interrupt, lts};
 use colored::Colorize;
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::fs::{create_dir_all, read_to_string, write};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::{create_dir_all, read_to_string, rename, write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-pub const DEFAULT_ALIAS: phf::Map<&'static str, &'static str> = phf::phf_map! {
-  "latest" => "*"
-};
+/// the aliases dvm predefines
+pub const DEFAULT_ALIAS: &[&str] = &["latest", "lts"];
+
+/// the range a predefined alias stands for, `lts` follows the current LTS line
+pub fn default_alias(name: &str) -> Option<String> {
+  match name {
+    "latest" => Some("*".to_string()),
+    "lts" => Some(lts::current()),
+    _ => None,
+  }
+}
 
 pub trait ToVersionReq {
+  #[allow(dead_code)]
   fn to_version_req(&self) -> VersionReq;
   fn try_to_version_req(&self) -> anyhow::Result<VersionReq>;
 }
@@ -66,12 +80,79 @@ impl ToVersionReq for Alias {
   }
 }
 
+/// an environment variable injected when launching versions that match `required`
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize, Debug)]
+pub struct VersionEnv {
+  pub required: String,
+  pub name: String,
+  pub value: String,
+}
+
+impl ToVersionReq for VersionEnv {
+  fn to_version_req(&self) -> VersionReq {
+    VersionReq::from_str(&self.required).expect("VersionEnv::required is not a valid VersionReq")
+  }
+
+  fn try_to_version_req(&self) -> anyhow::Result<VersionReq> {
+    VersionReq::from_str(&self.required).map_err(|err| anyhow::anyhow!(err))
+  }
+}
+
+/// a version made active by `dvm use`
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize, Debug)]
+pub struct HistoryEntry {
+  pub version: String,
+  /// what was asked for, e.g. an alias or a range
+  pub required: String,
+  /// unix time in seconds
+  pub time: u64,
+  /// the directory `dvm use` ran in
+  pub dir: String,
+}
+
+/// a project registered by `dvm link` or `dvm use --save`
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize, Debug)]
+pub struct ProjectPin {
+  pub dir: String,
+  pub version: String,
+}
+
+impl ProjectPin {
+  /// a project stays a reference only while its directory is there
+  pub fn exists(&self) -> bool {
+    Path::new(&self.dir).is_dir()
+  }
+}
+
 #[derive(Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct DvmMeta {
   #[serde(default = "default_registry")]
   pub registry: String,
   pub versions: Vec<VersionMapping>,
   pub alias: Vec<Alias>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub env: Vec<VersionEnv>,
+  /// versions referenced by projects, `clean` keeps them
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub projects: Vec<ProjectPin>,
+  /// oldest first, at most `DVM_HISTORY_LIMIT` entries
+  #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+  pub history: VecDeque<HistoryEntry>,
+  /// the mappings as they were before the last upgrade, `dvm rollback` puts them back
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub replaced: Vec<VersionMapping>,
+  /// unix time in seconds a version was last activated or run through `dvm exec`
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub last_used: BTreeMap<String, u64>,
+  /// the channel each installed version came from, `lts`, `rc` or `stable`
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub channels: BTreeMap<String, String>,
+  /// the repository of versions installed from a fork with `dvm install --repo`
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub origins: BTreeMap<String, String>,
+  /// the other architectures each version is installed for, by triple, the native build isn't listed
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub arches: BTreeMap<String, BTreeSet<String>>,
 }
 
 pub fn default_registry() -> String {
@@ -79,6 +160,14 @@ pub fn default_registry() -> String {
 }
 
 impl DvmMeta {
+  /// the registry releases are downloaded from, the `registry` config key takes precedence over `dvm registry`
+  pub fn registry(&self) -> String {
+    DvmConfig::load()
+      .registry
+      .map(String::from)
+      .unwrap_or_else(|| self.registry.clone())
+  }
+
   pub fn path() -> PathBuf {
     let mut meta = dvm_root();
     meta.push(Path::new("dvm-metadata.json"));
@@ -94,7 +183,7 @@ impl DvmMeta {
         if let Ok(mut config) = config {
           let mut i = 0;
           while i < config.versions.len() {
-            if !deno_version_path(&Version::parse(&config.versions[i].current).unwrap()).exists() {
+            if !is_installed(&Version::parse(&config.versions[i].current).unwrap()) {
               config.versions.remove(i);
             } else {
               i += 1;
@@ -110,34 +199,38 @@ impl DvmMeta {
     config
   }
 
-  pub fn clean_files(&self) {
-    let cache_folder = dvm_versions();
-    if let Ok(dir) = cache_folder.read_dir() {
-      for entry in dir.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-          let name = path.file_name().unwrap().to_str().unwrap();
-
-          // it's been pointed by dvm versions
-          if self.versions.iter().any(|it| it.current == name) {
-            continue;
-          }
-
-          // it's not been outdated
-          let stub = path.join(".dvmstub");
-          if stub.exists() && stub.is_file() {
-            let content = std::fs::read_to_string(stub).expect("read stub file failed");
-            let content: u128 = content.parse().expect("parse stub file failed");
-            if content > now() - DVM_CACHE_INVALID_TIMEOUT {
-              continue;
-            }
-          }
+  /// the installed versions `clean` removes, the ones kept are printed with why
+  pub fn cleanable(&self) -> Vec<String> {
+    if !is_versions_writable() {
+      println!("{}", t!("The versions store is read-only, no version will be cleaned"));
+      return Vec::new();
+    }
 
-          println!("Cleaning version {}", name.bright_black());
-          std::fs::remove_dir_all(path).unwrap();
-        }
+    let retention = DvmConfig::load().retention_millis();
+    let mut cleanable = Vec::new();
+    let entries = dvm_versions().read_dir().into_iter().flatten().flatten();
+    // versions are always utf-8, anything else isn't ours, the dot directories are versions being removed
+    let names = entries
+      .filter(|it| it.path().is_dir())
+      .filter_map(|it| it.file_name().into_string().ok())
+      .filter(|it| !it.starts_with('.'));
+    for name in names {
+      match self.keep_reason(&name, retention) {
+        Some(reason) => println!("{}", t!("Keeping version {}, {}", name.bright_black(), reason)),
+        None => cleanable.push(name),
       }
     }
+    cleanable
+  }
+
+  /// drop what's recorded about versions that have been removed
+  pub fn forget_versions(&mut self, names: &[String]) {
+    for name in names {
+      self.last_used.remove(name);
+      self.channels.remove(name);
+      self.origins.remove(name);
+      self.arches.remove(name);
+    }
   }
 
   ///
@@ -145,6 +238,7 @@ impl DvmMeta {
   ///   `required` is either a semver range or a alias to a semver rage
   ///   `current` is the current directory that the deno located in
   pub fn set_version_mapping(&mut self, required: String, current: String) {
+    self.tag_channel(&current);
     let result = self.versions.iter().position(|it| it.required == required);
     if let Some(index) = result {
       self.versions[index] = VersionMapping { required, current };
@@ -183,12 +277,12 @@ impl DvmMeta {
   /// including predefined aliases
   pub fn list_alias(&self) -> Vec<Alias> {
     let mut alias = self.alias.clone();
-    for (k, v) in DEFAULT_ALIAS.into_iter() {
+    for name in DEFAULT_ALIAS {
       alias.insert(
         0,
         Alias {
-          name: k.to_string(),
-          required: v.to_string(),
+          name: name.to_string(),
+          required: default_alias(name).unwrap(),
         },
       );
     }
@@ -200,7 +294,7 @@ impl DvmMeta {
   ///   name is alias name
   ///   required is a semver range
   pub fn set_alias(&mut self, name: String, required: String) {
-    if DEFAULT_ALIAS.contains_key(name.as_str()) {
+    if DEFAULT_ALIAS.contains(&name.as_str()) {
       return;
     }
     let result = self.alias.iter().position(|it| it.name == name);
@@ -213,14 +307,21 @@ impl DvmMeta {
     self.save_and_reload();
   }
 
+  /// everything a version argument can name locally, i.e. aliases and installed versions
+  pub fn version_names(&self) -> Vec<String> {
+    let mut names = self.list_alias().into_iter().map(|it| it.name).collect::<Vec<_>>();
+    names.extend(local_versions());
+    names
+  }
+
   pub fn has_alias(&self, name: &str) -> bool {
     self.get_alias(name).is_some()
   }
 
   /// get the semver range of alias
   pub fn get_alias(&self, name: &str) -> Option<VersionArg> {
-    if DEFAULT_ALIAS.contains_key(name) {
-      VersionArg::from_str(DEFAULT_ALIAS[name]).ok()
+    if let Some(required) = default_alias(name) {
+      VersionArg::from_str(&required).ok()
     } else {
       self
         .alias
@@ -240,6 +341,37 @@ impl DvmMeta {
     self.save_and_reload();
   }
 
+  /// set an environment variable for versions matching `required`
+  pub fn set_version_env(&mut self, required: String, name: String, value: String) {
+    let result = self
+      .env
+      .iter()
+      .position(|it| it.required == required && it.name == name);
+    if let Some(index) = result {
+      self.env[index].value = value;
+    } else {
+      self.env.push(VersionEnv { required, name, value });
+    }
+
+    self.save_and_reload();
+  }
+
+  /// delete an environment variable of versions matching `required`
+  pub fn delete_version_env(&mut self, required: &str, name: &str) {
+    self.env.retain(|it| !(it.required == required && it.name == name));
+    self.save_and_reload();
+  }
+
+  /// environment variables to inject when launching the given version
+  pub fn version_env(&self, version: &Version) -> Vec<(String, String)> {
+    self
+      .env
+      .iter()
+      .filter(|it| it.try_to_version_req().map(|req| req.matches(version)).unwrap_or(false))
+      .map(|it| (it.name.clone(), it.value.clone()))
+      .collect()
+  }
+
   pub fn resolve_version_req(&self, required: &str) -> VersionArg {
     if self.has_alias(required) {
       self.get_alias(required).unwrap()
@@ -248,11 +380,156 @@ impl DvmMeta {
     }
   }
 
+  ///
+  /// resolve a version, alias or range to an installed version without network access,
+  /// an existing mapping wins over the best installed match.
+  pub fn resolve_local_version(&self, required: &str) -> Option<Version> {
+    if let Some(current) = self.get_version_mapping(required) {
+      return Version::parse(&current).ok();
+    }
+    match self.resolve_version_req(required) {
+      VersionArg::Exact(v) => Some(v),
+      VersionArg::Range(r) => {
+        let versions = local_versions();
+        best_version(versions.iter().map(AsRef::as_ref), r)
+      }
+    }
+  }
+
+  /// why `clean` keeps a version, `None` if nothing needs it anymore
+  fn keep_reason(&self, name: &str, retention: u128) -> Option<String> {
+    // it's been pointed by dvm versions
+    if let Some(mapping) = self.versions.iter().find(|it| it.current == name) {
+      return Some(t!("mapped from {}", mapping.required));
+    }
+
+    // a linked project still needs it
+    if let Some(project) = self.projects.iter().find(|it| it.version == name && it.exists()) {
+      return Some(t!("linked by {}", project.dir));
+    }
+
+    // it's not been outdated
+    match self.last_used_time(name) {
+      Some(time) if time as u128 * 1000 > now().saturating_sub(retention) => Some(t!("used recently")),
+      _ => None,
+    }
+  }
+
+  ///
+  /// follow the `.dvmrc` of the linked projects, so a project whose pin changed since it was linked
+  /// protects the version it needs now, the projects whose directory is gone are forgotten.
+  pub fn resolve_project_pins(&mut self) {
+    let projects = std::mem::take(&mut self.projects);
+    self.projects = projects
+      .into_iter()
+      .filter(ProjectPin::exists)
+      .map(|mut project| {
+        let required = read_to_string(Path::new(&project.dir).join(DVM_CONFIG_FILENAME))
+          .ok()
+          .and_then(|it| ProjectConfig::from_str(&it).ok()?.version);
+        if let Some(version) = required.and_then(|it| self.resolve_local_version(&it)) {
+          project.version = version.to_string();
+        }
+        project
+      })
+      .collect();
+    self.save();
+  }
+
+  /// record the version the project in `dir` is pinned to, replacing its previous one
+  pub fn set_project_pin(&mut self, dir: String, version: String) {
+    self.projects.retain(|it| it.dir != dir);
+    self.projects.push(ProjectPin { dir, version });
+    self.save();
+  }
+
+  fn push_history(&mut self, entry: HistoryEntry) {
+    self.history.push_back(entry);
+    while self.history.len() > DVM_HISTORY_LIMIT {
+      self.history.pop_front();
+    }
+  }
+
+  /// record that `version` has been made active, asked for as `required`
+  /// the last activation of another version than the current one
+  pub fn previous_activation(&self) -> Option<&HistoryEntry> {
+    let current = self.history.back()?;
+    self.history.iter().rev().find(|it| it.version != current.version)
+  }
+
+  /// remember what an upgrade moved, replacing what the upgrade before it did
+  pub fn set_replaced_mappings(&mut self, replaced: Vec<VersionMapping>) {
+    self.replaced = replaced;
+    self.save();
+  }
+
+  pub fn record_activation(&mut self, version: &str, required: &str) {
+    let dir = std::env::current_dir()
+      .map(|it| it.to_string_lossy().to_string())
+      .unwrap_or_default();
+    let time = (now() / 1000) as u64;
+    self.push_history(HistoryEntry {
+      version: version.to_string(),
+      required: required.to_string(),
+      time,
+      dir,
+    });
+    self.last_used.insert(version.to_string(), time);
+    self.tag_channel(version);
+    self.save();
+  }
+
+  /// remember the fork a version was installed from
+  pub fn set_origin(&mut self, version: &str, repo: &str) {
+    self.origins.insert(version.to_string(), repo.to_string());
+    self.save();
+  }
+
+  /// remember a version is installed for another architecture too
+  pub fn add_arch(&mut self, version: &str, target: &str) {
+    self
+      .arches
+      .entry(version.to_string())
+      .or_default()
+      .insert(target.to_string());
+    self.save();
+  }
+
+  /// remember the channel of an installed version for `dvm list`, canary has none
+  pub fn tag_channel(&mut self, version: &str) {
+    if let Ok(parsed) = Version::parse(version) {
+      self
+        .channels
+        .insert(version.to_string(), lts::channel(&parsed).to_string());
+    }
+  }
+
+  /// record that `version` has been run through dvm
+  pub fn record_use(&mut self, version: &str) {
+    self.last_used.insert(version.to_string(), (now() / 1000) as u64);
+    self.save();
+  }
+
+  ///
+  /// the unix time in seconds a version was used last,
+  /// versions not used since the meta records it fall back to their stub.
+  pub fn last_used_time(&self, version: &str) -> Option<u64> {
+    self.last_used.get(version).copied().or_else(|| {
+      let stub = read_to_string(stub_path(version)).ok()?;
+      Some((stub.trim().parse::<u128>().ok()? / 1000) as u64)
+    })
+  }
+
   /// reload from disk
   pub fn reload(&mut self) {
     let new = DvmMeta::new();
     self.versions = new.versions;
     self.alias = new.alias;
+    self.env = new.env;
+    self.projects = new.projects;
+    self.history = new.history;
+    self.replaced = new.replaced;
+    self.last_used = new.last_used;
   }
 
   /// write to disk
@@ -262,7 +539,12 @@ impl DvmMeta {
     if !dir_path.exists() {
       create_dir_all(dir_path).unwrap();
     }
-    write(file_path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+    // through a temporary file, so ctrl-c never leaves a truncated meta behind
+    let partial = file_path.with_extension("json.partial");
+    interrupt::critical(|| {
+      write(&partial, serde_json::to_string_pretty(self).unwrap()).unwrap();
+      rename(partial, file_path).unwrap();
+    });
   }
 
   pub fn save_and_reload(&mut self) {
@@ -277,6 +559,14 @@ impl Default for DvmMeta {
       registry: REGISTRY_OFFICIAL.to_string(),
       versions: vec![],
       alias: vec![],
+      env: vec![],
+      projects: vec![],
+      history: VecDeque::new(),
+      replaced: vec![],
+      last_used: BTreeMap::new(),
+      channels: BTreeMap::new(),
+      origins: BTreeMap::new(),
+      arches: BTreeMap::new(),
     }
   }
 }
@@ -330,6 +620,100 @@ mod tests {
         )
   }
 
+  #[test]
+  fn test_version_env() {
+    let conf = DvmMeta {
+      env: vec![
+        VersionEnv {
+          required: "<1.20".to_string(),
+          name: "DENO_CERT".to_string(),
+          value: "/etc/ca.pem".to_string(),
+        },
+        VersionEnv {
+          required: "*".to_string(),
+          name: "NO_COLOR".to_string(),
+          value: "1".to_string(),
+        },
+      ],
+      ..DvmMeta::default()
+    };
+    assert_eq!(
+      conf.version_env(&Version::parse("1.19.0").unwrap()),
+      vec![
+        ("DENO_CERT".to_string(), "/etc/ca.pem".to_string()),
+        ("NO_COLOR".to_string(), "1".to_string())
+      ]
+    );
+    assert_eq!(
+      conf.version_env(&Version::parse("1.28.0").unwrap()),
+      vec![("NO_COLOR".to_string(), "1".to_string())]
+    );
+  }
+
+  #[test]
+  fn test_keep_reason() {
+    let conf = DvmMeta {
+      versions: vec![VersionMapping {
+        required: "~1.28".to_string(),
+        current: "1.28.3".to_string(),
+      }],
+      projects: vec![
+        ProjectPin {
+          dir: "/".to_string(),
+          version: "1.27.0".to_string(),
+        },
+        ProjectPin {
+          dir: "/nonexistent/project".to_string(),
+          version: "1.26.0".to_string(),
+        },
+      ],
+      ..DvmMeta::default()
+    };
+    assert_eq!(conf.keep_reason("1.28.3", 0), Some(t!("mapped from {}", "~1.28")));
+    assert_eq!(conf.keep_reason("1.27.0", 0), Some(t!("linked by {}", "/")));
+    assert_eq!(conf.keep_reason("1.26.0", 0), None);
+  }
+
+  #[test]
+  fn test_previous_activation() {
+    let entry = |version: &str| HistoryEntry {
+      version: version.to_string(),
+      required: "latest".to_string(),
+      time: 0,
+      dir: "/".to_string(),
+    };
+    let mut conf = DvmMeta::default();
+    assert_eq!(conf.previous_activation(), None);
+    conf.history = VecDeque::from([entry("1.27.0"), entry("1.28.0"), entry("1.28.0")]);
+    assert_eq!(conf.previous_activation(), Some(&entry("1.27.0")));
+    conf.history = VecDeque::from([entry("1.28.0")]);
+    assert_eq!(conf.previous_activation(), None);
+  }
+
+  #[test]
+  fn test_last_used_time() {
+    let mut conf = DvmMeta::default();
+    conf.last_used.insert("1.28.0".to_string(), 1_700_000_000);
+    assert_eq!(conf.last_used_time("1.28.0"), Some(1_700_000_000));
+    assert_eq!(conf.keep_reason("1.28.0", 0), None);
+    assert_eq!(conf.keep_reason("1.28.0", u128::MAX), Some(t!("used recently")));
+  }
+
+  #[test]
+  fn test_history_is_bounded() {
+    let mut conf = DvmMeta::default();
+    for time in 0..DVM_HISTORY_LIMIT as u64 + 5 {
+      conf.push_history(HistoryEntry {
+        version: "1.28.0".to_string(),
+        required: "latest".to_string(),
+        time,
+        dir: "/".to_string(),
+      });
+    }
+    assert_eq!(conf.history.len(), DVM_HISTORY_LIMIT);
+    assert_eq!(conf.history.front().unwrap().time, 5);
+  }
+
   #[test]
   fn test_parse_valid() {
     let raw = json!(
@@ -0,0 +1,164 @@
+use crate::cli::{ColorChoice, ProgressFormat};
+use crate::utils::did_you_mean;
+use colored::Colorize;
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+///
+/// a step of an install for the tools wrapping dvm, printed as one json object per line:
+///
+/// ```plain
+/// {"event":"download-progress","url":"https://dl.deno.land/...","downloaded":1048576,"total":35651584}
+/// ```
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event<'a> {
+  Resolve {
+    version: &'a str,
+  },
+  DownloadStart {
+    url: &'a str,
+    total: Option<u64>,
+  },
+  DownloadProgress {
+    url: &'a str,
+    downloaded: u64,
+    total: Option<u64>,
+  },
+  Extract {
+    archive: &'a Path,
+  },
+  Verify {
+    path: &'a Path,
+  },
+  Done {
+    version: &'a str,
+    path: &'a Path,
+  },
+}
+
+///
+/// decide whether to style the output,
+/// `auto` turns colors off for `NO_COLOR` and when stdout isn't a terminal.
+pub fn init_color(choice: ColorChoice) {
+  let enabled = match choice {
+    ColorChoice::Always => true,
+    ColorChoice::Never => false,
+    ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal(),
+  };
+  colored::control::set_override(enabled);
+}
+
+/// `json` keeps stdout for the events, the human messages go to stderr then
+pub fn init_progress(format: ProgressFormat) {
+  JSON_PROGRESS.store(matches!(format, ProgressFormat::Json), Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+  JSON_PROGRESS.load(Ordering::Relaxed)
+}
+
+/// the stdout of a child process, kept out of the way of the events
+pub fn passthrough() -> Stdio {
+  if is_json() {
+    Stdio::from(std::io::stderr())
+  } else {
+    Stdio::inherit()
+  }
+}
+
+pub fn event(event: Event) {
+  if JSON_PROGRESS.load(Ordering::Relaxed) {
+    println!("{}", serde_json::to_string(&event).unwrap());
+  }
+}
+
+/// a message about what's going on, out of the way of the events
+pub fn info(msg: impl Display) {
+  if JSON_PROGRESS.load(Ordering::Relaxed) {
+    eprintln!("{}", msg);
+  } else {
+    println!("{}", msg);
+  }
+}
+
+pub fn success(msg: impl Display) {
+  info(msg.to_string().green());
+}
+
+pub fn warn(msg: impl Display) {
+  eprintln!("{}", msg.to_string().yellow());
+}
+
+pub fn error(msg: impl Display) {
+  eprintln!("{}", msg.to_string().red());
+}
+
+/// the final error of a command
+pub fn report(err: impl Display) {
+  eprintln!("{} {}", format!("{}:", t!("error")).red().bold(), err);
+}
+
+/// a version worth the reader's attention, e.g. the one in use
+pub fn highlight(version: &str) -> String {
+  version.bright_green().bold().to_string()
+}
+
+///
+/// a diff-like summary of the upgraded aliases, i.e. `(alias, from, to)`:
+///
+/// ```plain
+/// - latest 1.27.0
+/// + latest 1.28.0
+/// ```
+pub fn upgrade_summary(changes: &[(String, String, String)]) {
+  for (alias, from, to) in changes {
+    println!("{}", format!("- {} {}", alias, from).red());
+    println!("{}", format!("+ {} {}", alias, to).green());
+  }
+}
+
+/// the closest matches of a name that wasn't found, e.g. `Did you mean 1.28.0?`
+pub fn suggestion(input: &str, candidates: &[String]) -> Option<String> {
+  let matches = did_you_mean(input, candidates.iter().map(AsRef::as_ref));
+  (!matches.is_empty()).then(|| t!("Did you mean {}?", matches.join(", ")))
+}
+
+/// hint the closest matches after a "not found" error
+pub fn suggest(input: &str, candidates: &[String]) {
+  if let Some(hint) = suggestion(input, candidates) {
+    eprintln!("{}", hint.bright_black());
+  }
+}
+
+/// an error message followed by the closest matches, if any
+pub fn with_suggestion(msg: String, input: &str, candidates: &[String]) -> String {
+  match suggestion(input, candidates) {
+    Some(hint) => format!("{}\n{}", msg, hint),
+    None => msg,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_json() {
+    let event = Event::DownloadProgress {
+      url: "https://dl.deno.land/release/v1.28.0/deno.zip",
+      downloaded: 1024,
+      total: None,
+    };
+    assert_eq!(
+      serde_json::to_string(&event).unwrap(),
+      r#"{"event":"download-progress","url":"https://dl.deno.land/release/v1.28.0/deno.zip","downloaded":1024,"total":null}"#
+    );
+  }
+}
@@ -0,0 +1,135 @@
+use crate::archive_cache;
+use crate::commands::install::{deno_asset, ARCHIVE_NAME, TARGET};
+use crate::consts::{DVM_VERSION_CANARY, DVM_VERSION_LTS};
+use crate::meta::DvmMeta;
+use crate::source;
+use crate::utils::{deno_canary_path, deno_version_path, normalize_version};
+use crate::version::{get_latest_canary, get_latest_version};
+use crate::{http, http_cache, lts};
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// what installing a version would do, nothing is downloaded to find it out
+#[derive(Serialize)]
+pub struct InstallPlan {
+  pub version: String,
+  pub target: &'static str,
+  /// tried in order, the later ones are fallbacks
+  pub urls: Vec<String>,
+  /// from the archive cache or the published `.sha256sum`, `None` if neither has it
+  pub sha256: Option<String>,
+  pub destination: PathBuf,
+  pub installed: bool,
+}
+
+/// an alias that would point to another version
+#[derive(Serialize)]
+pub struct MappingChange {
+  pub alias: String,
+  pub from: Option<String>,
+  pub to: String,
+}
+
+#[derive(Default, Serialize)]
+pub struct Plan {
+  pub installs: Vec<InstallPlan>,
+  pub mappings: Vec<MappingChange>,
+}
+
+/// the checksum deno publishes next to the archives of newer releases
+pub async fn published_sha256(registry: &str, version: &Version, target: &str, asset: &str) -> Option<String> {
+  for url in source::release_urls(registry, version, target, &format!("{}.sha256sum", asset)) {
+    let Ok(response) = http_cache::get(&url).await else {
+      continue;
+    };
+    if response.is_success() {
+      let text = response.text().await.ok()?;
+      return text.split_whitespace().next().map(|it| it.to_lowercase());
+    }
+  }
+  None
+}
+
+/// the sha256 of an asset from the archive cache, or else the published one
+pub fn known_sha256(registry: &str, version: &Version, target: &str, asset: &str) -> Result<Option<String>> {
+  match archive_cache::cached_hash(version, asset) {
+    Some(hash) => Ok(Some(hash)),
+    None => http::block_on(async { Ok(published_sha256(registry, version, target, asset).await) }),
+  }
+}
+
+/// plan the install of a version, `None` resolves the latest one
+pub fn install(meta: &DvmMeta, version: Option<&str>) -> Result<InstallPlan> {
+  let version = version.map(normalize_version);
+  if version == Some(DVM_VERSION_CANARY) {
+    let hash = get_latest_canary(&meta.registry())?;
+    let destination = deno_canary_path();
+    return Ok(InstallPlan {
+      version: format!("{}-{}", DVM_VERSION_CANARY, hash),
+      target: TARGET,
+      urls: vec![source::canary_url(&meta.registry(), &hash, ARCHIVE_NAME)],
+      sha256: None,
+      installed: false,
+      destination,
+    });
+  }
+
+  let version = match version {
+    Some(DVM_VERSION_LTS) => lts::latest()?,
+    Some(version) => Version::parse(version)?,
+    None => get_latest_version(&meta.registry())?,
+  };
+  let asset = deno_asset(&version)?;
+  let sha256 = known_sha256(&meta.registry(), &version, &asset.target, &asset.name)?;
+  let destination = deno_version_path(&version);
+  Ok(InstallPlan {
+    version: version.to_string(),
+    target: TARGET,
+    urls: source::release_urls(&meta.registry(), &version, &asset.target, &asset.name),
+    sha256,
+    installed: destination.exists(),
+    destination,
+  })
+}
+
+impl Plan {
+  /// print the plan for a human, or as a single json object
+  pub fn print(&self, json: bool) {
+    if json {
+      println!("{}", serde_json::to_string_pretty(self).unwrap());
+      return;
+    }
+
+    for install in &self.installs {
+      if install.installed {
+        println!("{}", t!("Version v{} is already installed", install.version));
+        continue;
+      }
+      println!("{}", t!("Would install deno v{}", install.version.bright_green()));
+      println!("  {:<12} {}", "target", install.target);
+      for (index, url) in install.urls.iter().enumerate() {
+        println!("  {:<12} {}", if index == 0 { "source" } else { "" }, url);
+      }
+      let sha256 = install.sha256.clone().unwrap_or_else(|| t!("unknown"));
+      println!("  {:<12} {}", "sha256", sha256.bright_black());
+      println!("  {:<12} {}", "destination", install.destination.display());
+    }
+    for change in &self.mappings {
+      println!(
+        "{}",
+        t!(
+          "Would map {} from {} to {}",
+          change.alias.bright_black(),
+          change.from.as_deref().unwrap_or("-").bright_red(),
+          change.to.bright_green()
+        )
+      );
+    }
+    if self.installs.is_empty() && self.mappings.is_empty() {
+      println!("{}", t!("Nothing to do"));
+    }
+  }
+}
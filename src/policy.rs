@@ -0,0 +1,181 @@
+use crate::archive_cache::sha256_hex;
+use crate::config::DvmConfig;
+use crate::consts::{DVM_POLICY_FILENAME_PREFIX, DVM_POLICY_REFRESH_MINUTES};
+use crate::http;
+use crate::output;
+use crate::utils::dvm_cache_root;
+use anyhow::{bail, Result};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// release channels, from the most to the least stable
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+  Stable,
+  Rc,
+  Canary,
+}
+
+impl std::fmt::Display for Channel {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(match self {
+      Channel::Stable => "stable",
+      Channel::Rc => "rc",
+      Channel::Canary => "canary",
+    })
+  }
+}
+
+///
+/// the organization policy served at `policy-url`, every rule is optional:
+///
+/// ```json
+/// { "allowed": ">=1.40.0", "banned": ["1.41.1"], "channel": "stable", "message": "ask the platform team" }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct Policy {
+  /// the range every version has to be in
+  pub allowed: Option<String>,
+  /// versions known to be bad
+  #[serde(default)]
+  pub banned: Vec<String>,
+  /// the least stable channel allowed
+  pub channel: Option<Channel>,
+  /// shown along with a refusal, e.g. where to ask for an exception
+  pub message: Option<String>,
+}
+
+impl Policy {
+  /// why the policy refuses a version, `None` is canary
+  fn violation(&self, version: Option<&Version>) -> Option<String> {
+    let channel = match version {
+      None => Channel::Canary,
+      Some(version) if !version.pre.is_empty() => Channel::Rc,
+      Some(_) => Channel::Stable,
+    };
+    if let Some(allowed) = self.channel.filter(|it| channel > *it) {
+      return Some(t!(
+        "the {} channel isn't allowed by the policy, only {} is",
+        channel,
+        allowed
+      ));
+    }
+    let version = version?;
+    if self
+      .banned
+      .iter()
+      .any(|it| Version::parse(it).ok().as_ref() == Some(version))
+    {
+      return Some(t!("deno v{} is banned by the policy", version));
+    }
+    // prereleases are held to the range of their version
+    let release = Version::new(version.major, version.minor, version.patch);
+    // a typo in the range mustn't turn the policy off
+    match self.allowed.as_deref().map(|it| (it, VersionReq::parse(it))) {
+      Some((_, Ok(range))) if !range.matches(&release) => {
+        Some(t!("deno v{} is outside of `{}` allowed by the policy", version, range))
+      }
+      Some((raw, Err(_))) => Some(t!("the policy allows `{}`, which is no version range", raw)),
+      _ => None,
+    }
+  }
+}
+
+/// the policies of different urls are cached apart
+fn cache_path(url: &str) -> PathBuf {
+  dvm_cache_root().join(format!(
+    "{}{}.json",
+    DVM_POLICY_FILENAME_PREFIX,
+    &sha256_hex(url.as_bytes())[..16]
+  ))
+}
+
+fn is_fresh(path: &Path) -> bool {
+  fs::metadata(path)
+    .and_then(|it| it.modified())
+    .ok()
+    .and_then(|it| SystemTime::now().duration_since(it).ok())
+    .map(|elapsed| elapsed < Duration::from_secs(DVM_POLICY_REFRESH_MINUTES * 60))
+    .unwrap_or(false)
+}
+
+fn download(url: &str) -> Result<String> {
+  http::block_on(async {
+    let response = http::get(url).await?;
+    if !response.is_success() {
+      bail!(t!("download '{}' failed: {}", url, response.status()));
+    }
+    response.text().await
+  })
+}
+
+///
+/// the policy of `policy-url`, refreshed at most every `DVM_POLICY_REFRESH_MINUTES`,
+/// the cached one keeps being enforced while it can't be downloaded.
+fn load() -> Option<Policy> {
+  let url = DvmConfig::load().policy_url?;
+  let path = cache_path(&url);
+  if !is_fresh(&path) {
+    match download(&url) {
+      Ok(body) => {
+        fs::create_dir_all(path.parent().unwrap()).ok();
+        fs::write(&path, body).ok();
+      }
+      Err(err) => output::warn(t!("Failed to refresh the policy {}: {}", url, err)),
+    }
+  }
+  let body = fs::read_to_string(&path).ok()?;
+  match serde_json::from_str(&body) {
+    Ok(policy) => Some(policy),
+    Err(err) => {
+      output::warn(t!("Ignoring the invalid policy {}: {}", url, err));
+      None
+    }
+  }
+}
+
+fn enforce(version: Option<&Version>) -> Result<()> {
+  let Some(policy) = load() else {
+    return Ok(());
+  };
+  if let Some(violation) = policy.violation(version) {
+    match policy.message {
+      Some(message) => bail!("{}\n{}", violation, message),
+      None => bail!(violation),
+    }
+  }
+  Ok(())
+}
+
+/// refuse a version the organization policy doesn't allow, before it's installed or used
+pub fn check(version: &Version) -> Result<()> {
+  enforce(Some(version))
+}
+
+pub fn check_canary() -> Result<()> {
+  enforce(None)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_violation() {
+    let policy: Policy =
+      serde_json::from_str(r#"{ "allowed": ">=1.40.0", "banned": ["1.41.1"], "channel": "rc" }"#).unwrap();
+    let version = |it: &str| Version::parse(it).unwrap();
+    assert_eq!(policy.violation(Some(&version("1.41.0"))), None);
+    assert_eq!(policy.violation(Some(&version("1.42.0-rc.1"))), None);
+    assert!(policy.violation(Some(&version("1.41.1"))).is_some());
+    assert!(policy.violation(Some(&version("1.28.0"))).is_some());
+    assert!(policy.violation(None).is_some());
+    assert_eq!(Policy::default().violation(None), None);
+    let typo: Policy = serde_json::from_str(r#"{ "allowed": ">=1.40.O" }"#).unwrap();
+    assert!(typo.violation(Some(&version("1.41.0"))).is_some());
+  }
+}
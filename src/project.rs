@@ -0,0 +1,256 @@
+use crate::consts::DVM_CONFIG_FILENAME;
+use crate::utils::normalize_version;
+use semver::VersionReq;
+use std::fmt::{Display, Formatter};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+///
+/// project config read from the `.dvmrc` file,
+/// the first bare line is the version, the following `key = value` lines are options.
+///
+/// ```plain
+/// 1.28.0
+/// deno_dir = .deno_cache
+/// env.NO_COLOR = 1
+/// channel = 1.x
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProjectConfig {
+  pub version: Option<String>,
+  /// what `dvm update-pin` advances the version along
+  pub channel: Option<Channel>,
+  /// `DENO_DIR` relative to the project directory
+  pub deno_dir: Option<String>,
+  /// environment variables injected when launching deno in the project
+  pub env: Vec<(String, String)>,
+}
+
+///
+/// a release channel a project follows, the version line stays the build it resolved to,
+/// so everyone gets the same deno until `dvm update-pin` moves it on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Channel {
+  Stable,
+  /// release candidates, or a newer stable release
+  Rc,
+  Canary,
+  /// the newest stable release in a range, e.g. `1.x` or `1.x stable`
+  Range(VersionReq),
+}
+
+impl FromStr for Channel {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "stable" | "latest" => Ok(Channel::Stable),
+      "rc" => Ok(Channel::Rc),
+      "canary" => Ok(Channel::Canary),
+      range => VersionReq::parse(range.strip_suffix(" stable").unwrap_or(range).trim())
+        .map(Channel::Range)
+        .map_err(|_| format!("unknown channel `{}`, expected stable, rc, canary or a semver range", s)),
+    }
+  }
+}
+
+impl Display for Channel {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Channel::Stable => f.write_str("stable"),
+      Channel::Rc => f.write_str("rc"),
+      Channel::Canary => f.write_str("canary"),
+      Channel::Range(range) => write!(f, "{}", range),
+    }
+  }
+}
+
+/// split a `key = value` option line, version lines such as `>=1.28` are not options
+fn parse_option(line: &str) -> Option<(&str, &str)> {
+  let (key, value) = line.split_once('=')?;
+  let key = key.trim();
+  let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+  is_key.then(|| (key, value.trim()))
+}
+
+impl FromStr for ProjectConfig {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut config = ProjectConfig::default();
+    for line in s.lines().map(str::trim) {
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let Some((key, value)) = parse_option(line) else {
+        if config.version.is_none() {
+          config.version = Some(normalize_version(line).to_string());
+        }
+        continue;
+      };
+
+      let value = value.to_string();
+      match key {
+        "deno_dir" => config.deno_dir = Some(value),
+        "channel" => config.channel = Some(Channel::from_str(&value)?),
+        key if key.starts_with("env.") => config.env.push((key[4..].to_string(), value)),
+        key => return Err(format!("unknown option `{}` in {}", key, DVM_CONFIG_FILENAME)),
+      }
+    }
+
+    Ok(config)
+  }
+}
+
+impl ProjectConfig {
+  /// the directory and the config of the current project, if any
+  pub fn load() -> Option<(PathBuf, ProjectConfig)> {
+    ProjectConfig::load_in(std::env::current_dir().ok()?)
+  }
+
+  /// the config of the project in `dir`, if any
+  pub fn load_in(dir: PathBuf) -> Option<(PathBuf, ProjectConfig)> {
+    let content = read_to_string(dir.join(DVM_CONFIG_FILENAME)).ok()?;
+    match ProjectConfig::from_str(&content) {
+      Ok(config) => Some((dir, config)),
+      Err(err) => {
+        eprintln!("{}", err);
+        None
+      }
+    }
+  }
+
+  /// the absolute `DENO_DIR` of the project
+  pub fn deno_dir(&self, project_dir: &Path) -> Option<PathBuf> {
+    self.deno_dir.as_ref().map(|it| project_dir.join(it))
+  }
+}
+
+/// replace the version line of a `.dvmrc` content, options are kept as-is
+fn replace_version(content: &str, version: &str) -> String {
+  let mut replaced = false;
+  let mut lines = content
+    .lines()
+    .map(|line| {
+      let trimmed = line.trim();
+      if !replaced && !trimmed.is_empty() && !trimmed.starts_with('#') && parse_option(trimmed).is_none() {
+        replaced = true;
+        version
+      } else {
+        line
+      }
+    })
+    .collect::<Vec<_>>();
+  if !replaced {
+    lines.insert(0, version);
+  }
+  lines.join("\n") + "\n"
+}
+
+/// write the version to the `.dvmrc` file at `path`, keeping the options in it
+pub fn save_version(path: &Path, version: &str) -> std::io::Result<()> {
+  let content = read_to_string(path).unwrap_or_default();
+  write(path, replace_version(&content, version))
+}
+
+/// drop the version line of a `.dvmrc` content, the removed version is returned with the rest
+fn remove_version(content: &str) -> (Option<String>, String) {
+  let mut removed = None;
+  let lines = content
+    .lines()
+    .filter(|line| {
+      let trimmed = line.trim();
+      if removed.is_none() && !trimmed.is_empty() && !trimmed.starts_with('#') && parse_option(trimmed).is_none() {
+        removed = Some(trimmed.to_string());
+        false
+      } else {
+        true
+      }
+    })
+    .collect::<Vec<_>>();
+  let rest = if lines.iter().all(|it| it.trim().is_empty()) {
+    String::new()
+  } else {
+    lines.join("\n") + "\n"
+  };
+  (removed, rest)
+}
+
+///
+/// remove the version from the `.dvmrc` file at `path`, keeping the options in it,
+/// the file is deleted once nothing is left, returns the version that was removed.
+pub fn clear_version(path: &Path) -> std::io::Result<Option<String>> {
+  let Ok(content) = read_to_string(path) else {
+    return Ok(None);
+  };
+  let (removed, rest) = remove_version(&content);
+  if removed.is_some() {
+    if rest.is_empty() {
+      std::fs::remove_file(path)?;
+    } else {
+      write(path, rest)?;
+    }
+  }
+  Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_version_only() {
+    let config = ProjectConfig::from_str("1.28.0\n").unwrap();
+    assert_eq!(config.version.as_deref(), Some("1.28.0"));
+    assert_eq!(config.deno_dir, None);
+    let config = ProjectConfig::from_str("v1.28.0\n").unwrap();
+    assert_eq!(config.version.as_deref(), Some("1.28.0"));
+  }
+
+  #[test]
+  fn test_parse_options() {
+    let config = ProjectConfig::from_str("# pinned\n~1.28\ndeno_dir = .deno_cache\nenv.NO_COLOR = 1\n").unwrap();
+    assert_eq!(config.version.as_deref(), Some("~1.28"));
+    assert_eq!(config.deno_dir.as_deref(), Some(".deno_cache"));
+    assert_eq!(config.env, vec![("NO_COLOR".to_string(), "1".to_string())]);
+    assert!(ProjectConfig::from_str("1.28.0\nfoo = bar").is_err());
+    let config = ProjectConfig::from_str(">=1.28, <2\n").unwrap();
+    assert_eq!(config.version.as_deref(), Some(">=1.28, <2"));
+  }
+
+  #[test]
+  fn test_parse_channel() {
+    let config = ProjectConfig::from_str("1.28.3\nchannel = 1.x stable\n").unwrap();
+    assert_eq!(config.version.as_deref(), Some("1.28.3"));
+    assert_eq!(config.channel, Some(Channel::Range(VersionReq::parse("1.x").unwrap())));
+    let config = ProjectConfig::from_str("channel = canary\n").unwrap();
+    assert_eq!((config.version, config.channel), (None, Some(Channel::Canary)));
+    assert!(ProjectConfig::from_str("channel = nightly\n").is_err());
+  }
+
+  #[test]
+  fn test_remove_version() {
+    assert_eq!(remove_version("1.28.0\n"), (Some("1.28.0".to_string()), String::new()));
+    assert_eq!(
+      remove_version("# pinned\n1.28.0\nenv.NO_COLOR = 1\n"),
+      (Some("1.28.0".to_string()), "# pinned\nenv.NO_COLOR = 1\n".to_string())
+    );
+    assert_eq!(remove_version("deno_dir = .deno_cache\n").0, None);
+  }
+
+  #[test]
+  fn test_replace_version() {
+    assert_eq!(replace_version("", "1.28.0"), "1.28.0\n");
+    assert_eq!(replace_version("1.27.0", "1.28.0"), "1.28.0\n");
+    assert_eq!(
+      replace_version("# pinned\n1.27.0\ndeno_dir = .deno_cache\n", "1.28.0"),
+      "# pinned\n1.28.0\ndeno_dir = .deno_cache\n"
+    );
+    assert_eq!(
+      replace_version("deno_dir = .deno_cache\n", "1.28.0"),
+      "1.28.0\ndeno_dir = .deno_cache\n"
+    );
+  }
+}
@@ -0,0 +1,166 @@
+use crate::archive_cache::{self, sha256_hex};
+use crate::output;
+use crate::utils::{deno_version_dir, now};
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+
+/// where deno is built from, an attestation naming another source is refused
+const SOURCE_REPOSITORY: &str = "https://github.com/denoland/deno";
+const PROVENANCE_FILENAME: &str = ".provenance.json";
+
+/// the DSSE envelope of an attestation, one per line of `<asset>.intoto.jsonl`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+  payload_type: String,
+  payload: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Statement {
+  #[serde(default)]
+  subject: Vec<Subject>,
+  predicate_type: String,
+  #[serde(default)]
+  predicate: Value,
+}
+
+#[derive(Deserialize)]
+struct Subject {
+  #[serde(default)]
+  digest: Value,
+}
+
+/// the provenance an archive was installed with, kept in `.provenance.json` of its version
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Provenance {
+  pub predicate_type: String,
+  pub builder: String,
+  pub source: String,
+  /// unix time in seconds
+  #[serde(alias = "verified_at")]
+  pub recorded_at: u64,
+}
+
+/// the builder and the source repository of a SLSA v1 or v0.2 predicate
+fn builder_and_source(statement: &Statement) -> Option<(String, String)> {
+  let predicate = &statement.predicate;
+  let text = |value: &Value| value.as_str().map(str::to_string);
+  if statement.predicate_type == "https://slsa.dev/provenance/v1" {
+    let builder = text(&predicate["runDetails"]["builder"]["id"])?;
+    let source = text(&predicate["buildDefinition"]["externalParameters"]["workflow"]["repository"])?;
+    return Some((builder, source));
+  }
+  let builder = text(&predicate["builder"]["id"])?;
+  let source = text(&predicate["invocation"]["configSource"]["uri"])?;
+  // `git+https://github.com/denoland/deno@refs/tags/v1.28.0`
+  let source = source.trim_start_matches("git+");
+  Some((builder, source.split('@').next()?.to_string()))
+}
+
+///
+/// find the attestation of `data` among the lines of an `.intoto.jsonl` file,
+/// it has to be a SLSA provenance with a subject of the same sha256 built from the deno repository.
+fn find(data: &[u8], attestations: &str) -> Result<Provenance> {
+  let sha256 = sha256_hex(data);
+  for line in attestations.lines().filter(|it| !it.trim().is_empty()) {
+    let envelope: Envelope = serde_json::from_str(line)?;
+    if envelope.payload_type != "application/vnd.in-toto+json" {
+      continue;
+    }
+    let statement: Statement = serde_json::from_slice(&STANDARD.decode(envelope.payload)?)?;
+    if !statement.predicate_type.starts_with("https://slsa.dev/provenance/")
+      || !statement
+        .subject
+        .iter()
+        .any(|it| it.digest["sha256"] == sha256.as_str())
+    {
+      continue;
+    }
+    let (builder, source) =
+      builder_and_source(&statement).ok_or_else(|| anyhow!("the provenance names no builder or source"))?;
+    if source.trim_end_matches(".git") != SOURCE_REPOSITORY {
+      bail!("it's built from {} instead of {}", source, SOURCE_REPOSITORY);
+    }
+    return Ok(Provenance {
+      predicate_type: statement.predicate_type,
+      builder,
+      source,
+      recorded_at: (now() / 1000) as u64,
+    });
+  }
+  bail!("no provenance covers the sha256 {}", sha256)
+}
+
+///
+/// read the SLSA provenance published next to a release archive as `<asset>.intoto.jsonl`,
+/// a missing one is fine, one that doesn't match the archive fails.
+/// the envelope signature is a sigstore certificate which isn't checked, so this verifies nothing,
+/// the attestation comes from the same registry as the archive and is only recorded.
+pub async fn check(
+  registry: &str,
+  version: &Version,
+  target: &str,
+  asset: &str,
+  data: &[u8],
+) -> Result<Option<Provenance>> {
+  let Some(attestations) = archive_cache::fetch(registry, version, target, &format!("{}.intoto.jsonl", asset)).await?
+  else {
+    return Ok(None);
+  };
+  let provenance = find(data, &String::from_utf8_lossy(&attestations))
+    .map_err(|err| anyhow!(t!("The provenance of {} is invalid: {}", asset, err)))?;
+  output::info(t!(
+    "{} has an unsigned provenance naming {} as its builder",
+    asset,
+    provenance.builder
+  ));
+  Ok(Some(provenance))
+}
+
+/// keep the provenance an installed version came with
+pub fn record(version: &Version, provenance: &Provenance) -> Result<()> {
+  let path = deno_version_dir(version).join(PROVENANCE_FILENAME);
+  fs::write(path, serde_json::to_string_pretty(provenance)?)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn attestation(sha256: &str, repository: &str) -> String {
+    let statement = json!({
+      "_type": "https://in-toto.io/Statement/v1",
+      "subject": [{ "name": "deno.zip", "digest": { "sha256": sha256 } }],
+      "predicateType": "https://slsa.dev/provenance/v1",
+      "predicate": {
+        "buildDefinition": { "externalParameters": { "workflow": { "repository": repository } } },
+        "runDetails": { "builder": { "id": "https://github.com/actions/runner" } },
+      },
+    });
+    json!({
+      "payloadType": "application/vnd.in-toto+json",
+      "payload": STANDARD.encode(statement.to_string()),
+      "signatures": [],
+    })
+    .to_string()
+  }
+
+  #[test]
+  fn test_find() {
+    let sha256 = sha256_hex(b"zip");
+    let provenance = find(b"zip", &attestation(&sha256, SOURCE_REPOSITORY)).unwrap();
+    assert_eq!(provenance.builder, "https://github.com/actions/runner");
+    assert_eq!(provenance.source, SOURCE_REPOSITORY);
+    assert!(find(b"other", &attestation(&sha256, SOURCE_REPOSITORY)).is_err());
+    assert!(find(b"zip", &attestation(&sha256, "https://github.com/evil/deno")).is_err());
+  }
+}
@@ -0,0 +1,163 @@
+use crate::archive_cache;
+use crate::config::DvmConfig;
+use crate::output;
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use semver::Version;
+use std::sync::OnceLock;
+
+static REQUIRED: OnceLock<bool> = OnceLock::new();
+
+/// a minisign public key, i.e. the base64 line of a `minisign.pub`
+pub struct PublicKey {
+  key_id: [u8; 8],
+  key: VerifyingKey,
+}
+
+/// the lines carrying data, the untrusted comments are skipped
+fn data_lines(text: &str) -> impl Iterator<Item = &str> {
+  text
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+}
+
+impl PublicKey {
+  pub fn parse(raw: &str) -> Result<Self> {
+    let line = data_lines(raw)
+      .next()
+      .ok_or_else(|| anyhow!("the public key is empty"))?;
+    let bytes = STANDARD.decode(line)?;
+    if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+      bail!("not a minisign public key");
+    }
+    Ok(PublicKey {
+      key_id: bytes[2..10].try_into()?,
+      key: VerifyingKey::from_bytes(bytes[10..].try_into()?)?,
+    })
+  }
+
+  /// the key id the way minisign prints it
+  pub fn key_id(&self) -> String {
+    format!("{:016X}", u64::from_le_bytes(self.key_id))
+  }
+}
+
+///
+/// verify `data` against the content of a `.minisig` file,
+/// returns the trusted comment, which is signed as well.
+pub fn verify(key: &PublicKey, data: &[u8], signature: &str) -> Result<String> {
+  let mut lines = data_lines(signature);
+  let (Some(signature), Some(trusted_comment), Some(global_signature)) = (lines.next(), lines.next(), lines.next())
+  else {
+    bail!("the signature file is incomplete");
+  };
+  let trusted_comment = trusted_comment
+    .strip_prefix("trusted comment: ")
+    .ok_or_else(|| anyhow!("the trusted comment is missing"))?;
+
+  let signature = STANDARD.decode(signature)?;
+  if signature.len() != 74 {
+    bail!("not a minisign signature");
+  }
+  let (algorithm, key_id, signature) = (&signature[..2], &signature[2..10], &signature[10..]);
+  if key_id != key.key_id {
+    bail!("it's signed by another key than {}", key.key_id());
+  }
+
+  let parsed = Signature::from_slice(signature)?;
+  let verified = match algorithm {
+    // the prehashed form, the default of minisign
+    b"ED" => key.key.verify(&Blake2b512::digest(data), &parsed),
+    b"Ed" => key.key.verify(data, &parsed),
+    _ => bail!("unsupported signature algorithm"),
+  };
+  verified.map_err(|_| anyhow!("the signature doesn't match"))?;
+
+  let global_signature = Signature::from_slice(&STANDARD.decode(global_signature)?)?;
+  key
+    .key
+    .verify(&[signature, trusted_comment.as_bytes()].concat(), &global_signature)
+    .map_err(|_| anyhow!("the trusted comment has been tampered with"))?;
+  Ok(trusted_comment.to_string())
+}
+
+/// `--require-signature` and the `require-signature` config key both turn the requirement on
+pub fn set_required(required: bool) {
+  REQUIRED.set(required || DvmConfig::load().require_signature()).ok();
+}
+
+pub fn is_required() -> bool {
+  *REQUIRED.get_or_init(|| DvmConfig::load().require_signature())
+}
+
+///
+/// check a release archive against its `.minisig` with the `signature-public-key` config,
+/// nothing is checked without a key, unless a signature is required.
+pub async fn check(registry: &str, version: &Version, target: &str, asset: &str, data: &[u8]) -> Result<()> {
+  let Some(raw_key) = DvmConfig::load().signature_public_key else {
+    if is_required() {
+      bail!(t!(
+        "A signature is required but no public key is configured, set `signature-public-key` with `dvm config set`"
+      ));
+    }
+    return Ok(());
+  };
+  let key = PublicKey::parse(&raw_key).map_err(|err| anyhow!("invalid `signature-public-key`: {}", err))?;
+
+  let Some(signature) = archive_cache::fetch(registry, version, target, &format!("{}.minisig", asset)).await? else {
+    if is_required() {
+      bail!(t!("{} has no published signature", asset));
+    }
+    output::warn(t!("{} has no published signature, it's not verified", asset));
+    return Ok(());
+  };
+  verify(&key, data, &String::from_utf8_lossy(&signature))
+    .map_err(|err| anyhow!(t!("The signature of {} is invalid: {}", asset, err)))?;
+
+  output::success(t!("Verified the signature of {} with key {}", asset, key.key_id()));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ed25519_dalek::{Signer, SigningKey};
+
+  /// what `minisign -S` would write for `data`
+  fn sign(signing_key: &SigningKey, key_id: [u8; 8], data: &[u8]) -> String {
+    let signature = signing_key.sign(&Blake2b512::digest(data)).to_bytes();
+    let trusted_comment = "timestamp:1700000000\tfile:deno.zip";
+    let global_signature = signing_key.sign(&[&signature[..], trusted_comment.as_bytes()].concat());
+    format!(
+      "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+      STANDARD.encode([&b"ED"[..], &key_id, &signature].concat()),
+      trusted_comment,
+      STANDARD.encode(global_signature.to_bytes())
+    )
+  }
+
+  #[test]
+  fn test_verify() {
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+    let public_key = format!(
+      "untrusted comment: minisign public key\n{}\n",
+      STANDARD.encode([&b"Ed"[..], &key_id, signing_key.verifying_key().as_bytes()].concat())
+    );
+    let key = PublicKey::parse(&public_key).unwrap();
+    assert_eq!(key.key_id(), "0807060504030201");
+
+    let signature = sign(&signing_key, key_id, b"deno");
+    assert_eq!(
+      verify(&key, b"deno", &signature).unwrap(),
+      "timestamp:1700000000\tfile:deno.zip"
+    );
+    assert!(verify(&key, b"dvm", &signature).is_err());
+    assert!(verify(&key, b"deno", &sign(&signing_key, [0; 8], b"deno")).is_err());
+    assert!(verify(&key, b"deno", &signature.replace("file:deno.zip", "file:other.zip")).is_err());
+  }
+}
@@ -0,0 +1,163 @@
+use crate::config::{DvmConfig, UrlTemplate};
+use crate::consts::{REGISTRY_GITHUB_RELEASE, REGISTRY_LATEST_CANARY_PATH, REGISTRY_LATEST_RELEASE_PATH};
+use crate::output::{self, Event};
+use crate::timings::{self, Phase};
+use crate::{http, http_trace};
+use anyhow::{anyhow, Result};
+use semver::Version;
+use std::time::{Duration, Instant};
+
+/// the least time between two `download-progress` events of a download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+///
+/// urls of a release asset, in the order they should be tried,
+/// the configured registry goes first and GitHub releases are the fallback, `target` is the one the asset is for.
+pub fn release_urls(registry: &str, version: &Version, target: &str, asset: &str) -> Vec<String> {
+  release_urls_with(
+    DvmConfig::load().download_url_template.as_ref(),
+    registry,
+    version,
+    target,
+    asset,
+  )
+}
+
+/// `download-url-template` takes the place of the registry
+fn release_urls_with(
+  template: Option<&UrlTemplate>,
+  registry: &str,
+  version: &Version,
+  target: &str,
+  asset: &str,
+) -> Vec<String> {
+  let primary = match template {
+    Some(template) => template.render(&version.to_string(), target, asset),
+    None => format!("{}release/v{}/{}", registry, version, asset),
+  };
+  vec![primary, format!("{}v{}/{}", REGISTRY_GITHUB_RELEASE, version, asset)]
+}
+
+/// canary builds are only published to the registry
+pub fn canary_url(registry: &str, hash: &str, asset: &str) -> String {
+  format!("{}canary/{}/{}", registry, hash, asset)
+}
+
+pub fn latest_release_url(registry: &str) -> String {
+  format!("{}{}", registry, REGISTRY_LATEST_RELEASE_PATH)
+}
+
+pub fn latest_canary_url(registry: &str) -> String {
+  format!("{}{}", registry, REGISTRY_LATEST_CANARY_PATH)
+}
+
+///
+/// download from the first url that has it, `None` if none of them has it,
+/// network errors and server errors move on to the next url.
+pub async fn fetch(urls: &[String]) -> Result<Option<Vec<u8>>> {
+  let _timer = timings::start(Phase::Download);
+  let mut error = None;
+  for (attempt, url) in urls.iter().enumerate() {
+    output::info(t!("downloading {}", url));
+    http_trace::log(format!(
+      "mirror {}/{}: {}",
+      attempt + 1,
+      urls.len(),
+      http_trace::redact_url(url)
+    ));
+    match http::get(url).await {
+      Ok(response) if response.is_success() => return Ok(Some(download(url, response).await?)),
+      Ok(response) if response.status() == 404 => {}
+      Ok(response) => error = Some(anyhow!(t!("download '{}' failed: {}", url, response.status()))),
+      Err(err) => error = Some(anyhow!(t!("download '{}' failed: {}", url, err))),
+    }
+  }
+
+  match error {
+    Some(err) => Err(err),
+    None => Ok(None),
+  }
+}
+
+async fn download(url: &str, response: http::Response) -> std::io::Result<Vec<u8>> {
+  let total = response.content_length();
+  output::event(Event::DownloadStart { url, total });
+  let mut reported = Instant::now();
+  let data = response
+    .bytes_with_progress(|downloaded| {
+      if reported.elapsed() >= PROGRESS_INTERVAL {
+        reported = Instant::now();
+        output::event(Event::DownloadProgress { url, downloaded, total });
+      }
+    })
+    .await?;
+  let downloaded = data.len() as u64;
+  output::event(Event::DownloadProgress { url, downloaded, total });
+  Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::consts::{REGISTRY_CN, REGISTRY_OFFICIAL};
+  use crate::http::{block_on, with_transport, MockTransport};
+
+  #[test]
+  fn test_release_urls() {
+    let v = Version::parse("1.7.0").unwrap();
+    assert_eq!(
+      release_urls_with(
+        None,
+        REGISTRY_OFFICIAL,
+        &v,
+        "x86_64-unknown-linux-gnu",
+        "deno-x86_64-unknown-linux-gnu.zip"
+      ),
+      vec![
+        "https://dl.deno.land/release/v1.7.0/deno-x86_64-unknown-linux-gnu.zip",
+        "https://github.com/denoland/deno/releases/download/v1.7.0/deno-x86_64-unknown-linux-gnu.zip",
+      ]
+    );
+    assert_eq!(
+      release_urls_with(
+        None,
+        REGISTRY_CN,
+        &v,
+        "x86_64-unknown-linux-gnu",
+        "deno-x86_64-unknown-linux-gnu.zip"
+      )[0],
+      "https://dl.deno.js.cn/release/v1.7.0/deno-x86_64-unknown-linux-gnu.zip"
+    );
+    let template = UrlTemplate::try_from("https://mirror.corp/{target}/v{version}/{archive}".to_string()).unwrap();
+    assert_eq!(
+      release_urls_with(
+        Some(&template),
+        REGISTRY_OFFICIAL,
+        &v,
+        "aarch64-apple-darwin",
+        "deno.zip"
+      )[0],
+      "https://mirror.corp/aarch64-apple-darwin/v1.7.0/deno.zip"
+    );
+  }
+
+  #[test]
+  fn test_fetch_falls_back() {
+    let v = Version::parse("1.7.0").unwrap();
+    let urls = release_urls(REGISTRY_OFFICIAL, &v, "x86_64-unknown-linux-gnu", "deno.zip");
+
+    let mock = MockTransport::default().with(&urls[1], 200, "zip");
+    assert_eq!(
+      with_transport(mock, || block_on(fetch(&urls)).unwrap()),
+      Some(b"zip".to_vec())
+    );
+
+    assert_eq!(
+      with_transport(MockTransport::default(), || block_on(fetch(&urls)).unwrap()),
+      None
+    );
+
+    let mock = MockTransport::default().with(&urls[0], 503, "");
+    assert!(with_transport(mock, || block_on(fetch(&urls))).is_err());
+  }
+}
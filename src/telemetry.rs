@@ -0,0 +1,127 @@
+use crate::cli::{Cli, TelemetryCommands};
+use crate::config::{self, DvmConfig};
+use crate::consts::DVM_TELEMETRY_NOTICE_FILENAME;
+use crate::http;
+use crate::output;
+use crate::utils::dvm_config_root;
+use crate::version::DVM;
+use anyhow::Result;
+use clap::CommandFactory;
+use serde::Serialize;
+use std::fs;
+use std::time::Duration;
+
+/// a report is never worth waiting for
+const REPORT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// everything a report contains, nothing identifies the user or the machine
+#[derive(Serialize)]
+struct Report<'a> {
+  command: &'a str,
+  dvm_version: &'a str,
+  os: &'a str,
+  arch: &'a str,
+  success: bool,
+}
+
+/// only the subcommand name is reported, never its arguments
+fn command_name() -> Option<String> {
+  let matches = Cli::command().try_get_matches().ok()?;
+  matches.subcommand_name().map(str::to_string)
+}
+
+/// tell what's reported the first time a report is sent
+fn show_notice_once(url: &str) {
+  let path = dvm_config_root().join(DVM_TELEMETRY_NOTICE_FILENAME);
+  if path.exists() {
+    return;
+  }
+  output::warn(t!(
+    "Telemetry is on, dvm reports the command name, its version, the OS and arch, and whether the command succeeded to {}, run `dvm telemetry off` to stop it",
+    url
+  ));
+  fs::create_dir_all(dvm_config_root()).ok();
+  fs::write(path, "").ok();
+}
+
+///
+/// send the report of the command that just ran to `telemetry-url` if `telemetry` is set to true,
+/// failures and slow networks are ignored, the command's outcome never depends on it.
+pub fn report(success: bool) {
+  let config = DvmConfig::load();
+  let enabled = config.telemetry();
+  let Some(url) = config.telemetry_url.filter(|_| enabled) else {
+    return;
+  };
+  let Some(command) = command_name() else {
+    return;
+  };
+  show_notice_once(&url);
+
+  let report = Report {
+    command: &command,
+    dvm_version: DVM,
+    os: std::env::consts::OS,
+    arch: std::env::consts::ARCH,
+    success,
+  };
+  let body = serde_json::to_string(&report).unwrap();
+  http::block_on(async {
+    tokio::time::timeout(REPORT_TIMEOUT, http::post_json(&url, body))
+      .await
+      .ok();
+    Ok(())
+  })
+  .ok();
+}
+
+pub fn exec(command: TelemetryCommands) -> Result<()> {
+  match command {
+    TelemetryCommands::Status => {
+      let config = DvmConfig::load();
+      if !config.telemetry() {
+        println!(
+          "{}",
+          t!("Telemetry is off, set `telemetry` and `telemetry-url` with `dvm config set` to send usage reports")
+        );
+      } else if let Some(url) = config.telemetry_url {
+        println!("{}", t!("Telemetry is on, reports are sent to {}", url));
+        println!(
+          "{}",
+          t!("Each report has the command name, the dvm version, the OS and arch, and whether it succeeded")
+        );
+      } else {
+        println!(
+          "{}",
+          t!("Telemetry is on but no `telemetry-url` is set, nothing is sent")
+        );
+      }
+      Ok(())
+    }
+    TelemetryCommands::Off => {
+      config::set("telemetry", "false")?;
+      output::success(t!("Telemetry is off"));
+      Ok(())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_report_fields() {
+    let report = Report {
+      command: "install",
+      dvm_version: "1.9.0",
+      os: "linux",
+      arch: "x86_64",
+      success: true,
+    };
+    assert_eq!(
+      serde_json::to_string(&report).unwrap(),
+      r#"{"command":"install","dvm_version":"1.9.0","os":"linux","arch":"x86_64","success":true}"#
+    );
+  }
+}
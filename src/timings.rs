@@ -0,0 +1,115 @@
+use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+/// the time spent in each phase and how often it was entered, in the order of `Phase::ALL`
+static SPENT: Mutex<[(Duration, u32); Phase::ALL.len()]> = Mutex::new([(Duration::ZERO, 0); Phase::ALL.len()]);
+
+/// what an install spends its time on
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+  /// asking the registry what a keyword or range means
+  Resolve,
+  Download,
+  /// checksums, signatures and provenance
+  Verify,
+  /// unpacking archives and patching deltas
+  Extract,
+  /// pointing the deno in PATH at a version
+  Activate,
+}
+
+impl Phase {
+  const ALL: [Phase; 5] = [
+    Phase::Resolve,
+    Phase::Download,
+    Phase::Verify,
+    Phase::Extract,
+    Phase::Activate,
+  ];
+
+  fn name(self) -> &'static str {
+    match self {
+      Phase::Resolve => "resolve",
+      Phase::Download => "download",
+      Phase::Verify => "verify",
+      Phase::Extract => "extract",
+      Phase::Activate => "activate",
+    }
+  }
+}
+
+/// a phase being timed, the time is added to it when dropped
+pub struct Timer {
+  phase: Phase,
+  started: Instant,
+}
+
+impl Drop for Timer {
+  fn drop(&mut self) {
+    let mut spent = SPENT.lock().unwrap_or_else(PoisonError::into_inner);
+    let (total, count) = &mut spent[self.phase as usize];
+    *total += self.started.elapsed();
+    *count += 1;
+  }
+}
+
+/// turn on `--timings`, the total is counted from here
+pub fn enable() {
+  ENABLED.store(true, Ordering::Relaxed);
+  STARTED.get_or_init(Instant::now);
+}
+
+///
+/// time a phase until the returned timer is dropped, phases can nest and overlap,
+/// deno and denort download at once, so the phases may add up to more than the total.
+pub fn start(phase: Phase) -> Timer {
+  Timer {
+    phase,
+    started: Instant::now(),
+  }
+}
+
+fn summary(spent: &[(Duration, u32)], total: Duration) -> String {
+  let mut text = String::new();
+  for (phase, (duration, count)) in Phase::ALL.iter().zip(spent) {
+    if *count == 0 {
+      continue;
+    }
+    text.push_str(&format!("{:<10}{:>8.2}s", phase.name(), duration.as_secs_f64()));
+    if *count > 1 {
+      text.push_str(&format!("  ({}x)", count));
+    }
+    text.push('\n');
+  }
+  text.push_str(&format!("{:<10}{:>8.2}s\n", "total", total.as_secs_f64()));
+  text
+}
+
+/// print where the command spent its time with `--timings`, on stderr
+pub fn print() {
+  let Some(started) = STARTED.get().filter(|_| ENABLED.load(Ordering::Relaxed)) else {
+    return;
+  };
+  let spent = *SPENT.lock().unwrap_or_else(PoisonError::into_inner);
+  eprint!("{}", summary(&spent, started.elapsed()).bright_black());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_summary() {
+    let mut spent = [(Duration::ZERO, 0); Phase::ALL.len()];
+    spent[Phase::Download as usize] = (Duration::from_millis(2500), 2);
+    spent[Phase::Extract as usize] = (Duration::from_millis(300), 1);
+    assert_eq!(
+      summary(&spent, Duration::from_secs(3)),
+      "download      2.50s  (2x)\nextract       0.30s\ntotal         3.00s\n"
+    );
+  }
+}
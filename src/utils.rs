@@ -1,30 +1,37 @@
 use cfg_if::cfg_if;
 
-use crate::consts::{DENO_EXE, DVM_CACHE_PATH_PREFIX, DVM_CANARY_PATH_PREFIX};
+use crate::config::{DvmConfig, VersionPrecedence};
+use crate::consts::{
+  DENORT_EXE, DENO_EXE, DVM_CACHE_PATH_PREFIX, DVM_CANARY_PATH_PREFIX, DVM_CONFIG_FILENAME, DVM_DENO_DIR_PATH_PREFIX,
+  DVM_DENO_VERSION_ENV, DVM_SHIM_TARGET_FILENAME, DVM_STUBS_PATH_PREFIX, DVM_UPDATE_CHECK_INTERVAL_HOURS,
+};
+use crate::output;
+use crate::project::ProjectConfig;
 use crate::version::VersionArg;
 use anyhow::anyhow;
 use dirs::home_dir;
 use semver::{Version, VersionReq};
 use std::env;
-use std::fs::{read_to_string, write};
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
 use std::io::{stdin, Read, Write};
-use std::path::Path;
 use std::path::PathBuf;
+use std::path::{Component, Path};
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tempfile::TempDir;
+use std::sync::Once;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn prompt_request(prompt: &str) -> bool {
   print!("{} (Y/n)", prompt);
 
   std::io::stdout().flush().unwrap();
+  let mut buf = [0u8; 1];
   let confirm = stdin()
-    .bytes()
-    .next()
-    .and_then(|it| it.ok())
-    .map(char::from)
-    .unwrap_or_else(|| 'y');
-  confirm == '\n' || confirm == '\r' || confirm.to_ascii_lowercase() == 'y'
+    .read(&mut buf)
+    .ok()
+    .filter(|&n| n > 0)
+    .map(|_| char::from(buf[0]))
+    .unwrap_or('y');
+  confirm == '\n' || confirm == '\r' || confirm.eq_ignore_ascii_case(&'y')
 }
 
 pub fn check_is_deactivated() -> bool {
@@ -37,17 +44,104 @@ pub fn now() -> u128 {
   SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()
 }
 
+///
+/// whether a day has passed since the time recorded in the cache file `name`,
+/// the time is recorded if so, a failure to record it never counts as due.
+pub fn mark_if_due(name: &str) -> bool {
+  let path = dvm_cache_root().join(name);
+  let interval = Duration::from_secs(DVM_UPDATE_CHECK_INTERVAL_HOURS * 60 * 60);
+  let checked_recently = std::fs::metadata(&path)
+    .and_then(|it| it.modified())
+    .ok()
+    .and_then(|it| SystemTime::now().duration_since(it).ok())
+    .map(|elapsed| elapsed < interval)
+    .unwrap_or(false);
+  if checked_recently {
+    return false;
+  }
+
+  create_dir_all(dvm_cache_root()).is_ok() && write(path, "").is_ok()
+}
+
+/// a unix time in seconds as `YYYY-MM-DD HH:MM:SS` in UTC
+pub fn format_timestamp(secs: u64) -> String {
+  let (days, rest) = (secs / 86400, secs % 86400);
+  // days to a civil date, http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+  let z = days as i64 + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!(
+    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+    year,
+    month,
+    day,
+    rest / 3600,
+    rest % 3600 / 60,
+    rest % 60
+  )
+}
+
+///
+/// the stub recording when a version was used last,
+/// kept in the dvm root when the versions store is shared since it may be read-only.
+pub fn stub_path(version: &str) -> PathBuf {
+  if dvm_system_root().is_some() {
+    dvm_root().join(DVM_STUBS_PATH_PREFIX).join(version)
+  } else {
+    dvm_versions().join(version).join(".dvmstub")
+  }
+}
+
 pub fn update_stub(verison: &str) {
-  let mut home = dvm_versions();
-  home.push(verison);
-  if home.is_dir() {
-    home.push(".dvmstub");
-    write(home, now().to_string()).unwrap();
+  if dvm_versions().join(verison).is_dir() {
+    let stub = stub_path(verison);
+    create_dir_all(stub.parent().unwrap()).unwrap();
+    write(stub, now().to_string()).unwrap();
+  }
+}
+
+/// whether dvm can write into the versions store, a shared one may be mounted read-only
+pub fn is_versions_writable() -> bool {
+  let versions = dvm_versions();
+  if create_dir_all(&versions).is_err() {
+    return false;
+  }
+  let probe = versions.join(".dvm-write-probe");
+  let writable = write(&probe, "").is_ok();
+  let _ = remove_file(probe);
+  writable
+}
+
+pub fn ensure_versions_writable() -> anyhow::Result<()> {
+  if !is_versions_writable() {
+    anyhow::bail!(t!(
+      "The versions store {} is read-only, please ask its administrator to manage versions",
+      dvm_versions().display()
+    ));
+  }
+  Ok(())
+}
+
+///
+/// the version as semver reads it, written by hand or by other tools,
+/// e.g. ` 1.43.5 `, `v1.43.5` or `deno 1.43.5` as printed by `deno -V`.
+pub fn normalize_version(input: &str) -> &str {
+  let input = input.trim();
+  let input = input.strip_prefix("deno ").map(str::trim_start).unwrap_or(input);
+  match input.strip_prefix(['v', 'V']) {
+    Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+    _ => input,
   }
 }
 
 pub fn is_exact_version(input: &str) -> bool {
-  Version::parse(input).is_ok()
+  Version::parse(normalize_version(input)).is_ok()
 }
 
 #[allow(dead_code)]
@@ -68,40 +162,164 @@ where
     .max_by(|a, b| a.partial_cmp(b).unwrap())
 }
 
+/// the version pinned for the session with `DVM_DENO_VERSION`, if any
+pub fn session_version() -> Option<String> {
+  env::var(DVM_DENO_VERSION_ENV)
+    .ok()
+    .map(|it| it.trim().to_string())
+    .filter(|it| !it.is_empty())
+}
+
+/// `DVM_DENO_VERSION` comes before the project `.dvmrc` with `deno-version-precedence = "env"`
+pub fn session_version_first() -> bool {
+  DvmConfig::load().deno_version_precedence() == VersionPrecedence::Env
+}
+
+fn read_dvmrc_version(path: &Path) -> Option<String> {
+  read_to_string(path)
+    .map_err(|e| anyhow!(e))
+    .and_then(|content| ProjectConfig::from_str(&content).map_err(|e| anyhow!(e)))
+    .and_then(|config| config.version.ok_or_else(|| anyhow!("")))
+    .ok()
+}
+
 ///
 /// Find and load the dvmrc
-/// local -> user -> default
+/// local -> `DVM_DENO_VERSION` -> user -> default
 pub fn load_dvmrc() -> VersionArg {
-  let project_config = PathBuf::from(".dvmrc");
-  let user_config = dvm_root();
-
-  Path::exists(project_config.as_path())
-    .then_some(project_config)
-    .or_else(|| Path::exists(&user_config).then_some(user_config))
-    .and_then(|found| {
-      read_to_string(found)
-        .map_err(|e| anyhow!(e))
-        .and_then(|content| VersionArg::from_str(&content).map_err(|_| anyhow!("")))
-        .ok()
-    })
+  let project = || read_dvmrc_version(Path::new(DVM_CONFIG_FILENAME));
+  let user = || home_dir().and_then(|home| read_dvmrc_version(&home.join(DVM_CONFIG_FILENAME)));
+
+  let found = if session_version_first() {
+    session_version().or_else(project)
+  } else {
+    project().or_else(session_version)
+  };
+  found
+    .or_else(user)
+    .and_then(|version| VersionArg::from_str(&version).ok())
     .unwrap_or_else(|| VersionArg::from_str("*").unwrap())
 }
 
+/// where dvm keeps its files
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Layout {
+  /// everything inside a single directory, `~/.dvm` or `$DVM_DIR`
+  Classic(PathBuf),
+  /// binaries in `$XDG_DATA_HOME/dvm`, caches in `$XDG_CACHE_HOME/dvm`
+  /// and config in `$XDG_CONFIG_HOME/dvm`
+  Xdg,
+}
+
+///
+/// whether to use the XDG layout when `DVM_DIR` is not set:
+///   `DVM_LAYOUT=xdg|classic` selects one explicitly,
+///   otherwise XDG is used only when `~/.dvm` is gone and the XDG data dir exists (after `dvm migrate-layout`).
+fn select_xdg_layout(layout: Option<String>, classic_exists: bool, xdg_exists: bool) -> bool {
+  match layout.as_deref() {
+    Some("xdg") => true,
+    Some(_) => false,
+    None => !classic_exists && xdg_exists,
+  }
+}
+
+fn home() -> PathBuf {
+  home_dir().expect("home directory not found, checked by `check_dvm_root` on startup")
+}
+
+pub fn classic_root() -> PathBuf {
+  // Note: on Windows, the $HOME environment variable may be set by users or by
+  // third party software, but it is non-standard and should not be relied upon.
+  env::var_os("DVM_DIR")
+    .map(PathBuf::from)
+    .unwrap_or_else(|| home().join(".dvm"))
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+  env::var_os(var)
+    .map(PathBuf::from)
+    .filter(|it| it.is_absolute())
+    .unwrap_or_else(|| home().join(fallback))
+    .join("dvm")
+}
+
+pub fn xdg_data_dir() -> PathBuf {
+  xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+pub fn xdg_cache_dir() -> PathBuf {
+  xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+pub fn xdg_config_dir() -> PathBuf {
+  xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `DVM_DIR` always means the classic layout
+pub fn layout() -> Layout {
+  if let Some(dvm_dir) = env::var_os("DVM_DIR") {
+    return Layout::Classic(PathBuf::from(dvm_dir));
+  }
+
+  let layout = env::var("DVM_LAYOUT").ok();
+  if select_xdg_layout(layout, home().join(".dvm").exists(), xdg_data_dir().exists()) {
+    Layout::Xdg
+  } else {
+    Layout::Classic(classic_root())
+  }
+}
+
+/// the root of versions, the bin directory and the metadata
 pub fn dvm_root() -> PathBuf {
-  env::var_os("DVM_DIR").map(PathBuf::from).unwrap_or_else(|| {
-    // Note: on Windows, the $HOME environment variable may be set by users or by
-    // third party software, but it is non-standard and should not be relied upon.
-    home_dir()
-      .map(PathBuf::from)
-      .map(|it| it.join(".dvm"))
-      .unwrap_or_else(|| TempDir::new().unwrap().into_path().join(".dvm"))
-  })
+  match layout() {
+    Layout::Classic(root) => root,
+    Layout::Xdg => xdg_data_dir(),
+  }
+}
+
+/// the root of caches that can be safely removed
+pub fn dvm_cache_root() -> PathBuf {
+  match layout() {
+    Layout::Classic(root) => root,
+    Layout::Xdg => xdg_cache_dir(),
+  }
+}
+
+/// the directory of `config.toml`
+pub fn dvm_config_root() -> PathBuf {
+  match layout() {
+    Layout::Classic(root) => root,
+    Layout::Xdg => xdg_config_dir(),
+  }
+}
+
+/// make sure the dvm root can be determined before anything touches it
+pub fn check_dvm_root() -> anyhow::Result<()> {
+  if env::var_os("DVM_DIR").is_none() && home_dir().is_none() {
+    anyhow::bail!(t!(
+      "Could not find the home directory, please set `DVM_DIR` or pass `--dvm-dir`"
+    ));
+  }
+  Ok(())
+}
+
+///
+/// the versions store shared by all users of a system-wide install,
+/// e.g. `/usr/local/share/dvm` or `C:\ProgramData\dvm`,
+/// set by `DVM_SYSTEM_DIR` or the `system-dir` config key.
+pub fn dvm_system_root() -> Option<PathBuf> {
+  env::var_os("DVM_SYSTEM_DIR")
+    .map(PathBuf::from)
+    .or_else(|| DvmConfig::load().system_dir)
 }
 
+/// installed versions live in the shared store if there is one, the dvm root otherwise
 pub fn dvm_versions() -> PathBuf {
-  let mut home = dvm_root();
-  home.push(DVM_CACHE_PATH_PREFIX);
-  home
+  dvm_system_root().unwrap_or_else(dvm_root).join(DVM_CACHE_PATH_PREFIX)
+}
+
+pub fn deno_version_dir(version: &Version) -> PathBuf {
+  dvm_versions().join(version.to_string())
 }
 
 pub fn deno_canary_path() -> PathBuf {
@@ -109,6 +327,64 @@ pub fn deno_canary_path() -> PathBuf {
   dvm_dir.join(DENO_EXE)
 }
 
+/// running inside the windows subsystem for linux
+pub fn is_wsl() -> bool {
+  cfg!(target_os = "linux")
+    && (env::var_os("WSL_DISTRO_NAME").is_some()
+      || read_to_string("/proc/sys/kernel/osrelease")
+        .map(|it| it.to_lowercase().contains("microsoft"))
+        .unwrap_or(false))
+}
+
+/// a windows executable seen from wsl, e.g. `/mnt/c/Users/me/.deno/bin/deno.exe`
+pub fn is_windows_mount(path: &Path) -> bool {
+  let mut components = path.components();
+  let on_drive = match (components.next(), components.next(), components.next()) {
+    (Some(Component::RootDir), Some(Component::Normal(mnt)), Some(Component::Normal(drive))) => {
+      mnt == "mnt" && drive.len() == 1 && drive.to_string_lossy().chars().all(|c| c.is_ascii_alphabetic())
+    }
+    _ => false,
+  };
+  on_drive || path.extension().is_some_and(|it| it.eq_ignore_ascii_case("exe"))
+}
+
+///
+/// the deno found in PATH, inside wsl the ones of the windows drives are skipped,
+/// dvm would manage a windows binary from linux otherwise.
+pub fn which_deno() -> Option<PathBuf> {
+  static WARNED: Once = Once::new();
+  let wsl = is_wsl();
+  which::which_all("deno").ok()?.find(|it| {
+    let skipped = wsl && is_windows_mount(it);
+    if skipped {
+      WARNED.call_once(|| {
+        output::warn(t!(
+          "{} is a Windows deno, it's ignored inside WSL in favor of dvm's Linux deno",
+          it.display()
+        ))
+      });
+    }
+    !skipped
+  })
+}
+
+///
+/// the deno found in PATH when it's not one dvm manages,
+/// e.g. installed by homebrew or scoop, dvm never touches it.
+pub fn external_deno() -> Option<PathBuf> {
+  let found = which_deno()?;
+  let resolved = found.canonicalize().unwrap_or_else(|_| found.clone());
+  let owned = [
+    deno_bin_path().parent().unwrap().to_path_buf(),
+    dvm_root(),
+    dvm_versions(),
+  ];
+  let is_owned = owned
+    .iter()
+    .any(|dir| found.starts_with(dir) || resolved.starts_with(dir.canonicalize().unwrap_or_else(|_| dir.clone())));
+  (!is_owned).then_some(found)
+}
+
 /// CGQAQ: Put hardlink to executable to this file,
 ///        and prepend this folder to env when dvm activated.
 pub fn deno_bin_path() -> PathBuf {
@@ -116,9 +392,37 @@ pub fn deno_bin_path() -> PathBuf {
   dvm_bin_dir.join(DENO_EXE)
 }
 
+/// the executable the shim in the dvm bin directory runs, with `activation = "shim"`
+pub fn shim_target_path() -> PathBuf {
+  deno_bin_path().with_file_name(DVM_SHIM_TARGET_FILENAME)
+}
+
+/// the executable the dvm bin directory runs, the one behind the shim if it is one
+pub fn active_bin_path() -> PathBuf {
+  let bin_path = deno_bin_path();
+  match read_to_string(shim_target_path()) {
+    Ok(target) if bin_path.exists() => PathBuf::from(target.trim_end()),
+    _ => bin_path,
+  }
+}
+
 pub fn deno_version_path(version: &Version) -> PathBuf {
-  let dvm_dir = dvm_root().join(format!("{}/{}", DVM_CACHE_PATH_PREFIX, version));
-  dvm_dir.join(DENO_EXE)
+  deno_version_dir(version).join(DENO_EXE)
+}
+
+/// the build of a version for another architecture of this os, kept in a directory of its own next to the native one
+pub fn deno_target_path(version: &Version, target: &str) -> PathBuf {
+  deno_version_dir(version).join(target).join(DENO_EXE)
+}
+
+/// the `denort` runtime used by `deno compile`, stored next to deno
+pub fn denort_version_path(version: &Version) -> PathBuf {
+  deno_version_dir(version).join(DENORT_EXE)
+}
+
+/// the isolated `DENO_DIR` of a given version
+pub fn deno_dir_path(version: &str) -> PathBuf {
+  dvm_cache_root().join(DVM_DENO_DIR_PATH_PREFIX).join(version)
 }
 
 #[inline]
@@ -145,21 +449,170 @@ cfg_if! {
 
       String::from_utf16_lossy(&buf).starts_with("zh-CN")
     }
+
+    /// the executable name of the process that started dvm, e.g. `pwsh.exe`
+    pub fn parent_process_name() -> Option<String> {
+      use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+      use winapi::um::processthreadsapi::GetCurrentProcessId;
+      use winapi::um::tlhelp32::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+      };
+
+      // SAFETY: Call `winapi` raw binding to win32 api, the snapshot is closed once it's been walked.
+      let processes = unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+          return None;
+        }
+        let mut processes = Vec::new();
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+        let mut more = Process32FirstW(snapshot, &mut entry) != 0;
+        while more {
+          let name = &entry.szExeFile;
+          let len = name.iter().position(|it| *it == 0).unwrap_or(name.len());
+          processes.push((
+            entry.th32ProcessID,
+            entry.th32ParentProcessID,
+            String::from_utf16_lossy(&name[..len]),
+          ));
+          more = Process32NextW(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+        processes
+      };
+
+      // SAFETY: Call `winapi` raw binding to win32 api.
+      let pid = unsafe { GetCurrentProcessId() };
+      let &(_, parent, _) = processes.iter().find(|(id, _, _)| *id == pid)?;
+      processes
+        .into_iter()
+        .find(|(id, _, _)| *id == parent)
+        .map(|(_, _, name)| name)
+    }
   } else {
     pub fn is_china_mainland() -> bool {
       env::var("LANG").map(|lng| lng.starts_with("zh_CN.")).unwrap_or(false)
     }
+
+    /// only windows has no `SHELL` to tell the shell
+    pub fn parent_process_name() -> Option<String> {
+      None
+    }
   }
 }
 
+///
+/// quote a value for a posix shell profile, e.g. a home directory with spaces,
+/// windows profiles take the value as is.
+pub fn shell_quote(value: &str) -> String {
+  if cfg!(windows) || (!value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || "/._-+:@%".contains(c))) {
+    value.to_string()
+  } else {
+    format!("'{}'", value.replace('\'', r"'\''"))
+  }
+}
+
+/// edit distance between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+  let b = b.chars().collect::<Vec<_>>();
+  let mut row = (0..=b.len()).collect::<Vec<_>>();
+  for (i, ca) in a.chars().enumerate() {
+    let mut previous = row[0];
+    row[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let current = row[j + 1];
+      row[j + 1] = if ca == *cb {
+        previous
+      } else {
+        1 + previous.min(row[j]).min(row[j + 1])
+      };
+      previous = current;
+    }
+  }
+  row[b.len()]
+}
+
+///
+/// the candidates close enough to what the user typed, closest first,
+/// at most 3 of them so the hint stays readable.
+pub fn did_you_mean<'a, T>(input: &str, candidates: T) -> Vec<String>
+where
+  T: IntoIterator<Item = &'a str>,
+{
+  let threshold = (input.chars().count() / 3).max(1);
+  let mut matches = candidates
+    .into_iter()
+    .filter(|it| *it != input)
+    .map(|it| (levenshtein(input, it), it))
+    .filter(|(distance, _)| *distance <= threshold)
+    .collect::<Vec<_>>();
+  matches.sort();
+  matches.dedup();
+  matches.into_iter().take(3).map(|(_, it)| it.to_string()).collect()
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
   use semver::VersionReq;
 
+  #[test]
+  #[cfg(unix)]
+  fn test_shell_quote() {
+    assert_eq!(shell_quote("/home/deno/.dvm"), "/home/deno/.dvm");
+    assert_eq!(shell_quote("/home/John Doe/.dvm"), "'/home/John Doe/.dvm'");
+    assert_eq!(shell_quote("/home/O'Hara/.dvm"), r"'/home/O'\''Hara/.dvm'");
+  }
+
+  #[test]
+  fn test_normalize_version() {
+    assert_eq!(normalize_version("v1.43.5"), "1.43.5");
+    assert_eq!(normalize_version(" 1.43.5 \n"), "1.43.5");
+    assert_eq!(normalize_version("deno 1.43.5"), "1.43.5");
+    assert_eq!(normalize_version("~1.28"), "~1.28");
+    assert_eq!(normalize_version("v1.28"), "1.28");
+    assert_eq!(normalize_version("vscode"), "vscode");
+    assert!(is_exact_version("v1.43.5"));
+  }
+
+  #[test]
+  fn test_is_windows_mount() {
+    assert!(is_windows_mount(Path::new("/mnt/c/Users/me/.deno/bin/deno.exe")));
+    assert!(is_windows_mount(Path::new("/mnt/d/tools/deno")));
+    assert!(!is_windows_mount(Path::new("/mnt/data/deno")));
+    assert!(!is_windows_mount(Path::new("/home/me/.dvm/bin/deno")));
+  }
+
+  #[test]
+  fn test_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+    assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00");
+    assert_eq!(format_timestamp(1668359591), "2022-11-13 17:13:11");
+  }
+
+  #[test]
+  fn test_did_you_mean() {
+    assert_eq!(levenshtein("1.43.9", "1.43.5"), 1);
+    assert_eq!(levenshtein("", "abc"), 3);
+    let versions = ["1.43.5", "1.42.0", "1.30.0", "canary"];
+    assert_eq!(did_you_mean("1.43.9", versions), vec!["1.43.5", "1.42.0"]);
+    assert_eq!(did_you_mean("canry", versions), vec!["canary"]);
+    assert!(did_you_mean("latest", versions).is_empty());
+  }
+
+  #[test]
+  fn test_select_xdg_layout() {
+    assert!(select_xdg_layout(Some("xdg".to_string()), true, false));
+    assert!(!select_xdg_layout(Some("classic".to_string()), false, true));
+    assert!(select_xdg_layout(None, false, true));
+    assert!(!select_xdg_layout(None, true, true));
+    assert!(!select_xdg_layout(None, false, false));
+  }
+
   #[test]
   fn test_best_version() {
-    let versions = vec![
+    let versions = [
       "0.8.5",
       "0.8.0",
       "0.9.0",
@@ -1,15 +1,21 @@
 // Copyright 2022 justjavac. All rights reserved. MIT license.
-use crate::consts::{DVM_CACHE_PATH_PREFIX, REGISTRY_LATEST_CANARY_PATH, REGISTRY_LATEST_RELEASE_PATH};
-use crate::utils::{dvm_root, is_china_mainland, is_exact_version, is_semver};
-use anyhow::Result;
+use crate::consts::{DVM_RELEASES_FILENAME, DVM_RELEASES_URL, DVM_UPDATE_CHECK_INTERVAL_HOURS};
+use crate::utils::{
+  dvm_cache_root, dvm_versions, is_china_mainland, is_exact_version, is_semver, normalize_version, which_deno,
+};
+use crate::{http, http_cache, source};
+use anyhow::{bail, Result};
 use json_minimal::Json;
 use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Formatter;
-use std::fs::read_dir;
+use std::fs::{self, read_dir};
 use std::path::Path;
 use std::process::{Command, Stdio};
 use std::str::FromStr;
 use std::string::String;
+use std::time::{Duration, SystemTime};
 
 pub const DVM: &str = env!("CARGO_PKG_VERSION");
 
@@ -32,6 +38,7 @@ impl FromStr for VersionArg {
   type Err = ();
 
   fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    let s = normalize_version(s);
     if is_exact_version(s) {
       Version::parse(s).map(VersionArg::Exact).map_err(|_| ())
     } else {
@@ -43,22 +50,23 @@ impl FromStr for VersionArg {
 }
 
 pub fn current_version() -> Option<String> {
-  match Command::new("deno").arg("-V").stderr(Stdio::inherit()).output() {
-    Ok(output) => {
-      assert!(output.status.success());
-      match String::from_utf8(output.stdout) {
-        Ok(stdout) => Some(stdout.trim()[5..].to_string()),
-        Err(_) => None,
-      }
-    }
-    Err(_) => None,
+  version_of(&which_deno()?)
+}
+
+/// the version `deno -V` of an executable prints
+pub fn version_of(deno: &Path) -> Option<String> {
+  let output = Command::new(deno).arg("-V").stderr(Stdio::inherit()).output().ok()?;
+  if !output.status.success() {
+    return None;
   }
+  let stdout = String::from_utf8(output.stdout).ok()?;
+  stdout.trim().strip_prefix("deno ").map(str::to_string)
 }
 
 pub fn local_versions() -> Vec<String> {
   let mut v: Vec<String> = Vec::new();
 
-  if let Ok(entries) = read_dir(dvm_root().join(Path::new(DVM_CACHE_PATH_PREFIX))) {
+  if let Ok(entries) = read_dir(dvm_versions()) {
     for entry in entries.flatten() {
       if let Ok(file_type) = entry.file_type() {
         if file_type.is_dir() {
@@ -76,8 +84,10 @@ pub fn local_versions() -> Vec<String> {
 
 pub fn remote_versions() -> Result<Vec<String>> {
   if is_china_mainland() {
-    let response = tinyget::get("https://cdn.jsdelivr.net/gh/denoland/dotland@main/versions.json").send()?;
-    let body = response.as_str()?;
+    let body = http::block_on(async {
+      let response = http_cache::get("https://cdn.jsdelivr.net/gh/denoland/dotland@main/versions.json").await?;
+      response.text().await
+    })?;
     let json = Json::parse(body.as_bytes()).unwrap();
     let mut result: Vec<String> = Vec::new();
 
@@ -94,10 +104,10 @@ pub fn remote_versions() -> Result<Vec<String>> {
     return Ok(result);
   }
 
-  let response = tinyget::get("https://api.github.com/repos/denoland/deno/tags")
-    .with_header("User-Agent", "tinyget") // http://developer.github.com/v3/#user-agent-required
-    .send()?;
-  let body = response.as_str()?;
+  let body = http::block_on(async {
+    let response = http_cache::get("https://api.github.com/repos/denoland/deno/tags").await?;
+    response.text().await
+  })?;
   let json = Json::parse(body.as_bytes()).unwrap();
   let mut result: Vec<String> = Vec::new();
 
@@ -114,18 +124,106 @@ pub fn remote_versions() -> Result<Vec<String>> {
   Ok(result)
 }
 
-pub fn get_latest_version(registry: &str) -> Result<Version> {
-  let response = tinyget::get(format!("{}{}", registry, REGISTRY_LATEST_RELEASE_PATH)).send()?;
+/// when and on which channel a version was published
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+  pub version: String,
+  /// `YYYY-MM-DD`
+  pub date: String,
+  /// `stable` or `rc`
+  pub channel: String,
+}
+
+/// the fields of the GitHub releases API that matter here
+#[derive(Deserialize)]
+struct GithubRelease {
+  tag_name: String,
+  published_at: Option<String>,
+  prerelease: bool,
+}
+
+const RELEASES_PER_PAGE: usize = 100;
+
+async fn fetch_releases() -> Result<Vec<Release>> {
+  let mut releases = Vec::new();
+  for page in 1.. {
+    let url = format!("{}?per_page={}&page={}", DVM_RELEASES_URL, RELEASES_PER_PAGE, page);
+    let response = http_cache::get(&url).await?;
+    if !response.is_success() {
+      bail!("{} responded with {}", url, response.status());
+    }
+    let list: Vec<GithubRelease> = serde_json::from_str(&response.text().await?)?;
+    let count = list.len();
+    releases.extend(list.into_iter().filter_map(|it| {
+      let version = Version::parse(it.tag_name.trim_start_matches('v')).ok()?;
+      let channel = if it.prerelease || !version.pre.is_empty() {
+        "rc"
+      } else {
+        "stable"
+      };
+      Some(Release {
+        version: version.to_string(),
+        date: it.published_at?.chars().take(10).collect(),
+        channel: channel.to_string(),
+      })
+    }));
+    if count < RELEASES_PER_PAGE {
+      break;
+    }
+  }
+  Ok(releases)
+}
 
-  let body = response.as_str()?;
+///
+/// publish dates and channels by version, cached for a day in `releases.json`,
+/// a stale cache is still used when the releases API can't be reached.
+pub fn releases() -> Result<HashMap<String, Release>> {
+  let path = dvm_cache_root().join(DVM_RELEASES_FILENAME);
+  let interval = Duration::from_secs(DVM_UPDATE_CHECK_INTERVAL_HOURS * 60 * 60);
+  let fresh = fs::metadata(&path)
+    .and_then(|it| it.modified())
+    .ok()
+    .and_then(|it| SystemTime::now().duration_since(it).ok())
+    .map(|elapsed| elapsed < interval)
+    .unwrap_or(false);
+  let cached = fs::read_to_string(&path)
+    .ok()
+    .and_then(|it| serde_json::from_str::<Vec<Release>>(&it).ok());
+
+  let releases = match cached {
+    Some(cached) if fresh => cached,
+    cached => match http::block_on(fetch_releases()) {
+      Ok(releases) => {
+        // the cache only saves requests, failing to write it is fine
+        if fs::create_dir_all(dvm_cache_root()).is_ok() {
+          fs::write(&path, serde_json::to_string(&releases)?).ok();
+        }
+        releases
+      }
+      Err(err) => cached.ok_or(err)?,
+    },
+  };
+  Ok(releases.into_iter().map(|it| (it.version.clone(), it)).collect())
+}
+
+pub fn get_latest_version(registry: &str) -> Result<Version> {
+  let body = http::block_on(async {
+    http_cache::get(&source::latest_release_url(registry))
+      .await?
+      .text()
+      .await
+  })?;
   let v = body.trim().replace('v', "");
   Ok(Version::parse(&v).unwrap())
 }
 
 pub fn get_latest_canary(registry: &str) -> Result<String> {
-  let response = tinyget::get(format!("{}{}", registry, REGISTRY_LATEST_CANARY_PATH)).send()?;
-
-  let body = response.as_str()?;
+  let body = http::block_on(async {
+    http_cache::get(&source::latest_canary_url(registry))
+      .await?
+      .text()
+      .await
+  })?;
   let v = body.trim().replace('v', "");
   Ok(v)
 }
@@ -147,3 +245,44 @@ where
       .max(),
   )
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::consts::REGISTRY_OFFICIAL;
+  use crate::http::{with_transport, MockTransport};
+
+  #[test]
+  fn test_get_latest_version() {
+    let mock = MockTransport::default().with(&source::latest_release_url(REGISTRY_OFFICIAL), 200, "v1.28.0\n");
+    let latest = with_transport(mock, || get_latest_version(REGISTRY_OFFICIAL).unwrap());
+    assert_eq!(latest, Version::parse("1.28.0").unwrap());
+  }
+
+  #[test]
+  fn test_fetch_releases() {
+    let body = r#"[
+      {"tag_name": "v1.29.0-rc.1", "published_at": "2022-12-13T18:02:41Z", "prerelease": true},
+      {"tag_name": "v1.28.0", "published_at": "2022-11-13T17:23:11Z", "prerelease": false},
+      {"tag_name": "std/0.1.0", "published_at": "2019-02-05T10:00:00Z", "prerelease": false}
+    ]"#;
+    let url = format!("{}?per_page={}&page=1", DVM_RELEASES_URL, RELEASES_PER_PAGE);
+    let mock = MockTransport::default().with(&url, 200, body);
+    let releases = with_transport(mock, || http::block_on(fetch_releases()).unwrap());
+    assert_eq!(
+      releases,
+      vec![
+        Release {
+          version: "1.29.0-rc.1".to_string(),
+          date: "2022-12-13".to_string(),
+          channel: "rc".to_string(),
+        },
+        Release {
+          version: "1.28.0".to_string(),
+          date: "2022-11-13".to_string(),
+          channel: "stable".to_string(),
+        },
+      ]
+    );
+  }
+}